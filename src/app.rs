@@ -1,115 +1,249 @@
-use std::collections::HashMap;
-
-use crossbeam::channel::{unbounded, Receiver, Sender};
-use egui::{CentralPanel, Pos2, SidePanel, Vec2};
-use egui::{Context, InputState, Stroke, Style, Ui};
+use std::collections::HashSet;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crossbeam::channel::{unbounded, Receiver, Sender, TryRecvError};
+use egui::{CentralPanel, SidePanel, TopBottomPanel};
+use egui::{Context, InputState, Style, Ui};
 use egui_graphs::events::{Event, PayloadNodeSelect};
-use egui_graphs::{add_node_custom, Graph, Node};
-use fdg_sim::glam::Vec3;
-use fdg_sim::{ForceGraph, Simulation, SimulationParameters};
 use log::error;
 use log::info;
-use petgraph::{
-    stable_graph::{NodeIndex, StableGraph},
-    Directed,
-};
-use rand::Rng;
-use reqwest::Error;
-use tokio::task::JoinHandle;
-
-use crate::cursor::Cursor;
+use petgraph::stable_graph::NodeIndex;
+
+use crate::command::Command;
+use crate::config::Config;
+use crate::diff::{self, Diff};
+use crate::dump::ImportedGraph;
+use crate::exploration::Exploration;
+use crate::export;
+use crate::keymap::{captured_binding, Binding, Keymap};
+use crate::link_source::{Error, LinkSource, SharedSource};
+use crate::metrics::Metric;
+use crate::revision::{self, Timeline, REVISIONS_LIMIT};
+use crate::session::{self, Session};
+use crate::views::communities::{self as communities_view, draw_view_communities};
+use crate::views::comparison::{self as comparison_view, draw_view_comparison};
+use crate::views::disambiguation::{self as disambiguation_view, draw_view_disambiguation};
+use crate::views::entities::{self as entities_view, draw_view_entities};
+use crate::views::export::{self as export_view, draw_view_export};
 use crate::views::graph::{self, draw_view_graph};
 use crate::views::input::draw_view_input;
-use crate::views::style::{
-    COLOR_ACCENT, COLOR_LEFT_LOW, COLOR_RIGHT_LOW, COLOR_SUB_ACCENT, CURSOR_WIDTH,
-};
+use crate::views::legend::{self, draw_view_legend};
+use crate::views::link_search::{self, draw_view_link_search};
+use crate::views::metrics::{self as metrics_view, draw_view_metrics, Encoding};
+use crate::views::missing::{self as missing_view, draw_view_missing};
+use crate::views::node_shape;
+use crate::views::palette::{self, draw_view_palette};
+use crate::views::prune::{self, draw_view_prune};
+use crate::views::settings::{self, draw_view_settings};
+use crate::views::snapshots::{self as snapshots_view, draw_view_snapshots, Against};
+use crate::views::tabs::{self as tabs_view, draw_view_tabs};
+use crate::views::timeline::{self as timeline_view, draw_view_timeline};
 use crate::views::toolbox::{self, draw_view_toolbox};
 use crate::{
-    node,
     state::{next, Fork, State},
     url::{self, Url},
-    url_retriever::UrlRetriever,
+    url_retriever::{Retrieval, WebSource},
+    utils,
 };
 
-const SIMULATION_DT: f32 = 0.035;
-const EDGE_WEIGHT: f32 = 0.1;
-const COOL_OFF: f32 = 0.5;
-const SCALE: f32 = 50.;
-
-type ActiveTasks = HashMap<NodeIndex, (Receiver<Result<Url, Error>>, JoinHandle<()>)>;
-
 pub struct App {
-    root_article_url: String,
-    state: State,
+    /// Date entered with the article the next exploration starts from.
+    as_of_input: String,
 
     style: Style,
 
-    active_tasks: ActiveTasks,
+    /// Source shared by all the tabs.
+    source: Arc<SharedSource>,
+
+    /// Explorations in the order their tabs are shown.
+    tabs: Vec<Exploration>,
+    active_tab: usize,
+
+    /// Media nodes are drawn as their thumbnails.
+    show_thumbnails: bool,
+    /// Nodes and edges out of the neighbourhood of the hovered or selected node are faded.
+    highlight_neighbourhood: bool,
+
+    /// Metric by which top nodes table is sorted.
+    metrics_sort_by: Metric,
+    encoded_metric: Metric,
+    metric_encoding: Encoding,
 
-    g: Graph<node::Node, (), Directed>,
-    sim: Simulation<(), f32>,
+    color_by_community: bool,
 
-    selected_node: Option<NodeIndex>,
+    /// Expanding an article retrieves its categories instead of its links.
+    category_mode: bool,
 
-    cursor: Option<Cursor>,
+    color_by_class: bool,
 
     changes_sender: Sender<Event>,
     changes_receiver: Receiver<Event>,
 
-    node_by_url: HashMap<Url, NodeIndex>,
+    config: Config,
+
+    palette_open: bool,
+    palette_query: String,
+
+    settings_open: bool,
+
+    export_open: bool,
+    export_path: String,
+    export_options: export::Options,
+    export_whole_graph: bool,
+    /// Result of the last export shown in the export window.
+    export_message: Option<String>,
+
+    compare_open: bool,
+    /// Languages to compare the selected article in, separated by spaces or commas.
+    compare_langs: String,
+
+    snapshots_open: bool,
+    /// Sessions saved to the snapshots directory, the oldest first.
+    snapshots: Vec<(PathBuf, Session)>,
+    snapshot_old: Option<usize>,
+    snapshot_against: Against,
+    /// Compared sessions, the older and the newer one, with their differences.
+    snapshot_diff: Option<(Session, Session, Diff)>,
+    /// Older session with its re-crawl.
+    recrawl_task: Option<Receiver<Result<(Session, Session), Error>>>,
+    /// Result of the last save shown in the snapshots window.
+    snapshot_message: Option<String>,
+
+    timeline_open: bool,
+    /// Number of the latest revisions the timeline is built from.
+    timeline_limit: usize,
+    timeline_task: Option<Receiver<Result<Timeline, Error>>>,
+    /// Command waiting for the user to press a new key binding.
+    rebinding: Option<Command>,
 }
 
 impl Default for App {
+    /// Uses the saved config and the source it names, the web if it can not be opened.
     fn default() -> Self {
-        let mut style = Style::default();
-        style.visuals.selection.stroke = Stroke::new(1., COLOR_ACCENT);
-        style.visuals.selection.bg_fill = COLOR_SUB_ACCENT;
+        let config = Config::load();
+        let source = config.source.open().unwrap_or_else(|err| {
+            error!("failed to open configured source, using web: {}", err);
+            Arc::new(WebSource::default())
+        });
+
+        Self::new(config, source)
+    }
+}
 
+impl App {
+    pub fn new(config: Config, source: Arc<dyn LinkSource>) -> Self {
         let (changes_sender, changes_receiver) = unbounded();
-        let sim = construct_simulation();
 
-        let g = Graph::new(StableGraph::new());
+        let mut style = Style::default();
+        config.theme.apply(&mut style);
+
+        let source = Arc::new(SharedSource::new(source));
 
         App {
             style,
             changes_sender,
             changes_receiver,
-            sim,
-            g,
 
-            root_article_url: Default::default(),
-            state: Default::default(),
-            active_tasks: Default::default(),
-            selected_node: Default::default(),
-            node_by_url: Default::default(),
-            cursor: Default::default(),
+            as_of_input: Default::default(),
+            tabs: vec![Exploration::new(source.clone())],
+            source,
+            active_tab: Default::default(),
+            show_thumbnails: Default::default(),
+            highlight_neighbourhood: true,
+            metrics_sort_by: Default::default(),
+            encoded_metric: Default::default(),
+            metric_encoding: Default::default(),
+            color_by_community: Default::default(),
+            category_mode: Default::default(),
+            color_by_class: Default::default(),
+            config,
+            palette_open: Default::default(),
+            palette_query: Default::default(),
+            settings_open: Default::default(),
+            export_open: Default::default(),
+            export_path: "graph.png".to_string(),
+            export_options: Default::default(),
+            export_whole_graph: Default::default(),
+            export_message: Default::default(),
+            compare_open: Default::default(),
+            compare_langs: Default::default(),
+            snapshots_open: Default::default(),
+            snapshots: Default::default(),
+            snapshot_old: Default::default(),
+            snapshot_against: Against::Graph,
+            snapshot_diff: Default::default(),
+            recrawl_task: Default::default(),
+            snapshot_message: Default::default(),
+            timeline_open: Default::default(),
+            timeline_limit: REVISIONS_LIMIT,
+            timeline_task: Default::default(),
+            rebinding: Default::default(),
         }
     }
-}
 
-impl App {
     pub fn update(&mut self, ctx: &Context) {
         ctx.set_style(self.style.clone());
 
-        self.handle_state();
+        // keys pressed while the palette is open or while typing belong to the text input
+        let keys_captured = self.palette_open || ctx.wants_keyboard_input();
+
+        self.handle_state_graph();
+        self.process_tabs(ctx);
+        self.process_recrawl_task();
+        self.process_timeline_task();
         self.draw(ctx);
-        self.handle_keys(ctx);
+        self.draw_overlays(ctx);
+        self.handle_keys(ctx, keys_captured);
+
+        let tab = &mut self.tabs[self.active_tab];
+        tab.metrics.update(&tab.g);
+        tab.communities.update(&tab.g, &tab.metrics);
+        tab.apply_metric_encoding(
+            self.encoded_metric,
+            self.metric_encoding,
+            &self.config.theme,
+        );
+        tab.apply_communities(self.color_by_community);
+        tab.apply_entities(self.color_by_class);
+        tab.apply_disambiguations(self.config.disambiguation);
+        tab.apply_timeline(ctx);
+        tab.apply_thumbnails(self.show_thumbnails);
+        tab.apply_labels();
+        tab.apply_neighbourhood(ctx, self.highlight_neighbourhood);
+
+        tab.layout.sync(&mut tab.g);
+        tab.pin_collapsed_communities();
+        tab.pin_collapsed_children();
+        tab.layout.update();
+    }
 
-        sync_graph_with_simulation(&mut self.g, &mut self.sim);
-        update_simulation(&mut self.sim);
+    /// Receives what the tasks of every tab retrieved, whether the tab is shown or not.
+    fn process_tabs(&mut self, ctx: &Context) {
+        let show_thumbnails = self.show_thumbnails;
+        self.tabs.iter_mut().for_each(|tab| {
+            tab.handle_state();
+            tab.process_summary_tasks();
+            tab.process_thumbnail_tasks(ctx, show_thumbnails);
+            tab.process_comparison_task();
+            tab.process_choice_task();
+            tab.process_entities();
+            tab.process_disambiguations();
+        });
     }
 
-    fn handle_state(&mut self) {
-        match self.state {
-            State::GraphAndLoading => self.handle_state_graph_and_loading(),
-            State::GraphLoaded => self.handle_state_graph_loaded(),
-            State::Graph => self.handle_state_graph(),
-            State::GraphAndLoadingError | State::Input | State::InputError => (),
-        }
+    fn tab(&self) -> &Exploration {
+        &self.tabs[self.active_tab]
+    }
+
+    fn tab_mut(&mut self) -> &mut Exploration {
+        &mut self.tabs[self.active_tab]
     }
 
     fn draw(&mut self, ctx: &Context) {
-        match self.state {
+        self.draw_tabs(ctx);
+        match self.tab().state {
             State::Input => self.draw_input(ctx),
             State::InputError => self.draw_input_error(ctx),
             State::GraphAndLoading => self.draw_graph_and_loading(ctx),
@@ -118,213 +252,493 @@ impl App {
         }
     }
 
-    fn handle_state_graph_loaded(&mut self) {
-        if self.cursor.is_none() {
-            let first_root = NodeIndex::new(0);
-            self.cursor = Some(Cursor::new(first_root, &self.g));
-            self.select_node(first_root)
-        } else {
-            self.cursor
-                .as_mut()
-                .unwrap()
-                .update(self.selected_node.unwrap(), &self.g);
+    /// Selects the node clicked in the graph of the shown tab.
+    fn handle_state_graph(&mut self) {
+        if self.tab().state != State::Graph {
+            return;
+        }
+        if let Ok(Event::NodeSelect(PayloadNodeSelect { id })) = self.changes_receiver.try_recv() {
+            self.tab_mut().select_node(NodeIndex::new(id));
         }
-
-        self.state = next(&self.state, Fork::Success)
     }
 
-    fn handle_state_graph(&mut self) {
-        if let Ok(Event::NodeSelect(PayloadNodeSelect { id })) = self.changes_receiver.try_recv() {
-            self.select_node(NodeIndex::new(id));
+    fn handle_keys(&mut self, ctx: &Context, keys_captured: bool) {
+        if self.rebinding.is_some() {
+            self.handle_keys_rebinding(ctx);
+            return;
         }
+
+        if keys_captured {
+            return;
+        }
+
+        ctx.input(|i| match self.tab().state {
+            State::Input => self.handle_keys_input(i),
+            State::InputError => (),
+            State::GraphAndLoading
+            | State::GraphAndLoadingError
+            | State::GraphLoaded
+            | State::Graph => self.handle_keys_graph(i),
+        });
     }
 
-    /// Checks for results from the url retriever for every active task. If any task is finished,
-    /// moves to the next state.
-    fn handle_state_graph_and_loading(&mut self) {
-        match self.process_active_tasks() {
-            Ok(_) => {
-                if self.active_tasks.is_empty() {
-                    info!("all tasks finished");
-                    self.state = next(&self.state, Fork::Success);
-                }
+    fn handle_keys_rebinding(&mut self, ctx: &Context) {
+        let cmd = self.rebinding.unwrap();
+        if let Some(binding) = ctx.input(captured_binding) {
+            self.rebinding = None;
+
+            if binding == Binding::key(egui::Key::Escape) {
+                info!("canceled rebinding of {:?}", cmd);
+                return;
             }
-            Err(err) => {
-                error!("error while checking active tasks: {}", err);
-                self.state = next(&self.state, Fork::Failure);
-            }
-        }
-    }
-
-    /// Processes results from the url retriever for every active task.
-    ///
-    /// Updates the graph with the retrieved urls.
-    ///
-    /// If any task is finished, removes it from the active tasks.
-    ///
-    /// If we got any url, function returns true, otherwise false. If an error was got function returns error.
-    fn process_active_tasks(&mut self) -> Result<(), Error> {
-        let mut finished_tasks = Vec::new();
-        self.active_tasks
-            .iter()
-            .for_each(
-                |(parent_idx, (receiver, join_handle))| match receiver.try_recv() {
-                    Ok(result) => match result {
-                        Ok(url) => {
-                            info!("got new url from the retriver: {}", url.val());
-
-                            let parent_loc = self.g.g.node_weight(*parent_idx).unwrap().location();
-
-                            match self.node_by_url.get(&url) {
-                                Some(idx) => {
-                                    add_edge(&mut self.g, &mut self.sim, *parent_idx, *idx);
-                                }
-                                None => {
-                                    let idx = add_node(
-                                        &mut self.g,
-                                        &mut self.sim,
-                                        parent_loc,
-                                        &node::Node::new(url.clone()),
-                                    );
-                                    self.node_by_url.insert(url, idx);
-                                    add_edge(&mut self.g, &mut self.sim, *parent_idx, idx);
-                                }
-                            };
-                        }
-                        Err(err) => {
-                            error!("got error from the retriver: {}", err);
-                        }
-                    },
 
-                    Err(_) => {
-                        if join_handle.is_finished() {
-                            finished_tasks.push(*parent_idx);
-                        }
-                    }
-                },
-            );
+            info!("rebinding {:?} to {}", cmd, binding);
+            self.config.keymap.rebind(cmd, binding);
+            self.config.save();
+        }
+    }
 
-        finished_tasks.iter().for_each(|finished| {
+    /// Single entry point for every user action, whether it came from the keymap or the palette.
+    fn dispatch(&mut self, cmd: Command) {
+        let tab = self.tab();
+        if cmd.needs_graph() && (tab.state != State::Graph || tab.selected_node.is_none()) {
             info!(
-                "task finished; received all children urls for: {}",
-                self.g
-                    .g
-                    .node_weight(*finished)
-                    .unwrap()
-                    .payload()
-                    .url()
-                    .val()
+                "command {:?} is not available in state {:?}",
+                cmd, tab.state
             );
-            self.active_tasks.remove(finished);
-        });
+            return;
+        }
 
-        Ok(())
+        info!("dispatching command {:?}", cmd);
+        match cmd {
+            Command::NextChild => {
+                self.tab_mut().select_next();
+            }
+            Command::PrevChild => {
+                self.tab_mut().select_prev();
+            }
+            Command::NextArticle => self.tab_mut().select_next_article(),
+            Command::PrevArticle => self.tab_mut().select_prev_article(),
+            Command::NextRoot => self.tab_mut().select_next_root(),
+            Command::PrevRoot => self.tab_mut().select_prev_root(),
+            Command::Expand => self.expand_selected(None),
+            Command::ExpandBacklinks => self.expand_selected(Some(Retrieval::Backlinks)),
+            Command::DeleteNode => {
+                let tab = self.tab_mut();
+                let idx = tab.selected_node.unwrap();
+                tab.remove_nodes(HashSet::from([idx]));
+            }
+            Command::DeleteSubtree => {
+                let tab = self.tab_mut();
+                let idx = tab.selected_node.unwrap();
+                tab.remove_nodes(tab.detached_by(idx));
+            }
+            Command::CollapseChildren => {
+                let tab = self.tab_mut();
+                let idx = tab.selected_node.unwrap();
+                tab.toggle_collapsed_children(idx);
+            }
+            Command::PruneLeaves => {
+                let tab = self.tab_mut();
+                tab.remove_nodes(tab.leaves());
+            }
+            Command::Undo => self.tab_mut().undo(),
+            Command::Redo => self.tab_mut().redo(),
+            Command::ToggleCategoryMode => self.category_mode = !self.category_mode,
+            Command::ToggleThumbnails => self.show_thumbnails = !self.show_thumbnails,
+            Command::ToggleNeighbourhood => {
+                self.highlight_neighbourhood = !self.highlight_neighbourhood
+            }
+            Command::OpenUrl => {
+                let tab = self.tab();
+                let idx = tab.selected_node.unwrap();
+                let n = tab.g.g.node_weight(idx).unwrap().payload();
+                match n.revision() {
+                    Some(id) => utils::url::open_url(&revision::url(n.url(), id)),
+                    None => utils::url::open_url(n.url().val()),
+                }
+            }
+            Command::CommandPalette => self.palette_open = !self.palette_open,
+            Command::Settings => self.settings_open = !self.settings_open,
+            Command::Export => self.export_open = !self.export_open,
+            Command::NewExploration => self.tab_mut().new_exploration(),
+            Command::NewTab => self.new_tab(),
+            Command::CloseTab => self.close_tab(self.active_tab),
+            Command::OpenInNewTab => {
+                let tab = self.tab();
+                let idx = tab.selected_node.unwrap();
+                let url = tab.g.g.node_weight(idx).unwrap().payload().url().clone();
+                self.open_in_new_tab(url);
+            }
+            Command::CompareLanguages => self.compare_open = !self.compare_open,
+            Command::RevisionTimeline => self.timeline_open = !self.timeline_open,
+            Command::SaveSnapshot => self.save_snapshot(),
+            Command::Snapshots => {
+                self.snapshots_open = !self.snapshots_open;
+                if self.snapshots_open {
+                    self.snapshots = session::list(Path::new(session::DIR));
+                }
+            }
+        }
     }
 
-    fn handle_keys(&mut self, ctx: &Context) {
-        ctx.input(|i| match self.state {
-            State::Input => self.handle_keys_input(i),
-            State::InputError
-            | State::GraphAndLoading
-            | State::GraphAndLoadingError
-            | State::GraphLoaded => (),
-            State::Graph => self.handle_keys_graph(i),
-        });
+    fn handle_keys_graph(&mut self, i: &InputState) {
+        self.config
+            .keymap
+            .pressed(i)
+            .into_iter()
+            .for_each(|cmd| self.dispatch(cmd));
     }
 
-    fn select_node(&mut self, idx: NodeIndex) {
-        if let Some(selected) = self.selected_node {
-            let n = self.g.g.node_weight_mut(selected).unwrap();
-            n.set_selected(false);
+    fn draw_overlays(&mut self, ctx: &Context) {
+        if self.palette_open {
+            let picked = draw_view_palette(
+                ctx,
+                &mut self.palette_open,
+                palette::State {
+                    query: &mut self.palette_query,
+                    keymap: &self.config.keymap,
+                },
+            );
+            if let Some(cmd) = picked {
+                self.dispatch(cmd);
+            }
         }
 
-        let n = self.g.g.node_weight_mut(idx).unwrap();
-        n.set_selected(true);
-        self.selected_node = Some(idx);
-    }
-
-    fn select_next(&mut self) -> NodeIndex {
-        let cursor = self.cursor.as_mut().unwrap();
-        let next = cursor.next_child();
+        if self.compare_open {
+            let tab = &self.tabs[self.active_tab];
+            let article = tab
+                .selected_node
+                .map(|idx| tab.g.g.node_weight(idx).unwrap().payload())
+                .filter(|n| n.url().lang().is_some() && n.url().url_type() == url::Type::Article)
+                .map(|n| n.label());
+            let compare = draw_view_comparison(
+                ctx,
+                &mut self.compare_open,
+                comparison_view::State {
+                    article,
+                    langs: &mut self.compare_langs,
+                    loading: tab.comparison_task.is_some(),
+                    comparison: tab.comparison.as_ref(),
+                },
+            );
+            if compare {
+                let langs = self.compare_langs.clone();
+                self.tab_mut().compare_selected(&langs);
+            }
+        }
 
-        self.select_node(next);
+        let tab = &mut self.tabs[self.active_tab];
+        if let Some((idx, options)) = &tab.choosing {
+            let mut open = true;
+            let chosen = draw_view_disambiguation(
+                ctx,
+                &mut open,
+                disambiguation_view::State {
+                    page: tab.g.g.node_weight(*idx).unwrap().payload().label(),
+                    loading: tab.choice_task.is_some(),
+                    options,
+                },
+            );
+            if let Some(i) = chosen {
+                let (idx, url) = (*idx, options[i].url.clone());
+                tab.resolve_disambiguation(idx, url);
+                open = false;
+            }
+            if !open {
+                tab.choosing = None;
+                tab.choice_task = None;
+            }
+        }
 
-        next
-    }
+        if self.export_open {
+            self.draw_export(ctx);
+        }
 
-    fn select_prev(&mut self) -> NodeIndex {
-        let cursor = self.cursor.as_mut().unwrap();
-        let prev = cursor.prev_child();
+        if self.snapshots_open {
+            self.draw_snapshots(ctx);
+        }
 
-        self.select_node(prev);
+        if self.timeline_open {
+            let tab = &mut self.tabs[self.active_tab];
+            let article = tab
+                .selected_node
+                .map(|idx| tab.g.g.node_weight(idx).unwrap().payload())
+                .filter(|n| n.url().is_wiki() && n.url().url_type() == url::Type::Article)
+                .map(|n| n.label());
+            let load = draw_view_timeline(
+                ctx,
+                &mut self.timeline_open,
+                timeline_view::State {
+                    article,
+                    limit: &mut self.timeline_limit,
+                    loading: self.timeline_task.is_some(),
+                    timeline: tab.timeline.as_mut(),
+                    time: ctx.input(|i| i.time),
+                },
+            );
+            if load {
+                self.load_timeline();
+            }
+        }
 
-        prev
+        if self.settings_open {
+            let action = draw_view_settings(
+                ctx,
+                &mut self.settings_open,
+                settings::State {
+                    keymap: &self.config.keymap,
+                    rebinding: self.rebinding,
+                    disambiguation: self.config.disambiguation,
+                    theme: &mut self.config.theme,
+                    error: self.config.error.as_deref(),
+                },
+            );
+            match action {
+                Some(settings::Action::Rebind(cmd)) => self.rebinding = Some(cmd),
+                Some(settings::Action::Clear(cmd)) => {
+                    self.config.keymap.clear(cmd);
+                    self.config.save();
+                }
+                Some(settings::Action::Reset) => {
+                    self.config.keymap = Keymap::default();
+                    self.config.save();
+                }
+                Some(settings::Action::SaveTheme) => {
+                    self.config.theme.apply(&mut self.style);
+                    self.config.save();
+                }
+                Some(settings::Action::SetDisambiguation(handling)) => {
+                    self.config.disambiguation = handling;
+                    self.config.save();
+                }
+                None => (),
+            }
+        }
     }
 
-    fn select_next_article(&mut self) {
-        let cursor = self.cursor.as_mut().unwrap();
-        let mut next = || cursor.next_child();
-        let mut n = next();
-        loop {
-            if self.g.g.node_weight(n).unwrap().payload().url().url_type() == url::Type::Article {
-                break;
+    fn draw_snapshots(&mut self, ctx: &Context) {
+        let has_graph = self.tab().state == State::Graph;
+        let action = draw_view_snapshots(
+            ctx,
+            &mut self.snapshots_open,
+            snapshots_view::State {
+                sessions: &self.snapshots,
+                old: &mut self.snapshot_old,
+                against: &mut self.snapshot_against,
+                has_graph,
+                recrawling: self.recrawl_task.is_some(),
+                diff: self.snapshot_diff.as_ref().map(|(_, _, diff)| diff),
+                message: self.snapshot_message.as_deref(),
+            },
+        );
+
+        match action {
+            Some(snapshots_view::Action::Save) => self.save_snapshot(),
+            Some(snapshots_view::Action::Open(i)) => {
+                let session = self.snapshots[i].1.clone();
+                self.open_session(&session);
             }
-
-            n = next();
+            Some(snapshots_view::Action::Compare) => self.compare_snapshots(),
+            Some(snapshots_view::Action::Show) => self.show_snapshot_diff(),
+            None => (),
         }
-
-        self.select_node(n);
     }
 
-    fn select_prev_article(&mut self) {
-        let cursor = self.cursor.as_mut().unwrap();
-        let mut prev = || cursor.prev_child();
+    /// Saves articles and links of the shown graph, so they can be compared later.
+    fn save_snapshot(&mut self) {
+        let tab = self.tab();
+        let cursor = match (&tab.state, &tab.cursor) {
+            (State::Graph, Some(cursor)) => cursor,
+            _ => return,
+        };
 
-        let mut p = prev();
-        loop {
-            if self.g.g.node_weight(p).unwrap().payload().url().url_type() == url::Type::Article {
-                break;
+        let message = match Session::of(&tab.g, cursor).save(Path::new(session::DIR)) {
+            Ok(path) => {
+                info!("saved snapshot to {}", path.display());
+                format!("saved to {}", path.display())
+            }
+            Err(err) => {
+                error!("failed to save snapshot: {}", err);
+                format!("failed to save: {}", err)
             }
+        };
+        self.snapshot_message = Some(message);
+        self.snapshots = session::list(Path::new(session::DIR));
+    }
 
-            p = prev();
-        }
+    /// Compares the older snapshot with the chosen one, the shown graph or, once it is
+    /// retrieved, a fresh re-crawl of the expanded articles.
+    fn compare_snapshots(&mut self) {
+        let old = match self.snapshot_old {
+            Some(i) => self.snapshots[i].1.clone(),
+            None => return,
+        };
+        self.snapshot_diff = None;
+
+        let new = match self.snapshot_against {
+            Against::Snapshot(i) => self.snapshots[i].1.clone(),
+            Against::Graph => match (&self.tab().state, &self.tab().cursor) {
+                (State::Graph, Some(cursor)) => Session::of(&self.tab().g, cursor),
+                _ => return,
+            },
+            Against::Recrawl => {
+                // cached links would be the ones retrieved when the session was explored
+                let source = self.source.uncached();
+                let (sender, receiver) = unbounded();
+                tokio::spawn(async move {
+                    let recrawled = session::recrawl(source.as_ref(), &old).await;
+                    sender.send(recrawled.map(|new| (old, new))).unwrap();
+                });
+                self.recrawl_task = Some(receiver);
+                return;
+            }
+        };
 
-        self.select_node(p);
+        let diff = diff::diff(&old, &new);
+        self.snapshot_diff = Some((old, new, diff));
     }
 
-    fn handle_keys_graph(&mut self, i: &InputState) {
-        if i.key_pressed(egui::Key::L) {
-            self.select_next();
+    fn process_recrawl_task(&mut self) {
+        let result = match &self.recrawl_task {
+            Some(receiver) => match receiver.try_recv() {
+                Ok(result) => result,
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => {
+                    self.recrawl_task = None;
+                    return;
+                }
+            },
+            None => return,
+        };
+
+        self.recrawl_task = None;
+        match result {
+            Ok((old, new)) => {
+                let diff = diff::diff(&old, &new);
+                self.snapshot_diff = Some((old, new, diff));
+            }
+            Err(err) => error!("failed to re-crawl snapshot: {}", err),
         }
-        if i.key_pressed(egui::Key::H) {
-            self.select_prev();
+    }
+
+    /// Opens both compared sessions as one graph in a new tab, added and removed articles and
+    /// links are colored.
+    fn show_snapshot_diff(&mut self) {
+        let union = match &self.snapshot_diff {
+            Some((old, new, diff)) => diff::union(old, new, diff),
+            None => return,
+        };
+
+        self.open_session(&union);
+        if let Some((_, _, diff)) = &self.snapshot_diff {
+            self.tabs[self.active_tab].mark_changes(diff);
         }
-        if i.key_pressed(egui::Key::ArrowLeft) {
-            self.select_prev_article();
+    }
+
+    /// Shows the saved session in a new tab, unless the shown one is still empty.
+    fn open_session(&mut self, session: &Session) {
+        let root = match Url::new(session.root()) {
+            Ok(root) => root,
+            Err(err) => {
+                error!("failed to open session of {}: {}", session.root(), err);
+                return;
+            }
+        };
+        if !matches!(self.tab().state, State::Input | State::InputError) {
+            self.new_tab();
         }
-        if i.key_pressed(egui::Key::ArrowRight) {
-            self.select_next_article();
+
+        self.tab_mut().open_session(root, session);
+    }
+
+    /// Starts retrieving the revision history of the selected article.
+    fn load_timeline(&mut self) {
+        let tab = self.tab();
+        let url = match tab.selected_node {
+            Some(idx) => tab.g.g.node_weight(idx).unwrap().payload().url().clone(),
+            None => return,
+        };
+        info!("loading {} revisions of {}", self.timeline_limit, url.val());
+
+        let (sender, receiver) = unbounded();
+        let source: Arc<dyn LinkSource> = self.source.clone();
+        let limit = self.timeline_limit;
+        tokio::spawn(async move {
+            sender
+                .send(revision::timeline(source, url, limit).await)
+                .unwrap();
+        });
+        self.timeline_task = Some(receiver);
+    }
+
+    fn process_timeline_task(&mut self) {
+        let result = match &self.timeline_task {
+            Some(receiver) => match receiver.try_recv() {
+                Ok(result) => result,
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => {
+                    self.timeline_task = None;
+                    return;
+                }
+            },
+            None => return,
+        };
+
+        self.timeline_task = None;
+        match result {
+            Ok(timeline) => self.open_timeline(timeline),
+            Err(err) => error!("failed to load revisions: {}", err),
         }
-        if i.key_pressed(egui::Key::ArrowDown) || i.key_pressed(egui::Key::J) {
-            self.select_next_root();
+    }
+
+    /// Shows the article of the timeline in a new tab, unless the shown one is still empty.
+    fn open_timeline(&mut self, timeline: Timeline) {
+        if !matches!(self.tab().state, State::Input | State::InputError) {
+            self.new_tab();
         }
-        if i.key_pressed(egui::Key::ArrowUp) || i.key_pressed(egui::Key::K) {
-            self.select_prev_root();
+
+        self.tab_mut().open_timeline(timeline);
+    }
+
+    fn draw_export(&mut self, ctx: &Context) {
+        let export = draw_view_export(
+            ctx,
+            &mut self.export_open,
+            export_view::State {
+                path: &mut self.export_path,
+                options: &mut self.export_options,
+                whole_graph: &mut self.export_whole_graph,
+                message: self.export_message.as_deref(),
+            },
+        );
+        if !export {
+            return;
         }
-        if i.key_pressed(egui::Key::Enter) {
-            if let Some(idx) = self.selected_node {
-                let n = self.g.g.node_weight(idx).unwrap().payload();
 
-                self.create_new_task(idx, n.url().clone());
-                self.state = State::GraphAndLoading;
-            }
+        let mut options = self.export_options.clone();
+        if !self.export_whole_graph {
+            options.area = self.tab().visible_area;
         }
+        let path = self.export_path.clone();
+        self.export_message = Some(match self.export_image(Path::new(&path), &options) {
+            Ok(_) => {
+                info!("exported graph to {}", path);
+                format!("saved {}", path)
+            }
+            Err(err) => {
+                error!("failed to export graph: {}", err);
+                format!("failed: {}", err)
+            }
+        });
     }
 
     fn draw_input_error(&mut self, ctx: &Context) {
+        let tab = &mut self.tabs[self.active_tab];
         let input_resp = CentralPanel::default().show(ctx, |ui| {
             draw_view_input(
-                &mut self.root_article_url,
+                &mut tab.root_article_url,
+                &mut self.as_of_input,
                 ui,
                 false,
                 ui.available_height() / 5.,
@@ -333,14 +747,16 @@ impl App {
         });
 
         if input_resp.inner.changed() {
-            self.state = next(&self.state, Fork::Success);
+            tab.state = next(&tab.state, Fork::Success);
         }
     }
 
     fn draw_input(&mut self, ctx: &Context) {
+        let tab = &mut self.tabs[self.active_tab];
         CentralPanel::default().show(ctx, |ui| {
             draw_view_input(
-                &mut self.root_article_url,
+                &mut tab.root_article_url,
+                &mut self.as_of_input,
                 ui,
                 true,
                 ui.available_height() / 5.,
@@ -350,6 +766,7 @@ impl App {
     }
 
     fn draw_graph_and_loading(&mut self, ctx: &Context) {
+        self.draw_analytics(ctx);
         SidePanel::right("toolbox").resizable(true).show(ctx, |ui| {
             ui.centered_and_justified(|ui| {
                 draw_view_toolbox(ui, &self.generate_toolbox_state(ui, true))
@@ -360,225 +777,799 @@ impl App {
         });
     }
 
-    fn create_new_task(&mut self, idx: NodeIndex, url: Url) {
-        let (sender, receiver) = unbounded();
-        let retriever = UrlRetriever::new(sender);
+    fn draw_analytics(&mut self, ctx: &Context) {
+        let tab = &mut self.tabs[self.active_tab];
+        let loading = tab.state != State::Graph;
+        let (clicked, action, class_filter, prune) = SidePanel::left("analytics")
+            .resizable(true)
+            .show(ctx, |ui| {
+                let spacing = ui.available_height() / 30.;
+                draw_view_legend(
+                    ui,
+                    legend::State {
+                        spacing,
+                        theme: &self.config.theme,
+                    },
+                );
+                let clicked = draw_view_metrics(
+                    ui,
+                    metrics_view::State {
+                        spacing,
+                        g: &tab.g,
+                        metrics: &tab.metrics,
+                        filter: tab.community_filter.and_then(|id| tab.communities.get(id)),
+                        sort_by: &mut self.metrics_sort_by,
+                        encoded: &mut self.encoded_metric,
+                        encoding: &mut self.metric_encoding,
+                    },
+                );
+                let found = draw_view_link_search(
+                    ui,
+                    link_search::State {
+                        spacing,
+                        g: &tab.g,
+                        query: &mut tab.link_search_query,
+                    },
+                );
+                let class_filter = draw_view_entities(
+                    ui,
+                    entities_view::State {
+                        spacing,
+                        entities: &tab.entities,
+                        color_by_class: &mut self.color_by_class,
+                        filter: tab.class_filter.as_deref(),
+                    },
+                );
+                let missing = draw_view_missing(
+                    ui,
+                    missing_view::State {
+                        spacing,
+                        g: &tab.g,
+                        color: self.config.theme.missing,
+                    },
+                );
+                let action = draw_view_communities(
+                    ui,
+                    communities_view::State {
+                        spacing,
+                        g: &tab.g,
+                        communities: &tab.communities,
+                        color_by_community: &mut self.color_by_community,
+                        filter: tab.community_filter,
+                        collapsed: &tab.collapsed_communities,
+                    },
+                );
+                let prune = draw_view_prune(ui, prune::State { spacing, loading });
+                (clicked.or(found).or(missing), action, class_filter, prune)
+            })
+            .inner;
+
+        if let Some(idx) = clicked {
+            if tab.state == State::Graph {
+                tab.select_node(idx);
+            }
+        }
 
-        info!("started retriever for {}", url.val());
+        if let Some(filter) = class_filter {
+            tab.class_filter = filter;
+        }
 
-        self.active_tasks
-            .insert(idx, (receiver, retriever.run(url)));
+        match prune {
+            Some(prune::Action::Leaves) => tab.remove_nodes(tab.leaves()),
+            Some(prune::Action::Type(t)) => tab.remove_nodes(tab.nodes_of_type(t)),
+            None => (),
+        }
+
+        match action {
+            Some(communities_view::Action::Filter(filter)) => tab.community_filter = filter,
+            Some(communities_view::Action::ToggleCollapse(id)) => {
+                if !tab.collapsed_communities.remove(&id) {
+                    tab.collapsed_communities.insert(id);
+                }
+            }
+            None => (),
+        }
     }
 
     fn draw_graph(&mut self, ctx: &Context) {
+        self.draw_analytics(ctx);
         SidePanel::right("toolbox").resizable(true).show(ctx, |ui| {
             if let Some(resp) = draw_view_toolbox(ui, &self.generate_toolbox_state(ui, false)) {
-                if !resp.clicked() {
-                    return;
+                if resp.clicked() {
+                    self.dispatch(Command::Expand);
                 }
-
-                let idx = self.selected_node.unwrap();
-                let n = self.g.g.node_weight(idx).unwrap().payload();
-
-                self.create_new_task(idx, n.url().clone());
-                self.state = State::GraphAndLoading;
             }
         });
         CentralPanel::default().show(ctx, |ui| {
+            let rect = ui.available_rect_before_wrap();
             draw_view_graph(ui, self.generate_graph_state(false));
+            self.tab_mut().visible_area = node_shape::visible_area(ctx, rect);
         });
     }
 
     fn handle_keys_input(&mut self, i: &InputState) {
         if i.key_pressed(egui::Key::Enter) {
-            match url::Url::new(&self.root_article_url) {
+            let tab = &mut self.tabs[self.active_tab];
+            match url::Url::new(&tab.root_article_url) {
                 Ok(u) => {
                     if !u.is_wiki() {
-                        self.state = next(&self.state, Fork::Failure);
+                        tab.state = next(&tab.state, Fork::Failure);
                         return;
                     }
 
-                    self.g.g = StableGraph::new();
-                    let mut rng = rand::thread_rng();
-                    let loc = egui::Pos2 {
-                        x: rng.gen_range(-100.0..100.),
-                        y: rng.gen_range(-100.0..100.),
+                    let as_of = self.as_of_input.trim();
+                    tab.as_of = match as_of.is_empty() {
+                        true => None,
+                        false => match revision::parse_date(as_of) {
+                            Some(at) => Some(at),
+                            None => {
+                                info!("invalid as of date: {}", as_of);
+                                return;
+                            }
+                        },
                     };
+                    tab.start_exploration(u);
+                    tab.state = next(&tab.state, Fork::Success);
+                }
+                Err(_) => {
+                    tab.state = next(&tab.state, Fork::Failure);
+                }
+            };
+        };
+    }
 
-                    let n = node::Node::new(u.clone());
-                    let label = n.label();
-                    let idx: NodeIndex = self.g.add_node_with_label_and_location(n, label, loc);
+    /// Starts retrieving for the selected node of the shown tab, see
+    /// [`Exploration::expand_selected`].
+    fn expand_selected(&mut self, retrieval: Option<Retrieval>) {
+        let (handling, category_mode) = (self.config.disambiguation, self.category_mode);
+        self.tab_mut()
+            .expand_selected(retrieval, handling, category_mode);
+    }
 
-                    self.node_by_url.insert(u.clone(), idx);
+    /// Opens the article in the shown tab, see [`Exploration::open_root`].
+    fn open_root(&mut self, u: Url) {
+        let (handling, category_mode) = (self.config.disambiguation, self.category_mode);
+        self.tab_mut().open_root(u, handling, category_mode);
+    }
 
-                    add_node_to_sim(&mut self.sim, idx, loc.to_vec2());
+    /// Titles of all the tabs in the order they are shown.
+    fn tab_titles(&self) -> Vec<String> {
+        self.tabs.iter().map(Exploration::title).collect()
+    }
 
-                    self.create_new_task(idx, u);
+    fn new_tab(&mut self) {
+        self.tabs.push(Exploration::new(self.source.clone()));
+        self.switch_tab(self.tabs.len() - 1);
+    }
 
-                    self.state = next(&self.state, Fork::Success);
-                }
-                Err(_) => {
-                    self.state = next(&self.state, Fork::Failure);
-                }
+    fn switch_tab(&mut self, i: usize) {
+        if i == self.active_tab || i >= self.tabs.len() {
+            return;
+        }
+
+        self.active_tab = i;
+        info!("switched to tab {}", i);
+
+        // selections made in the graph of the previous tab
+        while self.changes_receiver.try_recv().is_ok() {}
+    }
+
+    /// Closes the tab and stops its retrievals. The last tab is emptied instead.
+    fn close_tab(&mut self, i: usize) {
+        if i >= self.tabs.len() {
+            return;
+        }
+        if self.tabs.len() == 1 {
+            self.tab_mut().new_exploration();
+            return;
+        }
+
+        if i == self.active_tab {
+            let neighbour = match i + 1 < self.tabs.len() {
+                true => i + 1,
+                false => i - 1,
             };
-        };
+            self.switch_tab(neighbour);
+        }
+        self.tabs.remove(i).abort();
+        if i < self.active_tab {
+            self.active_tab -= 1;
+        }
+    }
+
+    /// Opens the article in a new tab explored as of the same time as the shown one.
+    fn open_in_new_tab(&mut self, u: Url) {
+        let as_of = self.tab().as_of;
+        self.new_tab();
+        self.tab_mut().as_of = as_of;
+        self.open_root(u);
+    }
+
+    /// Adds the graph of another tab to the shown one and closes that tab, see
+    /// [`Exploration::merge`]. Undoing the merge does not reopen the tab.
+    fn merge_tab(&mut self, i: usize) {
+        if i == self.active_tab || i >= self.tabs.len() {
+            return;
+        }
+        if self.tab().state != State::Graph || self.tabs[i].state != State::Graph {
+            info!("tabs can not be merged while they are loading");
+            return;
+        }
+
+        let other = self.tabs.remove(i);
+        if i < self.active_tab {
+            self.active_tab -= 1;
+        }
+        self.tab_mut().merge(other);
+    }
+
+    fn draw_tabs(&mut self, ctx: &Context) {
+        let dragged = self
+            .tab()
+            .g
+            .g
+            .node_weights()
+            .find(|n| n.dragged())
+            .map(|n| n.payload().url().clone());
+
+        let action = TopBottomPanel::top("tabs")
+            .show(ctx, |ui| {
+                draw_view_tabs(
+                    ui,
+                    tabs_view::State {
+                        titles: self.tab_titles(),
+                        active: self.active_tab,
+                        dragging: dragged.is_some(),
+                    },
+                )
+            })
+            .inner;
+
+        match action {
+            Some(tabs_view::Action::Show(i)) => self.switch_tab(i),
+            Some(tabs_view::Action::New) => self.new_tab(),
+            Some(tabs_view::Action::Close(i)) => self.close_tab(i),
+            Some(tabs_view::Action::Merge(i)) => self.merge_tab(i),
+            Some(tabs_view::Action::Drop(i)) => {
+                let url = dragged.unwrap();
+                self.switch_tab(i);
+                self.open_root(url);
+            }
+            Some(tabs_view::Action::DropNew) => self.open_in_new_tab(dragged.unwrap()),
+            None => (),
+        }
+    }
+
+    /// Sets the backend links are retrieved from when articles are expanded.
+    pub fn set_source(&mut self, source: Arc<dyn LinkSource>) {
+        self.source = Arc::new(SharedSource::new(source));
+        self.tabs
+            .iter_mut()
+            .for_each(|tab| tab.set_source(self.source.clone()));
+    }
+
+    /// Loads graph imported from the dumps into the shown tab, see
+    /// [`Exploration::load_import`].
+    pub fn load_import(&mut self, imported: ImportedGraph) {
+        self.tab_mut().load_import(imported);
+    }
+
+    /// Runs the layout of the shown tab without drawing, so the graph can be exported without a
+    /// window.
+    pub fn settle_layout(&mut self, steps: usize) {
+        self.tab_mut().settle_layout(steps);
+    }
+
+    /// Writes the graph as an image, see [`export::export`].
+    pub fn export_image(&self, path: &Path, options: &export::Options) -> io::Result<()> {
+        export::export(&self.tab().g, &self.config.theme, path, options)
     }
 
     fn generate_graph_state(&mut self, loading: bool) -> graph::State {
         graph::State {
             loading,
-            g: &mut self.g,
+            g: &mut self.tabs[self.active_tab].g,
             sender: self.changes_sender.clone(),
             receiver: self.changes_receiver.clone(),
         }
     }
 
-    fn generate_toolbox_state(&mut self, ui: &Ui, loading: bool) -> toolbox::State {
+    fn generate_toolbox_state(&self, ui: &Ui, loading: bool) -> toolbox::State {
+        let tab = self.tab();
         let mut selected_node_root = None;
-        if self.selected_node.is_some() {
-            selected_node_root = Some(self.cursor.as_ref().unwrap().position().0);
+        if tab.selected_node.is_some() {
+            selected_node_root = Some(tab.cursor.as_ref().unwrap().position().0);
         }
 
         toolbox::State {
             loading,
             selected_node_root,
             spacing: ui.available_height() / 30.,
-            selected_node: self.selected_node,
-            g: &self.g,
+            selected_node: tab.selected_node,
+            selected_node_metrics: tab
+                .selected_node
+                .and_then(|idx| tab.metrics.get(idx).cloned()),
+            selected_node_entity: tab.selected_node.and_then(|idx| tab.entities.get(idx)),
+            category_mode: self.category_mode,
+            as_of: tab.as_of,
+            selected_node_thumbnail: tab
+                .selected_node
+                .and_then(|idx| tab.thumbnails.get(&idx))
+                .and_then(Option::as_ref),
+            selected_node_summary: tab
+                .selected_node
+                .and_then(|idx| tab.summaries.get(&idx))
+                .map(String::as_str),
+            selected_edge: selected_node_root
+                .zip(tab.selected_node)
+                .and_then(|(root, idx)| tab.g.g.find_edge(root, idx))
+                .map(|e| tab.g.g.edge_weight(e).unwrap().payload()),
+            g: &tab.g,
         }
     }
+}
 
-    fn select_next_root(&mut self) {
-        let cursor = self.cursor.as_mut().unwrap();
-        let curr_root = cursor.position().0;
-        if let Some(next) = cursor.next_root() {
-            self.select_node(next);
-        } else {
-            self.select_node(curr_root);
-        }
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use super::*;
+    use crate::cursor::Cursor;
+    use crate::diff::Change;
+    use crate::node;
+    use crate::testing::{url, FakeSource};
+
+    /// App with the default config and no articles to retrieve.
+    fn app() -> App {
+        with_source(FakeSource::new(&[]))
     }
 
-    fn select_prev_root(&mut self) {
-        let cursor = self.cursor.as_mut().unwrap();
-        let curr_root = cursor.position().0;
-        if let Some(prev) = cursor.prev_root() {
-            self.select_node(prev);
-        } else {
-            self.select_node(curr_root);
+    fn with_source(source: FakeSource) -> App {
+        App::new(Config::default(), Arc::new(source))
+    }
+
+    fn import(root: &str, links: &[(&str, &str)]) -> ImportedGraph {
+        ImportedGraph {
+            seeds: vec![url(root)],
+            links: links
+                .iter()
+                .map(|(from, to)| (url(from), url(to)))
+                .collect(),
         }
     }
-}
 
-fn add_node(
-    g: &mut Graph<node::Node, (), Directed>,
-    sim: &mut Simulation<(), f32>,
-    loc_center: Pos2,
-    n: &node::Node,
-) -> NodeIndex {
-    let mut rng = rand::thread_rng();
-    let loc = egui::Pos2 {
-        x: loc_center.x + rng.gen_range(-100.0..100.),
-        y: loc_center.y + rng.gen_range(-100.0..100.),
-    };
-
-    let color = match n.url().url_type() {
-        url::Type::Article => Some(COLOR_SUB_ACCENT),
-        url::Type::ExternalArticle => Some(COLOR_RIGHT_LOW),
-        url::Type::File => Some(COLOR_LEFT_LOW),
-        url::Type::Other => None,
-    };
-
-    let idx = g.add_node_with_label_and_location(n.clone(), n.label(), loc);
-    add_node_to_sim(sim, idx, loc.to_vec2())
-}
+    /// Every graph node is known by its url and has the location of its simulation node.
+    fn assert_in_sync(tab: &mut Exploration) {
+        tab.layout.sync(&mut tab.g);
 
-fn add_node_to_sim(sim: &mut Simulation<(), f32>, idx: NodeIndex, loc: Vec2) -> NodeIndex {
-    let mut sim_node = fdg_sim::Node::new(idx.index().to_string().as_str(), ());
-    sim_node.location = Vec3::new(loc.x, loc.y, 0.);
-    sim.get_graph_mut().add_node(sim_node)
-}
+        assert_eq!(tab.node_by_url.len(), tab.g.g.node_count());
+        assert_eq!(tab.layout.node_count(), tab.g.g.node_count());
+        tab.g.g.node_indices().for_each(|idx| {
+            let n = tab.g.g.node_weight(idx).unwrap();
+            assert_eq!(tab.node_by_url.get(n.payload().url()), Some(&idx));
+            assert_eq!(tab.layout.location(idx), Some(n.location()));
+        });
+    }
 
-fn add_edge(
-    g: &mut Graph<node::Node, (), Directed>,
-    sim: &mut Simulation<(), f32>,
-    start: NodeIndex,
-    end: NodeIndex,
-) {
-    g.add_edge(start, end, ());
-    sim.get_graph_mut().add_edge(start, end, EDGE_WEIGHT);
-}
+    #[test]
+    fn second_exploration_starts_clean() {
+        let mut app = app();
+        let tab = app.tab_mut();
+        tab.load_import(import("A", &[("A", "B"), ("A", "C"), ("B", "C")]));
+        let root = tab.node_by_url[&url("A")];
+        tab.cursor = Some(Cursor::new(root, &tab.g));
+        tab.selected_node = Some(root);
+        tab.remove_nodes(HashSet::from([tab.node_by_url[&url("B")]]));
+        assert!(tab.history.can_undo());
+
+        tab.load_import(import("X", &[("X", "Y")]));
+
+        assert_eq!(tab.g.g.node_count(), 2);
+        assert_eq!(tab.g.g.edge_count(), 1);
+        assert!(tab.node_by_url.contains_key(&url("X")));
+        assert!(tab.node_by_url.contains_key(&url("Y")));
+        assert!(tab.cursor.is_none());
+        assert!(tab.selected_node.is_none());
+        assert!(!tab.history.can_undo());
+        assert_eq!(tab.state, State::GraphLoaded);
+        assert_in_sync(tab);
+    }
 
-fn construct_simulation() -> Simulation<(), f32> {
-    // create force graph
-    let force_graph = ForceGraph::default();
+    #[test]
+    fn removals_keep_layout_in_sync() {
+        let mut app = app();
+        let tab = app.tab_mut();
+        tab.load_import(import(
+            "A",
+            &[("A", "B"), ("A", "C"), ("A", "D"), ("C", "E")],
+        ));
+        let root = tab.node_by_url[&url("A")];
+        tab.cursor = Some(Cursor::new(root, &tab.g));
+        tab.selected_node = Some(root);
+
+        // removed in another order than added so vacant indices differ from the insertion order
+        tab.remove_nodes(HashSet::from([
+            tab.node_by_url[&url("D")],
+            tab.node_by_url[&url("B")],
+        ]));
+        tab.load_import_links(&[("A", "F"), ("E", "G")]);
+        assert_in_sync(tab);
+
+        tab.undo();
+        assert_eq!(tab.g.g.node_count(), 5);
+        assert!(tab.node_by_url.contains_key(&url("B")));
+        assert!(!tab.node_by_url.contains_key(&url("F")));
+        assert_in_sync(tab);
+        tab.redo();
+        assert_eq!(tab.g.g.node_count(), 5);
+        assert!(!tab.node_by_url.contains_key(&url("B")));
+        assert!(tab.node_by_url.contains_key(&url("G")));
+        assert_in_sync(tab);
+    }
 
-    // initialize simulation
-    let mut params = SimulationParameters::default();
-    let force = fdg_sim::force::fruchterman_reingold_weighted(SCALE, COOL_OFF);
-    params.set_force(force);
+    #[test]
+    fn articles_are_not_checked_for_thumbnails_again() {
+        let mut app = app();
+        let tab = app.tab_mut();
+        tab.load_import(import("A", &[("A", "B")]));
 
-    Simulation::from_graph(force_graph, params)
-}
+        tab.process_thumbnail_tasks(&Context::default(), true);
 
-fn update_simulation(sim: &mut Simulation<(), f32>) {
-    // the following manipulations is a hack to avoid having looped edges in the simulation
-    // because they cause the simulation to blow up;
-    // this is the issue of the fdg_sim engine we use for the simulation
-    // https://github.com/grantshandy/fdg/issues/10
-    // * remove loop edges
-    // * update simulation
-    // * restore loop edges
-
-    // remove looped edges
-    let looped_nodes = {
-        let graph = sim.get_graph_mut();
-        let mut looped_nodes = vec![];
-        let mut looped_edges = vec![];
-        graph.edge_indices().for_each(|idx| {
-            let edge = graph.edge_endpoints(idx).unwrap();
-            let looped = edge.0 == edge.1;
-            if looped {
-                looped_nodes.push((edge.0, ()));
-                looped_edges.push(idx);
-            }
+        assert!(tab.thumbnail_tasks.is_empty());
+        assert_eq!(tab.thumbnails.len(), 2);
+        assert!(tab.thumbnails.values().all(Option::is_none));
+    }
+
+    #[test]
+    fn import_adds_every_seed() {
+        let mut app = app();
+        app.load_import(ImportedGraph {
+            seeds: vec![url("A"), url("D")],
+            links: vec![
+                (url("D"), url("E")),
+                (url("A"), url("B")),
+                (url("E"), url("A")),
+            ],
         });
 
-        for idx in looped_edges {
-            graph.remove_edge(idx);
+        let tab = app.tab_mut();
+        assert_eq!(tab.g.g.node_count(), 4);
+        assert_eq!(tab.g.g.edge_count(), 3);
+        assert_eq!(tab.root_article_url, url("A").val());
+        let (d, e) = (tab.node_by_url[&url("D")], tab.node_by_url[&url("E")]);
+        assert!(tab.g.g.find_edge(d, e).is_some());
+        assert_in_sync(tab);
+    }
+
+    /// Drives the tabs as every frame does until the links of the shown one are retrieved.
+    async fn retrieve(app: &mut App) {
+        for _ in 0..1000 {
+            app.tabs.iter_mut().for_each(Exploration::handle_state);
+            if app.tab().state == State::Graph {
+                return;
+            }
+            tokio::task::yield_now().await;
         }
+        panic!("links were not retrieved");
+    }
 
-        sim.update(SIMULATION_DT);
+    #[tokio::test]
+    async fn expands_through_injected_source() {
+        let mut app = with_source(FakeSource::new(&[
+            ("A", &["B", "C"]),
+            ("B", &["C", "D", "D"]),
+        ]));
+
+        app.open_root(url("A"));
+        retrieve(&mut app).await;
+        assert_eq!(app.tab().g.g.node_count(), 3);
+
+        let tab = app.tab_mut();
+        tab.select_node(tab.node_by_url[&url("B")]);
+        app.dispatch(Command::Expand);
+        retrieve(&mut app).await;
+
+        let tab = app.tab_mut();
+        assert_eq!(tab.g.g.node_count(), 4);
+        let (b, d) = (tab.node_by_url[&url("B")], tab.node_by_url[&url("D")]);
+        let e = tab.g.g.find_edge(b, d).unwrap();
+        assert_eq!(tab.g.g.edge_weight(e).unwrap().payload().count(), 2);
+        assert_in_sync(tab);
+    }
 
-        looped_nodes
-    };
+    /// Loads the import into the shown tab as it is once its links are retrieved.
+    fn load_explored(app: &mut App, imported: ImportedGraph) {
+        let root = imported.seeds[0].clone();
+        let tab = app.tab_mut();
+        tab.load_import(imported);
+        let root = tab.node_by_url[&root];
+        tab.cursor = Some(Cursor::new(root, &tab.g));
+        tab.selected_node = Some(root);
+        tab.state = State::Graph;
+    }
 
-    // restore looped edges
-    let graph = sim.get_graph_mut();
-    for (idx, _) in looped_nodes.iter() {
-        graph.add_edge(*idx, *idx, EDGE_WEIGHT);
+    #[test]
+    fn tabs_keep_their_explorations() {
+        let mut app = app();
+        load_explored(&mut app, import("A", &[("A", "B")]));
+        app.new_tab();
+        assert_eq!(app.tab().g.g.node_count(), 0);
+        load_explored(&mut app, import("X", &[("X", "Y"), ("X", "Z")]));
+
+        app.switch_tab(0);
+        assert_eq!(app.tab().g.g.node_count(), 2);
+        assert!(app.tab().node_by_url.contains_key(&url("A")));
+        assert!(!app.tab().node_by_url.contains_key(&url("X")));
+        assert_eq!(app.tab_titles(), vec!["A".to_string(), "X".to_string()]);
+        assert_in_sync(app.tab_mut());
+
+        app.close_tab(0);
+        assert_eq!(app.tabs.len(), 1);
+        assert_eq!(app.active_tab, 0);
+        assert_eq!(app.tab().g.g.node_count(), 3);
+        assert_in_sync(app.tab_mut());
     }
-}
 
-/// Syncs the graph with the simulation.
-///
-/// Changes location of nodes in `g` according to the locations in `sim`. If node from `g` is dragged its location is prioritized
-/// over the location of the corresponding node from `sim` and this location is set to the node from the `sim`.
-fn sync_graph_with_simulation(
-    g: &mut Graph<node::Node, (), Directed>,
-    sim: &mut Simulation<(), f32>,
-) {
-    let g_indices = g.g.node_indices().collect::<Vec<_>>();
-    g_indices.iter().for_each(|g_n_idx| {
-        let g_n = g.g.node_weight_mut(*g_n_idx).unwrap();
-        let sim_n = sim.get_graph_mut().node_weight_mut(*g_n_idx).unwrap();
-
-        if g_n.dragged() {
-            let loc = g_n.location();
-            sim_n.location = Vec3::new(loc.x, loc.y, 0.);
-            return;
+    #[tokio::test]
+    async fn hidden_tabs_keep_retrieving() {
+        let mut app = with_source(FakeSource::new(&[("A", &["B", "C"])]));
+        app.open_root(url("A"));
+        app.new_tab();
+
+        let ctx = Context::default();
+        for _ in 0..1000 {
+            app.process_tabs(&ctx);
+            if app.tabs[0].state == State::Graph {
+                break;
+            }
+            tokio::task::yield_now().await;
         }
 
-        let loc = sim_n.location;
-        g_n.set_location(Pos2::new(loc.x, loc.y));
-    });
+        assert_eq!(app.tabs[0].state, State::Graph);
+        assert_eq!(app.tabs[0].g.g.node_count(), 3);
+        assert_eq!(app.active_tab, 1);
+        assert_eq!(app.tab().g.g.node_count(), 0);
+    }
+
+    #[test]
+    fn merged_tab_joins_shared_articles() {
+        let mut app = app();
+        load_explored(&mut app, import("A", &[("A", "B"), ("A", "C")]));
+        app.new_tab();
+        load_explored(&mut app, import("X", &[("X", "C"), ("X", "D")]));
+        app.switch_tab(0);
+
+        app.merge_tab(1);
+
+        assert_eq!(app.tabs.len(), 1);
+        let tab = app.tab_mut();
+        assert_eq!(tab.g.g.node_count(), 5);
+        assert_eq!(tab.g.g.edge_count(), 4);
+        let cursor = tab.cursor.as_ref().unwrap();
+        assert!(cursor.is_root(tab.node_by_url[&url("X")]));
+        assert_eq!(cursor.origin(), tab.node_by_url[&url("A")]);
+        assert_in_sync(tab);
+
+        tab.undo();
+        assert_eq!(tab.g.g.node_count(), 3);
+        assert_in_sync(tab);
+    }
+
+    #[tokio::test]
+    async fn undo_removes_expanded_links() {
+        let mut app = with_source(FakeSource::new(&[("A", &["B"]), ("B", &["A", "C", "C"])]));
+        app.open_root(url("A"));
+        retrieve(&mut app).await;
+        let tab = app.tab_mut();
+        tab.select_node(tab.node_by_url[&url("B")]);
+        app.dispatch(Command::Expand);
+        retrieve(&mut app).await;
+        let tab = app.tab_mut();
+        assert_eq!((tab.g.g.node_count(), tab.g.g.edge_count()), (3, 3));
+        let rooted = tab
+            .cursor
+            .as_ref()
+            .unwrap()
+            .is_root(tab.node_by_url[&url("B")]);
+
+        tab.undo();
+        assert_eq!((tab.g.g.node_count(), tab.g.g.edge_count()), (2, 1));
+        assert!(!tab.node_by_url.contains_key(&url("C")));
+        assert_eq!(tab.selected_node, Some(tab.node_by_url[&url("B")]));
+        assert_in_sync(tab);
+
+        tab.redo();
+        assert_eq!((tab.g.g.node_count(), tab.g.g.edge_count()), (3, 3));
+        let (b, c) = (tab.node_by_url[&url("B")], tab.node_by_url[&url("C")]);
+        let e = tab.g.g.find_edge(b, c).unwrap();
+        assert_eq!(tab.g.g.edge_weight(e).unwrap().payload().count(), 2);
+        assert_eq!(tab.cursor.as_ref().unwrap().is_root(b), rooted);
+        assert_in_sync(tab);
+    }
+
+    #[tokio::test]
+    async fn undo_removes_second_root_only() {
+        let mut app = with_source(FakeSource::new(&[
+            ("A", &["B"]),
+            ("B", &["C"]),
+            ("X", &["Y"]),
+        ]));
+        app.open_root(url("A"));
+        retrieve(&mut app).await;
+        let tab = app.tab_mut();
+        tab.select_node(tab.node_by_url[&url("B")]);
+        app.dispatch(Command::Expand);
+        retrieve(&mut app).await;
+        app.open_root(url("X"));
+        retrieve(&mut app).await;
+        let tab = app.tab_mut();
+        assert_eq!((tab.g.g.node_count(), tab.g.g.edge_count()), (5, 3));
+
+        tab.undo();
+        assert_eq!((tab.g.g.node_count(), tab.g.g.edge_count()), (3, 2));
+        assert!(!tab.node_by_url.contains_key(&url("X")));
+        assert!(tab.node_by_url.contains_key(&url("C")));
+        assert_in_sync(tab);
+
+        tab.undo();
+        assert_eq!((tab.g.g.node_count(), tab.g.g.edge_count()), (2, 1));
+        assert_in_sync(tab);
+    }
+
+    // merging spawns resolution of the entities
+    #[tokio::test]
+    async fn automatic_merge_can_be_undone() {
+        let mut app = app();
+        load_explored(&mut app, import("A", &[("A", "B"), ("A", "C"), ("C", "D")]));
+        let tab = app.tab_mut();
+        let (b, c) = (tab.node_by_url[&url("B")], tab.node_by_url[&url("C")]);
+
+        tab.pending_merges.push((c, b));
+        tab.process_entities();
+        assert_eq!(tab.g.g.node_count(), 3);
+        assert!(tab.g.g.find_edge(b, tab.node_by_url[&url("D")]).is_some());
+
+        tab.undo();
+        assert_eq!((tab.g.g.node_count(), tab.g.g.edge_count()), (4, 3));
+        let c = tab.node_by_url[&url("C")];
+        assert!(tab.g.g.find_edge(c, tab.node_by_url[&url("D")]).is_some());
+        assert_in_sync(tab);
+
+        // the duplicate is reported again once its entity is resolved
+        tab.pending_merges.push((c, b));
+        tab.process_entities();
+        assert_eq!(tab.g.g.node_count(), 4);
+    }
+
+    // selecting the origin requests its summary
+    #[tokio::test]
+    async fn snapshot_diff_opens_highlighted() {
+        let mut app = app();
+        load_explored(&mut app, import("A", &[("A", "B"), ("A", "C")]));
+        let tab = app.tab_mut();
+        let old = Session::of(&tab.g, tab.cursor.as_ref().unwrap());
+        tab.remove_nodes(HashSet::from([tab.node_by_url[&url("B")]]));
+        tab.load_import_links(&[("A", "D")]);
+        let new = Session::of(&tab.g, tab.cursor.as_ref().unwrap());
+        let diff = diff::diff(&old, &new);
+        app.snapshot_diff = Some((old, new, diff));
+
+        app.show_snapshot_diff();
+
+        assert_eq!(app.tabs.len(), 2);
+        let tab = app.tab_mut();
+        assert_eq!(tab.state, State::Graph);
+        assert_eq!(tab.g.g.node_count(), 4);
+        assert_eq!(tab.g.g.edge_count(), 3);
+        let change = |title| {
+            let idx = tab.node_by_url[&url(title)];
+            tab.g.g.node_weight(idx).unwrap().payload().change()
+        };
+        assert_eq!(change("A"), None);
+        assert_eq!(change("B"), Some(Change::Removed));
+        assert_eq!(change("D"), Some(Change::Added));
+        let edge = tab
+            .g
+            .g
+            .find_edge(tab.node_by_url[&url("A")], tab.node_by_url[&url("B")])
+            .unwrap();
+        assert_eq!(
+            tab.g.g.edge_weight(edge).unwrap().payload().change(),
+            Some(Change::Removed)
+        );
+        assert_in_sync(tab);
+    }
+
+    #[tokio::test]
+    async fn timeline_shows_links_of_revision() {
+        let revisions = [vec!["B", "C"], vec!["C", "D"]]
+            .into_iter()
+            .enumerate()
+            .map(|(i, links)| revision::Revision {
+                id: i as u64,
+                at: SystemTime::UNIX_EPOCH,
+                links: links.into_iter().map(url).collect(),
+            })
+            .collect();
+        let mut app = app();
+
+        app.open_timeline(Timeline::new(url("A"), revisions));
+        let tab = app.tab_mut();
+        assert_eq!(tab.g.g.node_count(), 4);
+        assert_eq!(tab.g.g.edge_count(), 3);
+        assert_in_sync(tab);
+
+        let ctx = Context::default();
+        let shown = |tab: &mut Exploration, position| {
+            tab.timeline.as_mut().unwrap().position = position;
+            tab.apply_communities(false);
+            tab.apply_timeline(&ctx);
+            ["B", "C", "D"].map(|title| {
+                let n = tab.g.g.node_weight(tab.node_by_url[&url(title)]).unwrap();
+                (!n.payload().hidden(), n.payload().change())
+            })
+        };
+        assert_eq!(shown(tab, 0), [(true, None), (true, None), (false, None)]);
+        assert_eq!(
+            shown(tab, 1),
+            [
+                (true, Some(Change::Removed)),
+                (true, None),
+                (true, Some(Change::Added))
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn collapsed_community_hides_its_members() {
+        // links are imported in the order they were found, starting from the root
+        let clique = |titles: [&'static str; 4]| {
+            titles.into_iter().flat_map(move |from| {
+                titles
+                    .into_iter()
+                    .filter(move |to| *to != from)
+                    .map(move |to| (from, to))
+            })
+        };
+        let links = clique(["A", "B", "C", "D"])
+            .chain([("D", "E")])
+            .chain(clique(["E", "F", "G", "H"]))
+            .collect::<Vec<_>>();
+        let mut app = app();
+        let tab = app.tab_mut();
+        tab.load_import(import("A", &links));
+        tab.metrics.invalidate();
+        tab.metrics.update(&tab.g);
+        tab.communities.invalidate();
+        assert!(tab.communities.update(&tab.g, &tab.metrics));
+        assert_eq!(tab.communities.list().len(), 2);
+
+        let id = tab.communities.of(tab.node_by_url[&url("A")]).unwrap();
+        let community = tab.communities.get(id).unwrap();
+        let (center, members) = (community.center, community.members.clone());
+        tab.collapsed_communities.insert(id);
+        tab.apply_communities(false);
+
+        tab.g.g.node_indices().for_each(|idx| {
+            let n = tab.g.g.node_weight(idx).unwrap().payload();
+            if idx == center {
+                assert!(!n.hidden());
+                assert_eq!(n.collapsed(), members.len() - 1);
+            } else {
+                assert_eq!(n.hidden(), members.contains(&idx));
+            }
+        });
+    }
+
+    impl Exploration {
+        /// Adds links to the current graph the way retrieved links are added.
+        fn load_import_links(&mut self, links: &[(&str, &str)]) {
+            links.iter().for_each(|(from, to)| {
+                let parent_idx = self.node_by_url[&url(from)];
+                let loc = self.g.g.node_weight(parent_idx).unwrap().location();
+                let idx = self.insert_node(loc, &node::Node::new(url(to)));
+                self.insert_link(parent_idx, idx, Default::default());
+            });
+        }
+    }
 }
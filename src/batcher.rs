@@ -0,0 +1,87 @@
+use std::collections::HashSet;
+
+use petgraph::stable_graph::NodeIndex;
+
+use crate::{entities::BATCH_SIZE, graph::Graph, url};
+
+/// Splits article nodes of the growing graph into batches for the api.
+///
+/// Every node is handed out once. Nodes of one batch are of the same wiki as they are sent in
+/// one request.
+#[derive(Default)]
+pub struct Batcher {
+    /// Nodes which were handed out, processed or not.
+    requested: HashSet<NodeIndex>,
+    /// The source can not process the batches, no more are handed out.
+    unsupported: bool,
+}
+
+impl Batcher {
+    pub fn set_unsupported(&mut self) {
+        self.unsupported = true;
+    }
+
+    /// Takes the next batch of article nodes which were not handed out yet.
+    pub fn next(&mut self, g: &Graph) -> Vec<(NodeIndex, url::Url)> {
+        if self.unsupported {
+            return vec![];
+        }
+
+        let mut host = None;
+        let batch =
+            g.g.node_indices()
+                .filter(|idx| !self.requested.contains(idx))
+                .map(|idx| (idx, g.g.node_weight(idx).unwrap().payload().url().clone()))
+                .filter(|(_, u)| u.url_type() == url::Type::Article)
+                .filter(|(_, u)| *host.get_or_insert_with(|| u.host().to_string()) == u.host())
+                .take(BATCH_SIZE)
+                .collect::<Vec<_>>();
+
+        self.requested.extend(batch.iter().map(|(idx, _)| *idx));
+        batch
+    }
+
+    /// Forgets the removed node, its index can be reused by a new one.
+    pub fn remove(&mut self, idx: NodeIndex) {
+        self.requested.remove(&idx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::Pos2;
+    use petgraph::stable_graph::StableGraph;
+
+    use super::*;
+    use crate::{node::Node, url::Url};
+
+    fn add_node(g: &mut Graph, url: &str) -> NodeIndex {
+        let n = Node::new(Url::new(url).unwrap());
+        let label = n.label();
+        g.add_node_with_label_and_location(n, label, Pos2::ZERO)
+    }
+
+    #[test]
+    fn batches_articles_of_one_wiki_once() {
+        let mut g = Graph::new(StableGraph::new());
+        let a = add_node(&mut g, "https://en.wikipedia.org/wiki/A");
+        add_node(&mut g, "https://en.wikipedia.org/wiki/Category:A");
+        let de = add_node(&mut g, "https://de.wikipedia.org/wiki/A");
+        let b = add_node(&mut g, "https://en.wikipedia.org/wiki/B");
+
+        let mut batcher = Batcher::default();
+        let indices = |batch: Vec<(NodeIndex, url::Url)>| {
+            batch.into_iter().map(|(idx, _)| idx).collect::<Vec<_>>()
+        };
+        assert_eq!(indices(batcher.next(&g)), [a, b]);
+        assert_eq!(indices(batcher.next(&g)), [de]);
+        assert!(batcher.next(&g).is_empty());
+
+        batcher.remove(b);
+        assert_eq!(indices(batcher.next(&g)), [b]);
+
+        batcher.remove(a);
+        batcher.set_unsupported();
+        assert!(batcher.next(&g).is_empty());
+    }
+}
@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+
+/// Every action the user can trigger from the keyboard or from the command palette.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Command {
+    NextChild,
+    PrevChild,
+    NextArticle,
+    PrevArticle,
+    NextRoot,
+    PrevRoot,
+    Expand,
+    ExpandBacklinks,
+    DeleteNode,
+    DeleteSubtree,
+    CollapseChildren,
+    PruneLeaves,
+    Undo,
+    Redo,
+    ToggleCategoryMode,
+    ToggleThumbnails,
+    ToggleNeighbourhood,
+    OpenUrl,
+    CompareLanguages,
+    CommandPalette,
+    Settings,
+    Export,
+    NewExploration,
+    NewTab,
+    CloseTab,
+    OpenInNewTab,
+    SaveSnapshot,
+    Snapshots,
+    RevisionTimeline,
+}
+
+impl Command {
+    pub fn all() -> &'static [Command] {
+        &[
+            Command::NextChild,
+            Command::PrevChild,
+            Command::NextArticle,
+            Command::PrevArticle,
+            Command::NextRoot,
+            Command::PrevRoot,
+            Command::Expand,
+            Command::ExpandBacklinks,
+            Command::DeleteNode,
+            Command::DeleteSubtree,
+            Command::CollapseChildren,
+            Command::PruneLeaves,
+            Command::Undo,
+            Command::Redo,
+            Command::ToggleCategoryMode,
+            Command::ToggleThumbnails,
+            Command::ToggleNeighbourhood,
+            Command::OpenUrl,
+            Command::CompareLanguages,
+            Command::CommandPalette,
+            Command::Settings,
+            Command::Export,
+            Command::NewExploration,
+            Command::NewTab,
+            Command::CloseTab,
+            Command::OpenInNewTab,
+            Command::SaveSnapshot,
+            Command::Snapshots,
+            Command::RevisionTimeline,
+        ]
+    }
+
+    /// Human readable name used in the command palette and in the settings.
+    pub fn title(&self) -> &'static str {
+        match self {
+            Command::NextChild => "select next link",
+            Command::PrevChild => "select previous link",
+            Command::NextArticle => "select next article",
+            Command::PrevArticle => "select previous article",
+            Command::NextRoot => "go to next root",
+            Command::PrevRoot => "go to previous root",
+            Command::Expand => "expand selected article",
+            Command::ExpandBacklinks => "expand articles linking to selected",
+            Command::DeleteNode => "delete selected node",
+            Command::DeleteSubtree => "delete selected node with nodes attached only through it",
+            Command::CollapseChildren => "collapse or restore links of selected found nowhere else",
+            Command::PruneLeaves => "delete nodes with a single neighbour",
+            Command::Undo => "undo last change of the graph",
+            Command::Redo => "redo last undone change of the graph",
+            Command::ToggleCategoryMode => "toggle expanding categories of articles",
+            Command::ToggleThumbnails => "toggle thumbnails of media in the graph",
+            Command::ToggleNeighbourhood => "toggle highlighting neighbours of hovered article",
+            Command::OpenUrl => "open selected url in browser",
+            Command::CompareLanguages => "compare selected article across languages",
+            Command::CommandPalette => "command palette",
+            Command::Settings => "settings",
+            Command::Export => "export graph image",
+            Command::NewExploration => "start new exploration from another article",
+            Command::NewTab => "open new tab",
+            Command::CloseTab => "close tab",
+            Command::OpenInNewTab => "explore selected article in new tab",
+            Command::SaveSnapshot => "save snapshot of the graph",
+            Command::Snapshots => "compare saved snapshots",
+            Command::RevisionTimeline => "animate link changes over revisions of selected article",
+        }
+    }
+
+    /// Commands which can only be executed when the graph is loaded and a node is selected.
+    pub fn needs_graph(&self) -> bool {
+        !matches!(
+            self,
+            Command::CommandPalette
+                | Command::Settings
+                | Command::Export
+                | Command::NewExploration
+                | Command::NewTab
+                | Command::CloseTab
+                | Command::Snapshots
+                | Command::CompareLanguages
+                | Command::RevisionTimeline
+                | Command::ToggleCategoryMode
+                | Command::ToggleThumbnails
+                | Command::ToggleNeighbourhood
+        )
+    }
+}
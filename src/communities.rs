@@ -0,0 +1,204 @@
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
+
+use petgraph::{stable_graph::NodeIndex, Direction::Incoming, Direction::Outgoing};
+
+use crate::{graph::Graph, metrics::Metrics};
+
+const MAX_ITERATIONS: usize = 20;
+
+/// Detection runs over the whole graph so while links are arriving it is rerun
+/// not more often than this.
+const UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+
+pub struct Community {
+    /// Index of the center node, so the id survives recomputation if the center stays the same.
+    pub id: usize,
+    /// Member with the highest page rank. Used as the community label.
+    pub center: NodeIndex,
+    pub members: Vec<NodeIndex>,
+}
+
+/// Communities of the links graph found with label propagation.
+#[derive(Default)]
+pub struct Communities {
+    /// Sorted by size in descending order.
+    list: Vec<Community>,
+    by_node: HashMap<NodeIndex, usize>,
+
+    dirty: bool,
+    computed_at: Option<Instant>,
+}
+
+impl Communities {
+    /// Marks communities as outdated. Should be called on every graph change.
+    pub fn invalidate(&mut self) {
+        self.dirty = true;
+    }
+
+    pub fn list(&self) -> &[Community] {
+        &self.list
+    }
+
+    pub fn get(&self, id: usize) -> Option<&Community> {
+        self.list.iter().find(|c| c.id == id)
+    }
+
+    /// Returns community id of the node.
+    pub fn of(&self, idx: NodeIndex) -> Option<usize> {
+        self.by_node.get(&idx).cloned()
+    }
+
+    /// Reruns detection if the graph changed. Returns true if communities were recomputed.
+    pub fn update(&mut self, g: &Graph, metrics: &Metrics) -> bool {
+        let due = self.dirty
+            && self
+                .computed_at
+                .map_or(true, |at| at.elapsed() >= UPDATE_INTERVAL);
+        if !due {
+            return false;
+        }
+
+        let mut members_by_label: HashMap<NodeIndex, Vec<NodeIndex>> = HashMap::new();
+        label_propagation(g)
+            .into_iter()
+            .for_each(|(idx, label)| members_by_label.entry(label).or_default().push(idx));
+
+        let page_rank = |idx: &NodeIndex| metrics.get(*idx).map_or(0., |m| m.page_rank);
+        self.list = members_by_label
+            .into_values()
+            .map(|mut members| {
+                members.sort();
+                let center = *members
+                    .iter()
+                    .max_by(|l, r| page_rank(l).total_cmp(&page_rank(r)))
+                    .unwrap();
+                Community {
+                    id: center.index(),
+                    center,
+                    members,
+                }
+            })
+            .collect();
+        self.list
+            .sort_by(|l, r| r.members.len().cmp(&l.members.len()).then(l.id.cmp(&r.id)));
+
+        self.by_node = self
+            .list
+            .iter()
+            .flat_map(|c| c.members.iter().map(|idx| (*idx, c.id)))
+            .collect();
+
+        self.dirty = false;
+        self.computed_at = Some(Instant::now());
+
+        true
+    }
+}
+
+/// Every node takes the label which is the most frequent among its neighbours, links are treated
+/// as undirected. Nodes are visited in index order and ties are broken by the smallest label,
+/// so the result is deterministic.
+fn label_propagation(g: &Graph) -> HashMap<NodeIndex, NodeIndex> {
+    let mut nodes = g.g.node_indices().collect::<Vec<_>>();
+    nodes.sort();
+
+    let mut labels = nodes
+        .iter()
+        .map(|idx| (*idx, *idx))
+        .collect::<HashMap<_, _>>();
+
+    for _ in 0..MAX_ITERATIONS {
+        let mut changed = false;
+
+        nodes.iter().for_each(|idx| {
+            let mut counts: BTreeMap<NodeIndex, usize> = BTreeMap::new();
+            g.g.neighbors_directed(*idx, Outgoing)
+                .chain(g.g.neighbors_directed(*idx, Incoming))
+                .filter(|n| n != idx)
+                .for_each(|n| *counts.entry(labels[&n]).or_default() += 1);
+
+            let max = match counts.values().max() {
+                Some(max) => *max,
+                None => return,
+            };
+
+            let curr = labels[idx];
+            if counts.get(&curr) == Some(&max) {
+                return;
+            }
+
+            let best = counts
+                .iter()
+                .find(|(_, count)| **count == max)
+                .map(|(label, _)| *label)
+                .unwrap();
+            labels.insert(*idx, best);
+            changed = true;
+        });
+
+        if !changed {
+            break;
+        }
+    }
+
+    labels
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::Pos2;
+    use petgraph::stable_graph::StableGraph;
+
+    use super::*;
+    use crate::{edge::Edge, node::Node, testing::url};
+
+    /// Two cliques of four articles, the last article of the first one links the first of the
+    /// second one.
+    fn two_cliques() -> Graph {
+        let mut g = Graph::new(StableGraph::new());
+        let nodes = (0..8)
+            .map(|i| {
+                let n = Node::new(url(&i.to_string()));
+                let label = n.label();
+                g.add_node_with_label_and_location(n, label, Pos2::ZERO)
+            })
+            .collect::<Vec<_>>();
+
+        [&nodes[..4], &nodes[4..]].into_iter().for_each(|clique| {
+            clique.iter().for_each(|from| {
+                clique.iter().filter(|to| *to != from).for_each(|to| {
+                    g.add_edge(*from, *to, Edge::new(Default::default()));
+                });
+            });
+        });
+        g.add_edge(nodes[3], nodes[4], Edge::new(Default::default()));
+        g
+    }
+
+    // centralities are computed in the background
+    #[tokio::test]
+    async fn cliques_are_communities_around_their_bridge() {
+        let g = two_cliques();
+        let mut metrics = Metrics::default();
+        metrics.invalidate();
+        metrics.update(&g);
+        let mut communities = Communities::default();
+        communities.invalidate();
+
+        assert!(communities.update(&g, &metrics));
+
+        let list = communities.list();
+        assert_eq!(list.len(), 2);
+        let members = |c: &Community| c.members.iter().map(|idx| idx.index()).collect::<Vec<_>>();
+        let (first, second) = match list[0].members[0].index() {
+            0 => (&list[0], &list[1]),
+            _ => (&list[1], &list[0]),
+        };
+        assert_eq!(members(first), [0, 1, 2, 3]);
+        assert_eq!(members(second), [4, 5, 6, 7]);
+        assert_eq!(first.center.index(), 3);
+        assert_eq!(second.center.index(), 4);
+        assert_eq!(communities.of(NodeIndex::new(1)), Some(first.id));
+    }
+}
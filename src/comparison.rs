@@ -0,0 +1,127 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    io,
+    sync::Arc,
+};
+
+use crate::{
+    entities::BATCH_SIZE,
+    link_source::{Error, LinkSource},
+    url::{self, Url},
+};
+
+/// Outgoing links of the editions of one article in several languages keyed by wikidata items,
+/// so the same link target is recognized in every language.
+pub struct Comparison {
+    pub article: String,
+    /// Languages which have the article, the compared article language is the first one.
+    pub langs: Vec<String>,
+    pub targets: Vec<Target>,
+}
+
+/// Link target found in at least one of the editions.
+pub struct Target {
+    pub qid: String,
+    /// Label in the first language which links it.
+    pub label: String,
+    pub langs: BTreeSet<String>,
+}
+
+impl Comparison {
+    /// Targets linked by every edition.
+    pub fn shared(&self) -> impl Iterator<Item = &Target> {
+        self.targets
+            .iter()
+            .filter(|t| t.langs.len() == self.langs.len())
+    }
+
+    /// Targets linked only by the edition in the language.
+    pub fn unique<'a>(&'a self, lang: &'a str) -> impl Iterator<Item = &'a Target> {
+        self.targets
+            .iter()
+            .filter(move |t| t.langs.len() == 1 && t.langs.contains(lang))
+    }
+}
+
+/// Follows interlanguage links of the article to the editions in the languages and maps their
+/// outgoing article links to wikidata items. Languages without the article are skipped. The
+/// editions are retrieved at once.
+pub async fn compare(
+    source: Arc<dyn LinkSource>,
+    url: Url,
+    langs: Vec<String>,
+) -> Result<Comparison, Error> {
+    let lang = url
+        .lang()
+        .ok_or_else(|| Error::NotFound(format!("language of {}", url.val())))?
+        .to_string();
+
+    let mut editions = vec![(lang.clone(), url.clone())];
+    source
+        .langlinks(&url)
+        .await?
+        .into_iter()
+        .filter_map(|u| Some((u.lang()?.to_string(), u)))
+        .filter(|(l, _)| langs.contains(l) && *l != lang)
+        .for_each(|edition| editions.push(edition));
+
+    let tasks = editions
+        .iter()
+        .map(|(_, edition)| {
+            let (source, edition) = (source.clone(), edition.clone());
+            tokio::spawn(async move { linked_items(source.as_ref(), &edition).await })
+        })
+        .collect::<Vec<_>>();
+
+    // labels are taken from the first edition which links the item
+    let mut targets: BTreeMap<String, Target> = BTreeMap::new();
+    for ((lang, _), task) in editions.iter().zip(tasks) {
+        task.await
+            .map_err(io::Error::from)??
+            .into_iter()
+            .for_each(|(u, qid)| {
+                targets
+                    .entry(qid.clone())
+                    .or_insert_with(|| Target {
+                        qid,
+                        label: u.val_for_type().replace('_', " "),
+                        langs: BTreeSet::new(),
+                    })
+                    .langs
+                    .insert(lang.clone());
+            });
+    }
+
+    let mut targets = targets.into_values().collect::<Vec<_>>();
+    targets.sort_by(|l, r| {
+        r.langs
+            .len()
+            .cmp(&l.langs.len())
+            .then(l.label.cmp(&r.label))
+    });
+
+    Ok(Comparison {
+        article: url.val_for_type().replace('_', " "),
+        langs: editions.into_iter().map(|(lang, _)| lang).collect(),
+        targets,
+    })
+}
+
+/// Wikidata items of the articles of the same wiki linked by the edition.
+async fn linked_items(source: &dyn LinkSource, edition: &Url) -> Result<Vec<(Url, String)>, Error> {
+    let mut seen = HashSet::new();
+    let links = source
+        .links(edition)
+        .await?
+        .into_iter()
+        .map(|l| l.url)
+        .filter(|u| u.url_type() == url::Type::Article && u.host() == edition.host())
+        .filter(|u| u != edition && seen.insert(u.clone()))
+        .collect::<Vec<_>>();
+
+    let mut items = vec![];
+    for chunk in links.chunks(BATCH_SIZE) {
+        items.extend(source.qids(chunk).await?);
+    }
+    Ok(items)
+}
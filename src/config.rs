@@ -0,0 +1,110 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+
+use crate::{disambiguation::Handling, keymap::Keymap, link_source::Backend, views::style::Theme};
+
+const CONFIG_ENV: &str = "WIKILINKS_CONFIG";
+const CONFIG_FILE: &str = "wikilinks.toml";
+
+/// User configuration. Loaded from `wikilinks.toml` in the working directory or from the path
+/// in `WIKILINKS_CONFIG` env variable.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub keymap: Keymap,
+    /// Where articles are retrieved from.
+    pub source: Backend,
+    /// How disambiguation pages are shown.
+    pub disambiguation: Handling,
+    pub theme: Theme,
+    /// Why the config file could not be read. The file is not overwritten while it is set, so
+    /// a typo does not cost the user the rest of their config.
+    #[serde(skip)]
+    pub error: Option<String>,
+}
+
+impl Config {
+    /// Loads config from the config file. Falls back to defaults if the file is missing or invalid.
+    pub fn load() -> Self {
+        Self::load_from(&path())
+    }
+
+    fn load_from(path: &Path) -> Self {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(_) => {
+                info!("config not found at {}; using defaults", path.display());
+                return Self::default();
+            }
+        };
+
+        match toml::from_str(&content) {
+            Ok(config) => {
+                info!("loaded config from {}", path.display());
+                config
+            }
+            Err(err) => {
+                error!("error parsing config {}: {}", path.display(), err);
+                Self {
+                    error: Some(err.to_string()),
+                    ..Self::default()
+                }
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        self.save_to(&path())
+    }
+
+    fn save_to(&self, path: &Path) {
+        if self.error.is_some() {
+            error!("config {} is invalid, not overwriting it", path.display());
+            return;
+        }
+
+        let content = match toml::to_string_pretty(self) {
+            Ok(content) => content,
+            Err(err) => {
+                error!("error serializing config: {}", err);
+                return;
+            }
+        };
+
+        match fs::write(path, content) {
+            Ok(_) => info!("saved config to {}", path.display()),
+            Err(err) => error!("error saving config to {}: {}", path.display(), err),
+        }
+    }
+}
+
+fn path() -> PathBuf {
+    env::var(CONFIG_ENV)
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(CONFIG_FILE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_config_is_not_overwritten() {
+        let path = env::temp_dir().join(format!("wikilinks-config-{}.toml", std::process::id()));
+        let content = "[keymap]\nexpand = [\"E\"\n";
+        fs::write(&path, content).unwrap();
+
+        let mut config = Config::load_from(&path);
+        assert!(config.error.is_some());
+        config.disambiguation = Handling::Hide;
+        config.save_to(&path);
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,17 +1,17 @@
 use std::collections::{HashMap, HashSet};
 
-use egui_graphs::Graph;
 use petgraph::{
     stable_graph::NodeIndex,
+    visit::Bfs,
     Directed,
     Direction::{Incoming, Outgoing},
 };
 
-use crate::node::Node;
+use crate::graph::Graph;
 
 pub type Position = (NodeIndex, NodeIndex);
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct Cursor {
     /// All the roots and their children. Root itself is included in children. Children are sorted.
     elements_by_root: HashMap<NodeIndex, Vec<NodeIndex>>,
@@ -28,7 +28,7 @@ pub struct Cursor {
 }
 
 impl Cursor {
-    pub fn new(root: NodeIndex, g: &Graph<Node, (), Directed>) -> Self {
+    pub fn new(root: NodeIndex, g: &Graph) -> Self {
         let mut elements_by_root = HashMap::new();
         let elements = get_children_unique_inclusive_sorted(root, g);
 
@@ -58,10 +58,29 @@ impl Cursor {
         self.position
     }
 
+    /// Moves the cursor to the element of the root if the root has it.
+    pub fn move_to(&mut self, position: Position) {
+        if self
+            .elements_by_root
+            .get(&position.0)
+            .is_some_and(|elements| elements.contains(&position.1))
+        {
+            self.position = position;
+        }
+    }
+
     /// Updates cursor with new roots and elements.
     ///
     /// Provided graph should already contain the root and all his children.
-    pub fn update(&mut self, root: NodeIndex, g: &Graph<Node, (), Directed>) {
+    pub fn update(&mut self, root: NodeIndex, g: &Graph) {
+        self.attach(root, self.position.0, g);
+        self.position = (root, root);
+    }
+
+    /// Adds root under the parent root without moving the cursor.
+    ///
+    /// Provided graph should already contain the root and all his children.
+    pub fn attach(&mut self, root: NodeIndex, parent: NodeIndex, g: &Graph) {
         let elements = get_children_unique_inclusive_sorted(root, g);
         elements.iter().for_each(|idx| {
             if let Some(val) = self.roots_by_element.get_mut(idx) {
@@ -74,9 +93,7 @@ impl Cursor {
         });
         self.elements_by_root.insert(root, elements);
 
-        self.add_root_to_tree(root);
-
-        self.position = (root, root);
+        self.add_root_to_tree(root, parent);
     }
 
     /// Gets the next element relative to the cursor position.
@@ -188,22 +205,125 @@ impl Cursor {
         Some(roots[0])
     }
 
+    /// Replaces the node with another one everywhere, used when two nodes are merged.
+    ///
+    /// If both nodes are roots their elements are joined under the new one.
+    pub fn replace(&mut self, old: NodeIndex, new: NodeIndex) {
+        let replace = |idx: &mut NodeIndex| {
+            if *idx == old {
+                *idx = new;
+            }
+        };
+
+        self.elements_by_root.values_mut().for_each(|elements| {
+            elements.iter_mut().for_each(replace);
+            elements.sort();
+            elements.dedup();
+        });
+        if let Some(elements) = self.elements_by_root.remove(&old) {
+            let joined = self.elements_by_root.entry(new).or_default();
+            joined.extend(elements);
+            joined.sort();
+            joined.dedup();
+        }
+
+        if let Some(roots) = self.roots_by_element.remove(&old) {
+            self.roots_by_element.entry(new).or_default().extend(roots);
+        }
+        self.roots_by_element.values_mut().for_each(|roots| {
+            if roots.remove(&old) {
+                roots.insert(new);
+            }
+        });
+
+        self.roots_tree.node_weights_mut().for_each(replace);
+
+        replace(&mut self.position.0);
+        replace(&mut self.position.1);
+    }
+
+    /// Root the exploration started from.
+    pub fn origin(&self) -> NodeIndex {
+        let rt_idx = self.roots_tree.externals(Incoming).next().unwrap();
+        *self.roots_tree.node_weight(rt_idx).unwrap()
+    }
+
+    /// Forgets the removed node. If it is a root its child roots are attached to its parent.
+    ///
+    /// The origin can not be removed.
+    pub fn remove(&mut self, idx: NodeIndex) {
+        self.elements_by_root
+            .values_mut()
+            .for_each(|elements| elements.retain(|el| *el != idx));
+        self.elements_by_root.remove(&idx);
+
+        self.roots_by_element.remove(&idx);
+        self.roots_by_element.values_mut().for_each(|roots| {
+            roots.remove(&idx);
+        });
+        self.roots_by_element.retain(|_, roots| !roots.is_empty());
+
+        let mut parent = None;
+        while let Some(rt_idx) = self
+            .roots_tree
+            .node_indices()
+            .find(|i| idx == *self.roots_tree.node_weight(*i).unwrap())
+        {
+            let rt_parent = self.roots_tree.neighbors_directed(rt_idx, Incoming).next();
+            let children = self
+                .roots_tree
+                .neighbors_directed(rt_idx, Outgoing)
+                .collect::<Vec<_>>();
+            if let Some(rt_parent) = rt_parent {
+                children.into_iter().for_each(|child| {
+                    self.roots_tree.add_edge(rt_parent, child, ());
+                });
+                parent = self.roots_tree.node_weight(rt_parent).copied();
+            }
+            self.roots_tree.remove_node(rt_idx);
+        }
+
+        if self.position.0 == idx {
+            let root = parent.unwrap_or_else(|| self.origin());
+            self.position = (root, root);
+        } else if self.position.1 == idx {
+            self.position.1 = self.position.0;
+        }
+    }
+
+    /// Roots together with their parent roots, every parent comes before its children.
+    pub fn roots_with_parents(&self) -> Vec<(NodeIndex, Option<NodeIndex>)> {
+        let start = self.roots_tree.externals(Incoming).next().unwrap();
+        let mut bfs = Bfs::new(&self.roots_tree, start);
+        let mut roots = vec![];
+        while let Some(rt_idx) = bfs.next(&self.roots_tree) {
+            let parent = self
+                .roots_tree
+                .neighbors_directed(rt_idx, Incoming)
+                .next()
+                .map(|p| *self.roots_tree.node_weight(p).unwrap());
+            roots.push((*self.roots_tree.node_weight(rt_idx).unwrap(), parent));
+        }
+        roots
+    }
+
+    pub fn is_root(&self, idx: NodeIndex) -> bool {
+        self.elements_by_root.contains_key(&idx)
+    }
+
     /// Adds root node to the root node tree.
-    fn add_root_to_tree(&mut self, root: NodeIndex) {
+    fn add_root_to_tree(&mut self, root: NodeIndex, parent: NodeIndex) {
         let root_idx = self.roots_tree.add_node(root);
         let parent_idx = self
             .roots_tree
             .node_indices()
-            .find(|i| self.position.0 == *self.roots_tree.node_weight(*i).unwrap())
+            .find(|i| parent == *self.roots_tree.node_weight(*i).unwrap())
             .unwrap();
         self.roots_tree.add_edge(parent_idx, root_idx, ());
     }
 }
 
-fn get_children_unique_inclusive_sorted(
-    root: NodeIndex,
-    g: &Graph<Node, (), Directed>,
-) -> Vec<NodeIndex> {
+fn get_children_unique_inclusive_sorted(root: NodeIndex, g: &Graph) -> Vec<NodeIndex> {
     let mut children = g.g.neighbors_directed(root, Outgoing).collect::<Vec<_>>();
     children.push(root);
     children.sort();
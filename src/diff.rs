@@ -0,0 +1,126 @@
+use std::collections::HashSet;
+
+use crate::session::{Session, SessionLink};
+
+/// How an article or a link changed between two sessions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    Added,
+    Removed,
+}
+
+/// Articles and links found only in one of two sessions.
+#[derive(Debug, Default)]
+pub struct Diff {
+    pub added_nodes: Vec<String>,
+    pub removed_nodes: Vec<String>,
+    pub added_links: Vec<(String, String)>,
+    pub removed_links: Vec<(String, String)>,
+}
+
+impl Diff {
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.added_links.is_empty()
+            && self.removed_links.is_empty()
+    }
+}
+
+/// Compares the newer session with the older one. Everything keeps the order of its session.
+pub fn diff(old: &Session, new: &Session) -> Diff {
+    Diff {
+        added_nodes: missing(&new.nodes, &old.nodes),
+        removed_nodes: missing(&old.nodes, &new.nodes),
+        added_links: missing(&keys(&new.links), &keys(&old.links)),
+        removed_links: missing(&keys(&old.links), &keys(&new.links)),
+    }
+}
+
+/// Session with everything of the newer session and what was removed from the older one, so
+/// both can be shown in one graph.
+pub fn union(old: &Session, new: &Session, diff: &Diff) -> Session {
+    let removed = diff.removed_links.iter().collect::<HashSet<_>>();
+    let mut union = new.clone();
+    union.nodes.extend(diff.removed_nodes.iter().cloned());
+    union.links.extend(
+        old.links
+            .iter()
+            .filter(|l| removed.contains(&l.key()))
+            .cloned(),
+    );
+    union
+}
+
+fn keys(links: &[SessionLink]) -> Vec<(String, String)> {
+    links.iter().map(SessionLink::key).collect()
+}
+
+/// Items of the first list which are not in the second one.
+fn missing<T: Clone + Eq + std::hash::Hash>(items: &[T], other: &[T]) -> Vec<T> {
+    let other = other.iter().collect::<HashSet<_>>();
+    items
+        .iter()
+        .filter(|item| !other.contains(item))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(nodes: &[&str], links: &[(&str, &str)]) -> Session {
+        Session {
+            saved_at: 0,
+            roots: vec![nodes[0].to_string()],
+            nodes: nodes.iter().map(|n| n.to_string()).collect(),
+            links: links
+                .iter()
+                .map(|(from, to)| SessionLink {
+                    from: from.to_string(),
+                    to: to.to_string(),
+                    contexts: vec![],
+                })
+                .collect(),
+            communities: Default::default(),
+        }
+    }
+
+    #[test]
+    fn lists_added_and_removed() {
+        let old = session(&["A", "B", "C"], &[("A", "B"), ("A", "C"), ("B", "C")]);
+        let new = session(&["A", "C", "D"], &[("A", "C"), ("A", "D"), ("C", "A")]);
+
+        let diff = diff(&old, &new);
+
+        assert_eq!(diff.added_nodes, vec!["D"]);
+        assert_eq!(diff.removed_nodes, vec!["B"]);
+        assert_eq!(
+            diff.added_links,
+            vec![
+                ("A".to_string(), "D".to_string()),
+                ("C".to_string(), "A".to_string())
+            ]
+        );
+        assert_eq!(
+            diff.removed_links,
+            vec![
+                ("A".to_string(), "B".to_string()),
+                ("B".to_string(), "C".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn union_keeps_removed() {
+        let old = session(&["A", "B"], &[("A", "B")]);
+        let new = session(&["A", "C"], &[("A", "C")]);
+
+        let union = union(&old, &new, &diff(&old, &new));
+
+        assert_eq!(union.nodes, vec!["A", "C", "B"]);
+        assert_eq!(union.links.len(), 2);
+        assert!(diff(&new, &new).is_empty());
+    }
+}
@@ -0,0 +1,66 @@
+use std::collections::HashSet;
+
+use petgraph::stable_graph::NodeIndex;
+use serde::{Deserialize, Serialize};
+
+use crate::{batcher::Batcher, graph::Graph, url};
+
+/// How disambiguation pages are shown in the graph.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Handling {
+    /// Drawn as hollow nodes.
+    #[default]
+    Style,
+    /// Not drawn together with their links.
+    Hide,
+    /// Drawn as hollow nodes, expanding one asks for the intended article instead.
+    Prompt,
+}
+
+impl Handling {
+    pub fn all() -> &'static [Handling] {
+        &[Handling::Style, Handling::Hide, Handling::Prompt]
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Handling::Style => "distinct style",
+            Handling::Hide => "hide",
+            Handling::Prompt => "choose target on expand",
+        }
+    }
+}
+
+/// Disambiguation pages among the article nodes.
+///
+/// Articles are checked in batches of the same wiki while the graph grows.
+#[derive(Default)]
+pub struct Disambiguations {
+    pages: HashSet<NodeIndex>,
+    batcher: Batcher,
+}
+
+impl Disambiguations {
+    pub fn contains(&self, idx: NodeIndex) -> bool {
+        self.pages.contains(&idx)
+    }
+
+    pub fn set_unsupported(&mut self) {
+        self.batcher.set_unsupported();
+    }
+
+    /// Takes the next batch of unchecked article nodes of the same wiki.
+    pub fn next_batch(&mut self, g: &Graph) -> Vec<(NodeIndex, url::Url)> {
+        self.batcher.next(g)
+    }
+
+    pub fn insert(&mut self, idx: NodeIndex) {
+        self.pages.insert(idx);
+    }
+
+    pub fn remove(&mut self, idx: NodeIndex) {
+        self.pages.remove(&idx);
+        self.batcher.remove(idx);
+    }
+}
@@ -0,0 +1,458 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    fs::{self, File},
+    hash::{Hash, Hasher},
+    io::{self, BufRead, BufReader},
+    path::{Path, PathBuf},
+};
+
+use flate2::read::GzDecoder;
+use log::info;
+
+use crate::url::Url;
+
+const PAGE_SUFFIX: &str = "-page.sql.gz";
+const PAGELINKS_SUFFIX: &str = "-pagelinks.sql.gz";
+const REDIRECT_SUFFIX: &str = "-redirect.sql.gz";
+const LINKTARGET_SUFFIX: &str = "-linktarget.sql.gz";
+
+const ARTICLE_NAMESPACE: &str = "0";
+
+/// Imports links graph from wikipedia SQL dumps without touching the network.
+///
+/// Expects a directory with `<wiki>-<date>-page.sql.gz` and `<wiki>-<date>-pagelinks.sql.gz`.
+/// `redirect.sql.gz` is optional and is used to resolve links to redirects. Newer dumps store
+/// link targets in `linktarget.sql.gz` which is required for them.
+///
+/// Dumps are streamed. Only hashes of the article titles and the visited part of the graph are
+/// kept in memory, titles of the visited articles are read in one more pass over `page` at the
+/// end. Every level of depth is one pass over `pagelinks`.
+pub struct DumpImport {
+    pub dir: PathBuf,
+    /// Titles of the articles to start from.
+    pub seeds: Vec<String>,
+    pub depth: usize,
+}
+
+/// Links graph read from the dumps.
+pub struct ImportedGraph {
+    /// Articles the import started from, the first one is the root.
+    pub seeds: Vec<Url>,
+    /// Links in the order they were found. Every link starts from a seed or from an article
+    /// linked before it.
+    pub links: Vec<(Url, Url)>,
+}
+
+/// Hash standing for the title while the dumps are read, so that millions of titles are not
+/// held as strings. Two titles of one wiki sharing a hash are unlikely enough to be ignored.
+type TitleHash = u64;
+
+/// Articles by title. Only the article namespace is kept.
+struct Pages {
+    id_by_title: HashMap<TitleHash, u64>,
+    redirects: HashSet<u64>,
+}
+
+impl DumpImport {
+    pub fn import(&self) -> io::Result<ImportedGraph> {
+        let page_path = self
+            .find(PAGE_SUFFIX)?
+            .ok_or_else(|| not_found(PAGE_SUFFIX))?;
+        let pagelinks_path = self
+            .find(PAGELINKS_SUFFIX)?
+            .ok_or_else(|| not_found(PAGELINKS_SUFFIX))?;
+        let lang = lang(&page_path);
+
+        let pages = read_pages(&page_path)?;
+        info!("read {} articles", pages.id_by_title.len());
+
+        let redirects = match self.find(REDIRECT_SUFFIX)? {
+            Some(path) => read_redirects(&path, &pages)?,
+            None => HashMap::new(),
+        };
+        info!("read {} redirects", redirects.len());
+
+        let link_targets = match self.find(LINKTARGET_SUFFIX)? {
+            Some(path) => read_link_targets(&path)?,
+            None => HashMap::new(),
+        };
+
+        let resolve = |title: TitleHash| -> Option<u64> {
+            let id = *pages.id_by_title.get(&title)?;
+            match pages.redirects.contains(&id) {
+                true => redirects
+                    .get(&id)
+                    .and_then(|t| pages.id_by_title.get(t))
+                    .cloned(),
+                false => Some(id),
+            }
+        };
+
+        let mut visited = HashSet::new();
+        let seeds = self
+            .seeds
+            .iter()
+            .filter_map(|title| resolve(title_hash(&title.replace(' ', "_"))))
+            .filter(|id| visited.insert(*id))
+            .collect::<Vec<_>>();
+        if seeds.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "none of the seeds were found in the dump",
+            ));
+        }
+
+        let mut frontier = visited.clone();
+        let mut links = vec![];
+        for level in 0..self.depth {
+            let mut next = HashSet::new();
+            for_each_tuple(&pagelinks_path, |fields| {
+                let from = match fields.first().and_then(|f| f.parse::<u64>().ok()) {
+                    Some(from) if frontier.contains(&from) => from,
+                    _ => return,
+                };
+
+                // older dumps store namespace and title, newer ones reference linktarget
+                let (namespace, title) = match fields.len() {
+                    3 => match fields[2].parse().ok().and_then(|id| link_targets.get(&id)) {
+                        Some(title) => (ARTICLE_NAMESPACE, *title),
+                        None => return,
+                    },
+                    _ => (fields[1].as_str(), title_hash(&fields[2])),
+                };
+                if namespace != ARTICLE_NAMESPACE {
+                    return;
+                }
+
+                if let Some(to) = resolve(title) {
+                    links.push((from, to));
+                    if visited.insert(to) {
+                        next.insert(to);
+                    }
+                }
+            })?;
+
+            info!(
+                "depth {}: {} links, {} new articles",
+                level + 1,
+                links.len(),
+                next.len()
+            );
+            frontier = next;
+        }
+
+        let titles = read_titles(&page_path, &visited)?;
+        let url = |id: &u64| article_url(&lang, &titles[id]);
+        Ok(ImportedGraph {
+            seeds: seeds.iter().map(url).collect::<io::Result<_>>()?,
+            links: links
+                .iter()
+                .map(|(from, to)| Ok((url(from)?, url(to)?)))
+                .collect::<io::Result<_>>()?,
+        })
+    }
+
+    fn find(&self, suffix: &str) -> io::Result<Option<PathBuf>> {
+        Ok(fs::read_dir(&self.dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .find(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .map_or(false, |name| name.ends_with(suffix))
+            }))
+    }
+}
+
+fn read_pages(path: &Path) -> io::Result<Pages> {
+    let mut pages = Pages {
+        id_by_title: HashMap::new(),
+        redirects: HashSet::new(),
+    };
+
+    // page_id, page_namespace, page_title, page_is_redirect, ...
+    for_each_tuple(path, |fields| {
+        if fields.len() < 4 || fields[1] != ARTICLE_NAMESPACE {
+            return;
+        }
+        let id = match fields[0].parse() {
+            Ok(id) => id,
+            Err(_) => return,
+        };
+
+        if fields[3] == "1" {
+            pages.redirects.insert(id);
+        }
+        pages.id_by_title.insert(title_hash(&fields[2]), id);
+    })?;
+
+    Ok(pages)
+}
+
+/// Titles of the articles by their ids.
+fn read_titles(path: &Path, ids: &HashSet<u64>) -> io::Result<HashMap<u64, String>> {
+    let mut titles = HashMap::new();
+
+    for_each_tuple(path, |fields| {
+        if fields.len() < 3 || fields[1] != ARTICLE_NAMESPACE {
+            return;
+        }
+        if let Some(id) = fields[0].parse().ok().filter(|id| ids.contains(id)) {
+            titles.insert(id, fields[2].clone());
+        }
+    })?;
+
+    Ok(titles)
+}
+
+fn read_redirects(path: &Path, pages: &Pages) -> io::Result<HashMap<u64, TitleHash>> {
+    let mut redirects = HashMap::new();
+
+    // rd_from, rd_namespace, rd_title, ...
+    for_each_tuple(path, |fields| {
+        if fields.len() < 3 || fields[1] != ARTICLE_NAMESPACE {
+            return;
+        }
+        if let Ok(from) = fields[0].parse() {
+            if pages.redirects.contains(&from) {
+                redirects.insert(from, title_hash(&fields[2]));
+            }
+        }
+    })?;
+
+    Ok(redirects)
+}
+
+fn read_link_targets(path: &Path) -> io::Result<HashMap<u64, TitleHash>> {
+    let mut targets = HashMap::new();
+
+    // lt_id, lt_namespace, lt_title
+    for_each_tuple(path, |fields| {
+        if fields.len() < 3 || fields[1] != ARTICLE_NAMESPACE {
+            return;
+        }
+        if let Ok(id) = fields[0].parse() {
+            targets.insert(id, title_hash(&fields[2]));
+        }
+    })?;
+
+    Ok(targets)
+}
+
+fn title_hash(title: &str) -> TitleHash {
+    let mut hasher = DefaultHasher::new();
+    title.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Streams gzipped SQL dump and calls `f` with the fields of every inserted row.
+fn for_each_tuple(path: &Path, f: impl FnMut(&[String])) -> io::Result<()> {
+    info!("reading {}", path.display());
+    parse_tuples(BufReader::new(GzDecoder::new(File::open(path)?)), f)
+}
+
+/// Calls `f` with the fields of every row inserted by the SQL statements.
+///
+/// Strings are unescaped, `NULL` and numbers are passed as they are.
+fn parse_tuples(mut reader: impl BufRead, mut f: impl FnMut(&[String])) -> io::Result<()> {
+    let mut line = vec![];
+    let mut fields = vec![];
+    let mut field = vec![];
+
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            return Ok(());
+        }
+        if !line.starts_with(b"INSERT INTO") {
+            continue;
+        }
+
+        let mut in_tuple = false;
+        let mut in_string = false;
+        let mut escaped = false;
+        for b in line.iter().skip_while(|b| **b != b'(') {
+            if in_string {
+                match (escaped, *b) {
+                    (true, b'n') => field.push(b'\n'),
+                    (true, b't') => field.push(b'\t'),
+                    (true, b'0') => field.push(0),
+                    (true, b) => field.push(b),
+                    (false, b'\\') => {
+                        escaped = true;
+                        continue;
+                    }
+                    (false, b'\'') => in_string = false,
+                    (false, b) => field.push(b),
+                }
+                escaped = false;
+                continue;
+            }
+
+            match *b {
+                b'(' if !in_tuple => in_tuple = true,
+                b'\'' if in_tuple => in_string = true,
+                b',' if in_tuple => fields.push(String::from_utf8_lossy(&field).to_string()),
+                b')' if in_tuple => {
+                    fields.push(String::from_utf8_lossy(&field).to_string());
+                    f(&fields);
+                    fields.clear();
+                    in_tuple = false;
+                }
+                _ if in_tuple => {
+                    field.push(*b);
+                    continue;
+                }
+                _ => (),
+            }
+            field.clear();
+        }
+    }
+}
+
+/// Gets language from the dump file name, e.g. `en` from `enwiki-20240101-page.sql.gz`.
+fn lang(path: &Path) -> String {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.split("wiki").next())
+        .unwrap_or("en")
+        .to_string()
+}
+
+fn article_url(lang: &str, title: &str) -> io::Result<Url> {
+    Url::wiki_article(&format!("{}.wikipedia.org", lang), title)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+fn not_found(suffix: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("dump file *{} not found", suffix),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, io::Write};
+
+    use flate2::{write::GzEncoder, Compression};
+
+    use super::*;
+
+    fn rows(sql: &str) -> Vec<Vec<String>> {
+        let mut rows = vec![];
+        parse_tuples(sql.as_bytes(), |fields| rows.push(fields.to_vec())).unwrap();
+        rows
+    }
+
+    #[test]
+    fn parses_inserted_rows() {
+        let sql = concat!(
+            "-- MySQL dump\n",
+            "CREATE TABLE `page` (`page_id` int);\n",
+            "INSERT INTO `page` VALUES (1,0,'Graph_theory',0),",
+            "(2,0,'Euler\\'s_(1736),_bridges',1);\n",
+            "INSERT INTO `page` VALUES (3,14,'Tab\\tand\\\\',NULL);\n",
+        );
+
+        assert_eq!(
+            rows(sql),
+            vec![
+                vec!["1", "0", "Graph_theory", "0"],
+                vec!["2", "0", "Euler's_(1736),_bridges", "1"],
+                vec!["3", "14", "Tab\tand\\", "NULL"],
+            ]
+        );
+    }
+
+    #[test]
+    fn gets_lang_from_file_name() {
+        assert_eq!(lang(Path::new("dumps/dewiki-20240101-page.sql.gz")), "de");
+    }
+
+    fn write_gz(path: &Path, sql: &str) {
+        let mut encoder = GzEncoder::new(File::create(path).unwrap(), Compression::fast());
+        encoder.write_all(sql.as_bytes()).unwrap();
+        encoder.finish().unwrap();
+    }
+
+    #[test]
+    fn imports_every_seed() {
+        let dir = env::temp_dir().join(format!("wikilinks-dump-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        write_gz(
+            &dir.join("enwiki-20240101-page.sql.gz"),
+            "INSERT INTO `page` VALUES (1,0,'A',0),(2,0,'B',0),(3,0,'C',0),(4,0,'D',0);\n",
+        );
+        // rows are sorted by the linking page, the second seed links first
+        write_gz(
+            &dir.join("enwiki-20240101-pagelinks.sql.gz"),
+            "INSERT INTO `pagelinks` VALUES (1,0,'B',0),(3,0,'D',0),(4,0,'A',0);\n",
+        );
+
+        let imported = DumpImport {
+            dir: dir.clone(),
+            seeds: vec!["C".to_string(), "A".to_string()],
+            depth: 1,
+        }
+        .import();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let imported = imported.unwrap();
+        let title = |u: &Url| u.val_for_type();
+        assert_eq!(
+            imported.seeds.iter().map(title).collect::<Vec<_>>(),
+            vec!["C", "A"]
+        );
+        assert_eq!(
+            imported
+                .links
+                .iter()
+                .map(|(from, to)| (title(from), title(to)))
+                .collect::<Vec<_>>(),
+            vec![
+                ("A".to_string(), "B".to_string()),
+                ("C".to_string(), "D".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn resolves_link_targets_and_redirects() {
+        let dir = env::temp_dir().join(format!("wikilinks-targets-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        write_gz(
+            &dir.join("enwiki-20240101-page.sql.gz"),
+            "INSERT INTO `page` VALUES (1,0,'A',0),(2,0,'B',1),(3,0,'C',0),(4,14,'D',0);\n",
+        );
+        write_gz(
+            &dir.join("enwiki-20240101-redirect.sql.gz"),
+            "INSERT INTO `redirect` VALUES (2,0,'C','',''),(4,0,'A','','');\n",
+        );
+        write_gz(
+            &dir.join("enwiki-20240101-linktarget.sql.gz"),
+            "INSERT INTO `linktarget` VALUES (10,0,'B'),(11,14,'D'),(12,0,'Missing');\n",
+        );
+        write_gz(
+            &dir.join("enwiki-20240101-pagelinks.sql.gz"),
+            "INSERT INTO `pagelinks` VALUES (1,0,10),(1,0,11),(1,0,12);\n",
+        );
+
+        let imported = DumpImport {
+            dir: dir.clone(),
+            seeds: vec!["A".to_string()],
+            depth: 2,
+        }
+        .import();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let imported = imported.unwrap();
+        assert_eq!(
+            imported
+                .links
+                .iter()
+                .map(|(from, to)| (from.val_for_type(), to.val_for_type()))
+                .collect::<Vec<_>>(),
+            vec![("A".to_string(), "C".to_string())]
+        );
+    }
+}
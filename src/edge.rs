@@ -0,0 +1,57 @@
+use crate::{diff::Change, link::LinkContext};
+
+/// Link between two articles. An article can link the same target several times, every
+/// occurrence is recorded.
+#[derive(Clone)]
+pub struct Edge {
+    occurrences: Vec<LinkContext>,
+    /// Edge is drawn faded when it is out of the highlighted neighbourhood.
+    faded: bool,
+    /// Link was added or removed between the compared sessions.
+    change: Option<Change>,
+}
+
+impl Edge {
+    pub fn new(context: LinkContext) -> Self {
+        Self {
+            occurrences: vec![context],
+            faded: false,
+            change: None,
+        }
+    }
+
+    pub fn add(&mut self, context: LinkContext) {
+        self.occurrences.push(context);
+    }
+
+    /// Removes the last occurrences, returns them in the order they were added.
+    pub fn remove_last(&mut self, n: usize) -> Vec<LinkContext> {
+        let at = self.occurrences.len().saturating_sub(n);
+        self.occurrences.split_off(at)
+    }
+
+    /// How many times the link appears in the article.
+    pub fn count(&self) -> usize {
+        self.occurrences.len()
+    }
+
+    pub fn occurrences(&self) -> &[LinkContext] {
+        &self.occurrences
+    }
+
+    pub fn faded(&self) -> bool {
+        self.faded
+    }
+
+    pub fn set_faded(&mut self, faded: bool) {
+        self.faded = faded;
+    }
+
+    pub fn change(&self) -> Option<Change> {
+        self.change
+    }
+
+    pub fn set_change(&mut self, change: Option<Change>) {
+        self.change = change;
+    }
+}
@@ -0,0 +1,259 @@
+use std::collections::{HashMap, HashSet};
+
+use petgraph::stable_graph::NodeIndex;
+use serde_json::Value;
+
+use crate::{
+    batcher::Batcher,
+    graph::Graph,
+    url::{self, Url},
+};
+
+/// Max number of titles or ids in one api request.
+pub const BATCH_SIZE: usize = 50;
+
+/// Properties shown in the details panel, in this order.
+pub const KEY_PROPERTIES: [(&str, &str); 8] = [
+    ("P31", "instance of"),
+    ("P17", "country"),
+    ("P569", "date of birth"),
+    ("P570", "date of death"),
+    ("P571", "inception"),
+    ("P580", "start time"),
+    ("P582", "end time"),
+    ("P577", "publication date"),
+];
+const INSTANCE_OF: &str = "P31";
+
+/// Time precision values from the wikidata data model.
+const PRECISION_MONTH: u64 = 10;
+const PRECISION_DAY: u64 = 11;
+
+/// Wikidata item the article is about.
+#[derive(Debug, Clone)]
+pub struct Entity {
+    pub qid: String,
+    /// Values of the key properties the item has, items are replaced with their labels.
+    pub statements: Vec<Statement>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Statement {
+    pub property: &'static str,
+    pub values: Vec<String>,
+}
+
+impl Entity {
+    /// The first instance of class, used to group nodes.
+    pub fn class(&self) -> Option<&str> {
+        let title = KEY_PROPERTIES
+            .iter()
+            .find(|(id, _)| *id == INSTANCE_OF)
+            .map(|(_, title)| *title)?;
+        self.statements
+            .iter()
+            .find(|s| s.property == title)
+            .and_then(|s| s.values.first())
+            .map(String::as_str)
+    }
+}
+
+/// Wikidata entities of the article nodes.
+///
+/// Articles are resolved in batches of the same wiki while the graph grows. Nodes of the same
+/// entity found in different language editions are reported as duplicates to be merged.
+#[derive(Default)]
+pub struct Entities {
+    by_node: HashMap<NodeIndex, Entity>,
+    node_by_qid: HashMap<String, NodeIndex>,
+    batcher: Batcher,
+    /// Duplicates whose merge was undone, they are not reported again.
+    kept_apart: HashSet<Url>,
+}
+
+impl Entities {
+    pub fn get(&self, idx: NodeIndex) -> Option<&Entity> {
+        self.by_node.get(&idx)
+    }
+
+    pub fn class(&self, idx: NodeIndex) -> Option<&str> {
+        self.get(idx).and_then(Entity::class)
+    }
+
+    /// Classes with the number of nodes in them sorted by the number in descending order.
+    pub fn classes(&self) -> Vec<(String, usize)> {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        self.by_node
+            .values()
+            .filter_map(Entity::class)
+            .for_each(|c| *counts.entry(c).or_default() += 1);
+
+        let mut classes = counts
+            .into_iter()
+            .map(|(c, n)| (c.to_string(), n))
+            .collect::<Vec<_>>();
+        classes.sort_by(|(lc, ln), (rc, rn)| rn.cmp(ln).then(lc.cmp(rc)));
+        classes
+    }
+
+    pub fn set_unsupported(&mut self) {
+        self.batcher.set_unsupported();
+    }
+
+    /// Takes the next batch of unresolved article nodes of the same wiki.
+    pub fn next_batch(&mut self, g: &Graph) -> Vec<(NodeIndex, url::Url)> {
+        self.batcher.next(g)
+    }
+
+    pub fn keep_apart(&mut self, url: Url) {
+        self.kept_apart.insert(url);
+    }
+
+    pub fn is_kept_apart(&self, url: &Url) -> bool {
+        self.kept_apart.contains(url)
+    }
+
+    /// Stores entity of the node. Returns the node already holding the same entity if any.
+    pub fn insert(&mut self, idx: NodeIndex, entity: Entity) -> Option<NodeIndex> {
+        if let Some(existing) = self.node_by_qid.get(&entity.qid) {
+            if *existing != idx {
+                return Some(*existing);
+            }
+        }
+
+        self.node_by_qid.insert(entity.qid.clone(), idx);
+        self.by_node.insert(idx, entity);
+        None
+    }
+
+    pub fn remove(&mut self, idx: NodeIndex) {
+        if let Some(entity) = self.by_node.remove(&idx) {
+            self.node_by_qid.remove(&entity.qid);
+        }
+        self.batcher.remove(idx);
+    }
+}
+
+/// Parses `prop=pageprops` response into values of the page property by the requested title.
+/// Normalization and redirects are followed back to the requested titles.
+pub fn parse_pageprops(res: &Value, prop: &str) -> HashMap<String, String> {
+    let query = match res.get("query") {
+        Some(query) => query,
+        None => return HashMap::new(),
+    };
+
+    let value_by_title = query
+        .get("pages")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|p| {
+            let title = p.get("title")?.as_str()?;
+            let value = p.pointer(&format!("/pageprops/{}", prop))?.as_str()?;
+            Some((title.to_string(), value.to_string()))
+        })
+        .collect::<HashMap<_, _>>();
+
+    let mapping = |key: &str| -> HashMap<String, String> {
+        query
+            .get(key)
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|m| {
+                let from = m.get("from")?.as_str()?;
+                let to = m.get("to")?.as_str()?;
+                Some((to.to_string(), from.to_string()))
+            })
+            .collect()
+    };
+    let normalized = mapping("normalized");
+    let redirects = mapping("redirects");
+
+    value_by_title
+        .into_iter()
+        .map(|(title, value)| {
+            let title = redirects.get(&title).cloned().unwrap_or(title);
+            let title = normalized.get(&title).cloned().unwrap_or(title);
+            (title, value)
+        })
+        .collect()
+}
+
+/// Parses claims of `wbgetentities` response into raw values of the key properties: item ids
+/// and formatted dates.
+pub fn parse_claims(res: &Value) -> HashMap<String, Vec<(&'static str, Vec<String>)>> {
+    res.get("entities")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .map(|(qid, entity)| {
+            let statements = KEY_PROPERTIES
+                .iter()
+                .filter_map(|(id, title)| {
+                    let values = entity
+                        .pointer(&format!("/claims/{}", id))?
+                        .as_array()?
+                        .iter()
+                        .filter_map(|c| c.pointer("/mainsnak/datavalue"))
+                        .filter_map(format_value)
+                        .collect::<Vec<_>>();
+                    match values.is_empty() {
+                        true => None,
+                        false => Some((*title, values)),
+                    }
+                })
+                .collect();
+            (qid.clone(), statements)
+        })
+        .collect()
+}
+
+/// Parses labels of `wbgetentities` response preferring the first of the languages.
+pub fn parse_labels(res: &Value, langs: &[&str]) -> HashMap<String, String> {
+    res.get("entities")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .filter_map(|(qid, entity)| {
+            let label = langs
+                .iter()
+                .find_map(|lang| entity.pointer(&format!("/labels/{}/value", lang)))?
+                .as_str()?;
+            Some((qid.clone(), label.to_string()))
+        })
+        .collect()
+}
+
+pub fn is_item(val: &str) -> bool {
+    val.starts_with('Q') && val[1..].chars().all(|c| c.is_ascii_digit())
+}
+
+fn format_value(datavalue: &Value) -> Option<String> {
+    let value = datavalue.get("value")?;
+    match datavalue.get("type")?.as_str()? {
+        "wikibase-entityid" => Some(value.get("id")?.as_str()?.to_string()),
+        "time" => {
+            let time = value.get("time")?.as_str()?.trim_start_matches('+');
+            let date = time.split('T').next()?;
+            let precision = value.get("precision").and_then(Value::as_u64).unwrap_or(0);
+            let parts = match precision {
+                PRECISION_DAY.. => 3,
+                PRECISION_MONTH => 2,
+                _ => 1,
+            };
+            // negative years keep their sign in the first part
+            let sign = if date.starts_with('-') { "-" } else { "" };
+            Some(format!(
+                "{}{}",
+                sign,
+                date.trim_start_matches('-')
+                    .splitn(3, '-')
+                    .take(parts)
+                    .collect::<Vec<_>>()
+                    .join("-")
+            ))
+        }
+        _ => None,
+    }
+}
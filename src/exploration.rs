@@ -0,0 +1,1871 @@
+use std::collections::{HashMap, HashSet};
+use std::mem;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use crossbeam::channel::{unbounded, Receiver, TryRecvError};
+use egui::{ColorImage, Context, Pos2, Rect, TextureHandle, TextureOptions, Vec2};
+use log::{error, info};
+use petgraph::stable_graph::{NodeIndex, StableGraph};
+use petgraph::visit::{EdgeRef, IntoEdgeReferences};
+use petgraph::Direction::{Incoming, Outgoing};
+use rand::Rng;
+use tokio::task::JoinHandle;
+
+use crate::communities::Communities;
+use crate::comparison::{compare, Comparison};
+use crate::cursor::Cursor;
+use crate::diff::{Change, Diff};
+use crate::disambiguation::{Disambiguations, Handling};
+use crate::dump::ImportedGraph;
+use crate::edge::Edge;
+use crate::entities::{Entities, Entity};
+use crate::graph::Graph;
+use crate::history::{Edit, Entry, History, Marks, Step};
+use crate::layout::Layout;
+use crate::link_source::{Error, LinkSource, SharedSource};
+use crate::metrics::{Metric, Metrics};
+use crate::revision::{Linked, Timeline};
+use crate::session::Session;
+use crate::views::metrics::Encoding;
+use crate::views::node_shape;
+use crate::views::style::{
+    class_color, community_color, lerp_color, Theme, COLOR_METRIC_HIGH, COLOR_METRIC_LOW,
+};
+use crate::{
+    link::{Link, LinkContext},
+    node,
+    state::{next, Fork, State},
+    thumbnail,
+    url::{self, Url},
+    url_retriever::{Retrieval, UrlRetriever},
+};
+
+/// Radius multiplier of the node with the max value of the encoded metric.
+const METRIC_MAX_SIZE: f32 = 4.;
+/// Max number of thumbnails retrieved at once for the graph.
+const MAX_THUMBNAIL_TASKS: usize = 4;
+/// Number of the best connected nodes which labels are drawn when the graph is zoomed out.
+const LABELED_NODES: usize = 20;
+
+/// Horizontal distance between the origins of the shown graph and the graph merged into it.
+const MERGED_TAB_OFFSET: f32 = 600.;
+
+pub type EntityTask = Receiver<Result<Vec<(Url, Entity)>, Error>>;
+
+/// Retrievers by the node and what they retrieve for it.
+pub type ActiveTasks =
+    HashMap<(NodeIndex, Retrieval), (Receiver<Result<Link, Error>>, JoinHandle<()>)>;
+
+/// Everything explored in one tab: its graph, layout, cursor, loading tasks and what is known
+/// about its nodes.
+///
+/// Every tab keeps retrieving while another one is shown. Node indices are only meaningful
+/// inside their tab.
+pub struct Exploration {
+    pub root_article_url: String,
+    pub state: State,
+    /// Articles are explored as they were at the time instead of their live versions.
+    pub as_of: Option<SystemTime>,
+    source: Arc<SharedSource>,
+    pub active_tasks: ActiveTasks,
+    /// Summaries of the selected articles. Empty if the source could not provide one.
+    pub summaries: HashMap<NodeIndex, String>,
+    pub summary_tasks: HashMap<NodeIndex, Receiver<Result<String, Error>>>,
+    /// Thumbnails of media nodes, `None` if the media has no thumbnail.
+    pub thumbnails: HashMap<NodeIndex, Option<TextureHandle>>,
+    pub thumbnail_tasks: HashMap<NodeIndex, Receiver<Result<ColorImage, Error>>>,
+    pub g: Graph,
+    pub layout: Layout,
+    pub metrics: Metrics,
+    pub communities: Communities,
+    /// Community which members are highlighted.
+    pub community_filter: Option<usize>,
+    pub collapsed_communities: HashSet<usize>,
+    /// Nodes with the links found only in them which are hidden and counted on the node.
+    pub collapsed_children: HashMap<NodeIndex, Vec<NodeIndex>>,
+    pub history: History,
+    pub link_search_query: String,
+    pub entities: Entities,
+    pub entity_task: Option<EntityTask>,
+    /// Wikidata class which members are highlighted.
+    pub class_filter: Option<String>,
+    /// Nodes of the same entity waiting to be merged: duplicate and the node it merges into.
+    pub pending_merges: Vec<(NodeIndex, NodeIndex)>,
+    pub disambiguations: Disambiguations,
+    pub disambiguation_task: Option<Receiver<Result<Vec<Url>, Error>>>,
+    /// Disambiguation page waiting for the user to choose the intended article, together with
+    /// the articles it lists.
+    pub choosing: Option<(NodeIndex, Vec<Link>)>,
+    pub choice_task: Option<Receiver<Result<Vec<Link>, Error>>>,
+    pub selected_node: Option<NodeIndex>,
+    pub cursor: Option<Cursor>,
+    pub node_by_url: HashMap<Url, NodeIndex>,
+    /// Part of the canvas visible in the graph view in the last frame.
+    pub visible_area: Option<Rect>,
+    pub comparison: Option<Comparison>,
+    pub comparison_task: Option<Receiver<Result<Comparison, Error>>>,
+    /// Revision history of the article the tab was opened for.
+    pub timeline: Option<Timeline>,
+}
+
+impl Exploration {
+    /// Empty exploration retrieving from the source.
+    pub fn new(source: Arc<SharedSource>) -> Self {
+        Self {
+            g: Graph::new(StableGraph::new()),
+            source,
+
+            root_article_url: Default::default(),
+            state: Default::default(),
+            as_of: Default::default(),
+            active_tasks: Default::default(),
+            summaries: Default::default(),
+            summary_tasks: Default::default(),
+            thumbnails: Default::default(),
+            thumbnail_tasks: Default::default(),
+            layout: Default::default(),
+            metrics: Default::default(),
+            communities: Default::default(),
+            community_filter: Default::default(),
+            collapsed_communities: Default::default(),
+            collapsed_children: Default::default(),
+            history: Default::default(),
+            link_search_query: Default::default(),
+            entities: Default::default(),
+            entity_task: Default::default(),
+            class_filter: Default::default(),
+            pending_merges: Default::default(),
+            disambiguations: Default::default(),
+            disambiguation_task: Default::default(),
+            choosing: Default::default(),
+            choice_task: Default::default(),
+            selected_node: Default::default(),
+            cursor: Default::default(),
+            node_by_url: Default::default(),
+            visible_area: Default::default(),
+            comparison: Default::default(),
+            comparison_task: Default::default(),
+            timeline: Default::default(),
+        }
+    }
+
+    /// Retrieves from the source from now on, tasks already running keep their source.
+    pub fn set_source(&mut self, source: Arc<SharedSource>) {
+        self.source = source;
+    }
+
+    /// Stops retrieving links of the closed exploration.
+    pub fn abort(&mut self) {
+        self.active_tasks
+            .drain()
+            .for_each(|(_, (_, join_handle))| join_handle.abort());
+    }
+
+    /// Name of the tab: the article the exploration started from.
+    pub fn title(&self) -> String {
+        if let Some(cursor) = &self.cursor {
+            return self
+                .g
+                .g
+                .node_weight(cursor.origin())
+                .unwrap()
+                .payload()
+                .label();
+        }
+        match Url::new(&self.root_article_url) {
+            Ok(url) if url.is_wiki() => url.val_for_type().replace('_', " "),
+            _ => "new exploration".to_string(),
+        }
+    }
+
+    /// Applies community colors and filter to the nodes. Nodes are hidden and counted as
+    /// collapsed both for collapsed communities and collapsed children.
+    pub fn apply_communities(&mut self, color_by_community: bool) {
+        if let Some(id) = self.community_filter {
+            if self.communities.get(id).is_none() {
+                self.community_filter = None;
+            }
+        }
+        self.collapsed_communities
+            .retain(|id| self.communities.get(*id).is_some());
+
+        let collapsed_children = self
+            .collapsed_children
+            .values()
+            .flatten()
+            .copied()
+            .collect::<HashSet<_>>();
+
+        let indices = self.g.g.node_indices().collect::<Vec<_>>();
+        indices.into_iter().for_each(|idx| {
+            let community = self.communities.of(idx);
+            let collapsed_into = community
+                .filter(|id| self.collapsed_communities.contains(id))
+                .and_then(|id| self.communities.get(id));
+
+            let n = self.g.g.node_weight_mut(idx).unwrap().payload_mut();
+            n.set_community(community);
+            if color_by_community {
+                n.set_color(community.map(community_color));
+            }
+            n.set_faded(matches!(self.community_filter, Some(id) if community != Some(id)));
+
+            let (hidden, collapsed) = match collapsed_into {
+                Some(c) if c.center == idx => (false, c.members.len() - 1),
+                Some(_) => (true, 0),
+                None => (false, 0),
+            };
+            let children = self.collapsed_children.get(&idx).map_or(0, Vec::len);
+            n.set_hidden(hidden || collapsed_children.contains(&idx));
+            n.set_collapsed(collapsed + children);
+        });
+    }
+
+    /// Resolves article nodes to wikidata entities batch by batch. Nodes of the same entity are
+    /// merged once no links are being retrieved.
+    pub fn process_entities(&mut self) {
+        if let Some(receiver) = &self.entity_task {
+            match receiver.try_recv() {
+                Ok(Ok(resolved)) => {
+                    resolved.into_iter().for_each(|(url, entity)| {
+                        let idx = match self.node_by_url.get(&url) {
+                            Some(idx) => *idx,
+                            None => return,
+                        };
+                        if let Some(existing) = self.entities.insert(idx, entity) {
+                            self.pending_merges.push((idx, existing));
+                        }
+                    });
+                    self.entity_task = None;
+                }
+                Ok(Err(Error::Unsupported(what))) => {
+                    info!("{} are not supported by the source", what);
+                    self.entities.set_unsupported();
+                    self.entity_task = None;
+                }
+                Ok(Err(err)) => {
+                    error!("failed to resolve entities: {}", err);
+                    self.entity_task = None;
+                }
+                Err(TryRecvError::Disconnected) => self.entity_task = None,
+                Err(TryRecvError::Empty) => (),
+            }
+        }
+
+        if self.entity_task.is_none() {
+            let urls = self
+                .entities
+                .next_batch(&self.g)
+                .into_iter()
+                .map(|(_, url)| url)
+                .collect::<Vec<_>>();
+            if !urls.is_empty() {
+                let (sender, receiver) = unbounded();
+                let source = self.source.clone();
+                tokio::spawn(async move {
+                    sender.send(source.entities(&urls).await).unwrap();
+                });
+                self.entity_task = Some(receiver);
+            }
+        }
+
+        if self.state == State::Graph {
+            let merges = std::mem::take(&mut self.pending_merges)
+                .into_iter()
+                .filter(|(dup, _)| match self.g.g.node_weight(*dup) {
+                    Some(n) => !self.entities.is_kept_apart(n.payload().url()),
+                    None => false,
+                })
+                .collect::<Vec<_>>();
+            if !merges.is_empty() {
+                self.record(Edit::MergeDuplicates);
+            }
+            merges.into_iter().for_each(|(dup, into)| {
+                if self.g.g.contains_node(dup) && self.g.g.contains_node(into) {
+                    self.merge_nodes(dup, into);
+                }
+            });
+        }
+    }
+
+    /// Checks article nodes for disambiguation pages batch by batch.
+    pub fn process_disambiguations(&mut self) {
+        if let Some(receiver) = &self.disambiguation_task {
+            match receiver.try_recv() {
+                Ok(Ok(pages)) => {
+                    pages.into_iter().for_each(|url| {
+                        if let Some(idx) = self.node_by_url.get(&url) {
+                            self.disambiguations.insert(*idx);
+                        }
+                    });
+                    self.disambiguation_task = None;
+                }
+                Ok(Err(Error::Unsupported(what))) => {
+                    info!("{} are not supported by the source", what);
+                    self.disambiguations.set_unsupported();
+                    self.disambiguation_task = None;
+                }
+                Ok(Err(err)) => {
+                    error!("failed to check disambiguation pages: {}", err);
+                    self.disambiguation_task = None;
+                }
+                Err(TryRecvError::Disconnected) => self.disambiguation_task = None,
+                Err(TryRecvError::Empty) => (),
+            }
+        }
+
+        if self.disambiguation_task.is_none() {
+            let urls = self
+                .disambiguations
+                .next_batch(&self.g)
+                .into_iter()
+                .map(|(_, url)| url)
+                .collect::<Vec<_>>();
+            if !urls.is_empty() {
+                let (sender, receiver) = unbounded();
+                let source = self.source.clone();
+                tokio::spawn(async move {
+                    sender.send(source.disambiguations(&urls).await).unwrap();
+                });
+                self.disambiguation_task = Some(receiver);
+            }
+        }
+    }
+
+    /// Marks disambiguation pages and hides them if configured so.
+    pub fn apply_disambiguations(&mut self, handling: Handling) {
+        let indices = self.g.g.node_indices().collect::<Vec<_>>();
+        indices.into_iter().for_each(|idx| {
+            let disambiguation = self.disambiguations.contains(idx);
+
+            let n = self.g.g.node_weight_mut(idx).unwrap().payload_mut();
+            n.set_disambiguation(disambiguation);
+            if disambiguation && handling == Handling::Hide {
+                n.set_hidden(true);
+            }
+        });
+    }
+
+    /// Starts retrieving articles listed on the disambiguation page for the user to choose from.
+    fn prompt_disambiguation(&mut self, idx: NodeIndex) {
+        let url = self.g.g.node_weight(idx).unwrap().payload().url().clone();
+
+        let (sender, receiver) = unbounded();
+        let source = self.source.clone();
+        tokio::spawn(async move {
+            sender.send(source.links(&url).await).unwrap();
+        });
+        self.choosing = Some((idx, vec![]));
+        self.choice_task = Some(receiver);
+    }
+
+    pub fn process_choice_task(&mut self) {
+        let result = match &self.choice_task {
+            Some(receiver) => match receiver.try_recv() {
+                Ok(result) => result,
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => {
+                    self.choice_task = None;
+                    return;
+                }
+            },
+            None => return,
+        };
+
+        self.choice_task = None;
+        let (idx, options) = match self.choosing.as_mut() {
+            Some(choosing) => choosing,
+            None => return,
+        };
+        match result {
+            Ok(links) => {
+                let page = self.g.g.node_weight(*idx).unwrap().payload().url().clone();
+                let mut seen = HashSet::new();
+                *options = links
+                    .into_iter()
+                    .filter(|l| l.url.url_type() == url::Type::Article)
+                    .filter(|l| l.url.host() == page.host() && l.url != page)
+                    .filter(|l| seen.insert(l.url.clone()))
+                    .collect();
+            }
+            Err(err) => error!("failed to retrieve disambiguation page: {}", err),
+        }
+    }
+
+    /// Replaces the disambiguation page with the chosen article, links to the page are moved to
+    /// the article.
+    pub fn resolve_disambiguation(&mut self, idx: NodeIndex, url: Url) {
+        if self.active_tasks.keys().any(|(parent, _)| *parent == idx) {
+            info!("links of the disambiguation page are still being retrieved");
+            return;
+        }
+
+        self.record(Edit::Merge);
+        let target = match self.node_by_url.get(&url) {
+            Some(target) => *target,
+            None => {
+                let loc = self.g.g.node_weight(idx).unwrap().location();
+                self.insert_node(loc, &node::Node::new(url))
+            }
+        };
+        self.merge_nodes(idx, target);
+    }
+
+    /// Replaces the duplicate node with the other one moving all its links to it. Links between
+    /// the two nodes, such as interlanguage links, are dropped.
+    fn merge_nodes(&mut self, dup: NodeIndex, into: NodeIndex) {
+        info!(
+            "merging {} into {}",
+            self.g.g.node_weight(dup).unwrap().payload().url().val(),
+            self.g.g.node_weight(into).unwrap().payload().url().val()
+        );
+
+        let replace = |idx: NodeIndex| if idx == dup { into } else { idx };
+        let edges = self
+            .g
+            .g
+            .edges_directed(dup, Outgoing)
+            .chain(self.g.g.edges_directed(dup, Incoming))
+            .map(|e| {
+                (
+                    replace(e.source()),
+                    replace(e.target()),
+                    e.weight().payload().clone(),
+                )
+            })
+            .filter(|(start, end, _)| start != end)
+            .collect::<Vec<_>>();
+
+        if self.selected_node == Some(dup) {
+            self.selected_node = None;
+        }
+        let into_url = self.g.g.node_weight(into).unwrap().payload().url().clone();
+        self.delete_node(dup, Some(into_url));
+
+        edges.into_iter().for_each(|(start, end, edge)| {
+            edge.occurrences()
+                .iter()
+                .for_each(|context| self.insert_link(start, end, context.clone()));
+        });
+
+        self.node_by_url
+            .values_mut()
+            .for_each(|idx| *idx = replace(*idx));
+        if let Some(cursor) = self.cursor.as_mut() {
+            cursor.replace(dup, into);
+        }
+        if self.selected_node.is_none() {
+            self.select_node(into);
+        }
+
+        self.forget(dup);
+        self.metrics.invalidate();
+        self.communities.invalidate();
+    }
+
+    /// Remembers the marks before the edit, the graph changes it makes are logged as they are
+    /// made so it can be undone.
+    pub fn record(&mut self, edit: Edit) {
+        let marks = self.marks();
+        self.history.record(edit, marks);
+    }
+
+    pub fn undo(&mut self) {
+        if let Some(entry) = self.history.undo() {
+            info!("undoing {}", entry.edit.title());
+            let reverted = self.revert(entry);
+            // duplicates added back are not merged again right away
+            if reverted.edit == Edit::MergeDuplicates {
+                reverted.steps.iter().for_each(|step| {
+                    if let Step::AddNode { node, .. } = step {
+                        self.entities.keep_apart(node.url().clone());
+                    }
+                });
+            }
+            self.history.undone(reverted);
+        }
+    }
+
+    pub fn redo(&mut self) {
+        if let Some(entry) = self.history.redo() {
+            info!("redoing {}", entry.edit.title());
+            let reverted = self.revert(entry);
+            self.history.redone(reverted);
+        }
+    }
+
+    /// Reverts the steps of the entry in the reverse order and restores the marks before them.
+    /// Returns the entry which reverts this revert.
+    fn revert(&mut self, entry: Entry) -> Entry {
+        let marks = self.marks();
+        let steps = entry
+            .steps
+            .into_iter()
+            .rev()
+            .filter_map(|step| self.apply(step.inverse()))
+            .collect();
+        self.restore_marks(entry.marks);
+
+        self.pending_merges.clear();
+        self.choosing = None;
+        self.choice_task = None;
+        self.metrics.invalidate();
+        self.communities.invalidate();
+
+        Entry {
+            edit: entry.edit,
+            steps,
+            marks,
+        }
+    }
+
+    /// Makes the step while an edit is undone or redone. Returns the step as it was made, with
+    /// the current state of the removed nodes and links, or `None` if it does not apply to the
+    /// graph as it is.
+    fn apply(&mut self, step: Step) -> Option<Step> {
+        match step {
+            Step::AddNode {
+                node,
+                location,
+                merged_into,
+            } => {
+                if self.node_of(node.url()).is_some() {
+                    return None;
+                }
+                let idx =
+                    self.g
+                        .add_node_with_label_and_location(node.clone(), node.label(), location);
+                self.layout.add_node(idx, location);
+                self.node_by_url.insert(node.url().clone(), idx);
+                Some(Step::AddNode {
+                    node,
+                    location,
+                    merged_into,
+                })
+            }
+            Step::RemoveNode {
+                node, merged_into, ..
+            } => {
+                let idx = self.node_of(node.url())?;
+                let removed = self.g.g.remove_node(idx).unwrap();
+                self.layout.remove_node(idx);
+                match merged_into.as_ref().and_then(|u| self.node_of(u)) {
+                    Some(into) => self
+                        .node_by_url
+                        .values_mut()
+                        .filter(|i| **i == idx)
+                        .for_each(|i| *i = into),
+                    None => self.node_by_url.retain(|_, i| *i != idx),
+                }
+                self.forget(idx);
+                Some(Step::RemoveNode {
+                    node: removed.payload().clone(),
+                    location: removed.location(),
+                    merged_into,
+                })
+            }
+            Step::AddLink { from, to, contexts } => {
+                let (start, end) = (self.node_of(&from)?, self.node_of(&to)?);
+                contexts.iter().for_each(|context| {
+                    add_edge(&mut self.g, &mut self.layout, start, end, context.clone())
+                });
+                Some(Step::AddLink { from, to, contexts })
+            }
+            Step::RemoveLink { from, to, contexts } => {
+                let (start, end) = (self.node_of(&from)?, self.node_of(&to)?);
+                let existing = self.g.g.find_edge(start, end)?;
+                let mut edge = self.g.g.remove_edge(existing).unwrap().payload().clone();
+                let removed = edge.remove_last(contexts.len());
+                match edge.count() {
+                    0 => self.layout.remove_edge(start, end),
+                    count => {
+                        self.g.add_edge(start, end, edge);
+                        self.layout.set_edge(start, end, count);
+                    }
+                }
+                Some(Step::RemoveLink {
+                    from,
+                    to,
+                    contexts: removed,
+                })
+            }
+        }
+    }
+
+    /// Node of the article, not the one it was merged into.
+    fn node_of(&self, url: &Url) -> Option<NodeIndex> {
+        self.node_by_url.get(url).copied().filter(|idx| {
+            self.g
+                .g
+                .node_weight(*idx)
+                .is_some_and(|n| n.payload().url() == url)
+        })
+    }
+
+    fn marks(&self) -> Marks {
+        let url = |idx: NodeIndex| self.g.g.node_weight(idx).map(|n| n.payload().url().clone());
+
+        let (roots, position) = match &self.cursor {
+            Some(cursor) => {
+                let roots = cursor
+                    .roots_with_parents()
+                    .into_iter()
+                    .filter_map(|(root, parent)| Some((url(root)?, parent.and_then(url))))
+                    .collect();
+                let (root, element) = cursor.position();
+                (roots, url(root).zip(url(element)))
+            }
+            None => (vec![], None),
+        };
+        let collapsed_children = self
+            .collapsed_children
+            .iter()
+            .filter_map(|(idx, children)| {
+                Some((
+                    url(*idx)?,
+                    children.iter().filter_map(|c| url(*c)).collect(),
+                ))
+            })
+            .collect();
+
+        Marks {
+            roots,
+            position,
+            collapsed_children,
+            selected: self.selected_node.and_then(url),
+        }
+    }
+
+    /// Rebuilds the cursor from its roots, which are attached again in the same order, and
+    /// restores the collapsed nodes and the selection.
+    fn restore_marks(&mut self, marks: Marks) {
+        let idx = |url: &Url| self.node_by_url.get(url).copied();
+
+        let mut roots = marks
+            .roots
+            .iter()
+            .filter_map(|(root, parent)| Some((idx(root)?, parent.as_ref().and_then(idx))));
+        let cursor = roots.next().map(|(origin, _)| {
+            let mut cursor = Cursor::new(origin, &self.g);
+            roots.for_each(|(root, parent)| {
+                if cursor.is_root(root) {
+                    return;
+                }
+                let parent = parent.filter(|p| cursor.is_root(*p)).unwrap_or(origin);
+                cursor.attach(root, parent, &self.g);
+            });
+            if let Some((root, element)) = marks.position.as_ref() {
+                if let (Some(root), Some(element)) = (idx(root), idx(element)) {
+                    cursor.move_to((root, element));
+                }
+            }
+            cursor
+        });
+        let collapsed_children = marks
+            .collapsed_children
+            .iter()
+            .filter_map(|(node, children)| {
+                Some((idx(node)?, children.iter().filter_map(idx).collect()))
+            })
+            .collect();
+        let selected = marks.selected.as_ref().and_then(idx);
+
+        self.cursor = cursor;
+        self.collapsed_children = collapsed_children;
+        if let Some(n) = self
+            .selected_node
+            .take()
+            .and_then(|idx| self.g.g.node_weight_mut(idx))
+        {
+            n.set_selected(false);
+        }
+        if let Some(n) = selected.and_then(|idx| self.g.g.node_weight_mut(idx)) {
+            n.set_selected(true);
+        }
+        self.selected_node = selected;
+    }
+
+    /// Adds the node near the location. The step is logged in the edit in progress.
+    pub fn insert_node(&mut self, loc_center: Pos2, n: &node::Node) -> NodeIndex {
+        let idx = add_node(&mut self.g, &mut self.layout, loc_center, n);
+        self.node_by_url.insert(n.url().clone(), idx);
+        self.history.log(Step::AddNode {
+            node: n.clone(),
+            location: self.g.g.node_weight(idx).unwrap().location(),
+            merged_into: None,
+        });
+        idx
+    }
+
+    /// Adds the link, see [`add_edge`]. The step is logged in the edit in progress.
+    pub fn insert_link(&mut self, start: NodeIndex, end: NodeIndex, context: LinkContext) {
+        let url = |idx: NodeIndex| self.g.g.node_weight(idx).unwrap().payload().url().clone();
+        self.history.log(Step::AddLink {
+            from: url(start),
+            to: url(end),
+            contexts: vec![context.clone()],
+        });
+        add_edge(&mut self.g, &mut self.layout, start, end, context);
+    }
+
+    /// Removes the node with its links from the graph and the layout. The steps are logged in
+    /// the edit in progress.
+    fn delete_node(&mut self, idx: NodeIndex, merged_into: Option<Url>) {
+        let url = |idx: NodeIndex| self.g.g.node_weight(idx).unwrap().payload().url().clone();
+        let links = self
+            .g
+            .g
+            .edges_directed(idx, Outgoing)
+            .chain(
+                self.g
+                    .g
+                    .edges_directed(idx, Incoming)
+                    .filter(|e| e.source() != idx),
+            )
+            .map(|e| Step::RemoveLink {
+                from: url(e.source()),
+                to: url(e.target()),
+                contexts: e.weight().payload().occurrences().to_vec(),
+            })
+            .collect::<Vec<_>>();
+        links.into_iter().for_each(|step| self.history.log(step));
+
+        let removed = self.g.g.remove_node(idx).unwrap();
+        self.layout.remove_node(idx);
+        self.history.log(Step::RemoveNode {
+            node: removed.payload().clone(),
+            location: removed.location(),
+            merged_into,
+        });
+    }
+
+    /// Drops everything known about the node which is no longer in the graph.
+    fn forget(&mut self, idx: NodeIndex) {
+        self.summaries.remove(&idx);
+        self.summary_tasks.remove(&idx);
+        self.thumbnails.remove(&idx);
+        self.thumbnail_tasks.remove(&idx);
+        self.entities.remove(idx);
+        self.disambiguations.remove(idx);
+        self.collapsed_children.remove(&idx);
+        self.collapsed_children
+            .values_mut()
+            .for_each(|children| children.retain(|c| *c != idx));
+    }
+
+    /// Removes the nodes from the graph, the layout and the cursor. The node the exploration
+    /// started from is kept.
+    pub fn remove_nodes(&mut self, nodes: HashSet<NodeIndex>) {
+        let origin = match &self.cursor {
+            Some(cursor) => cursor.origin(),
+            None => return,
+        };
+        let mut nodes = nodes
+            .into_iter()
+            .filter(|idx| *idx != origin)
+            .collect::<Vec<_>>();
+        if nodes.is_empty() {
+            return;
+        }
+        nodes.sort();
+        info!("removing {} nodes", nodes.len());
+        self.record(Edit::Delete);
+
+        if self.selected_node.is_some_and(|idx| nodes.contains(&idx)) {
+            self.selected_node = None;
+        }
+        nodes.iter().for_each(|idx| {
+            self.delete_node(*idx, None);
+            self.cursor.as_mut().unwrap().remove(*idx);
+        });
+        let position = self.cursor.as_ref().unwrap().position();
+
+        self.node_by_url.retain(|_, idx| !nodes.contains(idx));
+        self.pending_merges
+            .retain(|(dup, into)| !nodes.contains(dup) && !nodes.contains(into));
+        if matches!(&self.choosing, Some((idx, _)) if nodes.contains(idx)) {
+            self.choosing = None;
+            self.choice_task = None;
+        }
+        nodes.iter().for_each(|idx| self.forget(*idx));
+
+        if self.selected_node.is_none() {
+            self.select_node(position.1);
+        }
+        self.metrics.invalidate();
+        self.communities.invalidate();
+    }
+
+    /// The node together with the nodes which are connected to the origin only through it.
+    pub fn detached_by(&self, idx: NodeIndex) -> HashSet<NodeIndex> {
+        let origin = match &self.cursor {
+            Some(cursor) => cursor.origin(),
+            None => return HashSet::new(),
+        };
+
+        let reachable = |from: NodeIndex, blocked: &dyn Fn(NodeIndex) -> bool| {
+            let mut visited = HashSet::from([from]);
+            let mut stack = vec![from];
+            while let Some(curr) = stack.pop() {
+                self.g.g.neighbors_undirected(curr).for_each(|next| {
+                    if !blocked(next) && visited.insert(next) {
+                        stack.push(next);
+                    }
+                });
+            }
+            visited
+        };
+
+        let attached = reachable(origin, &|next| next == idx);
+        reachable(idx, &|next| attached.contains(&next))
+    }
+
+    /// Nodes with a single neighbour.
+    pub fn leaves(&self) -> HashSet<NodeIndex> {
+        self.g
+            .g
+            .node_indices()
+            .filter(|idx| distinct_neighbours(&self.g, *idx).len() == 1)
+            .collect()
+    }
+
+    pub fn nodes_of_type(&self, t: url::Type) -> HashSet<NodeIndex> {
+        self.g
+            .g
+            .node_indices()
+            .filter(|idx| {
+                self.g
+                    .g
+                    .node_weight(*idx)
+                    .unwrap()
+                    .payload()
+                    .url()
+                    .url_type()
+                    == t
+            })
+            .collect()
+    }
+
+    /// Collapses links of the node which are not linked with any other node, or restores them if
+    /// they are collapsed.
+    pub fn toggle_collapsed_children(&mut self, idx: NodeIndex) {
+        if self.collapsed_children.contains_key(&idx) {
+            self.record(Edit::Collapse);
+            self.collapsed_children.remove(&idx);
+            return;
+        }
+
+        let children = self
+            .g
+            .g
+            .neighbors_directed(idx, Outgoing)
+            .filter(|child| *child != idx)
+            .filter(|child| distinct_neighbours(&self.g, *child) == HashSet::from([idx]))
+            .collect::<HashSet<_>>();
+        if !children.is_empty() {
+            self.record(Edit::Collapse);
+            self.collapsed_children
+                .insert(idx, children.into_iter().collect());
+        }
+    }
+
+    /// Moves collapsed links to the node they are collapsed into. Simulation is not affected.
+    pub fn pin_collapsed_children(&mut self) {
+        self.collapsed_children.iter().for_each(|(idx, children)| {
+            let loc = self.g.g.node_weight(*idx).unwrap().location();
+            children
+                .iter()
+                .for_each(|child| self.g.g.node_weight_mut(*child).unwrap().set_location(loc));
+        });
+    }
+
+    /// Colors nodes by their wikidata class and fades nodes out of the filtered class.
+    pub fn apply_entities(&mut self, color_by_class: bool) {
+        let indices = self.g.g.node_indices().collect::<Vec<_>>();
+        indices.into_iter().for_each(|idx| {
+            let class = self.entities.class(idx);
+
+            let n = self.g.g.node_weight_mut(idx).unwrap().payload_mut();
+            if color_by_class {
+                n.set_color(class.map(class_color));
+            }
+            if matches!(&self.class_filter, Some(filter) if class != Some(filter.as_str())) {
+                n.set_faded(true);
+            }
+        });
+    }
+
+    /// Moves members of collapsed communities to their center, so the community is drawn as a
+    /// single node and its links start from it. Simulation is not affected.
+    pub fn pin_collapsed_communities(&mut self) {
+        self.collapsed_communities.iter().for_each(|id| {
+            let c = match self.communities.get(*id) {
+                Some(c) => c,
+                None => return,
+            };
+
+            let loc = self.g.g.node_weight(c.center).unwrap().location();
+            c.members
+                .iter()
+                .filter(|idx| **idx != c.center)
+                .for_each(|idx| self.g.g.node_weight_mut(*idx).unwrap().set_location(loc));
+        });
+    }
+
+    /// Sets size or color of every node according to the encoded metric.
+    pub fn apply_metric_encoding(&mut self, metric: Metric, encoding: Encoding, theme: &Theme) {
+        let max = self.metrics.max(metric);
+        let indices = self.g.g.node_indices().collect::<Vec<_>>();
+        indices.into_iter().for_each(|idx| {
+            let val = match max > 0. {
+                true => self.metrics.get(idx).map_or(0., |m| m.get(metric)) / max,
+                false => 0.,
+            };
+
+            let n = self.g.g.node_weight_mut(idx).unwrap().payload_mut();
+            match encoding {
+                Encoding::None => {
+                    n.set_size(1.);
+                    n.set_color(Some(theme.type_color(&n.url().url_type())));
+                }
+                Encoding::Size => {
+                    n.set_size(1. + val * (METRIC_MAX_SIZE - 1.));
+                    n.set_color(Some(theme.type_color(&n.url().url_type())));
+                }
+                Encoding::Color => {
+                    n.set_size(1.);
+                    n.set_color(Some(lerp_color(COLOR_METRIC_LOW, COLOR_METRIC_HIGH, val)));
+                }
+            }
+        });
+    }
+
+    /// Moves the exploration on with the links retrieved since the last frame.
+    pub fn handle_state(&mut self) {
+        match self.state {
+            State::GraphAndLoading => self.handle_state_graph_and_loading(),
+            State::GraphLoaded => self.handle_state_graph_loaded(),
+            State::Graph | State::GraphAndLoadingError | State::Input | State::InputError => (),
+        }
+    }
+
+    fn handle_state_graph_loaded(&mut self) {
+        if self.cursor.is_none() {
+            let first_root = NodeIndex::new(0);
+            self.cursor = Some(Cursor::new(first_root, &self.g));
+            self.select_node(first_root)
+        } else {
+            self.cursor
+                .as_mut()
+                .unwrap()
+                .update(self.selected_node.unwrap(), &self.g);
+        }
+
+        self.state = next(&self.state, Fork::Success)
+    }
+
+    /// Checks for results from the url retriever for every active task. If any task is finished,
+    /// moves to the next state.
+    fn handle_state_graph_and_loading(&mut self) {
+        match self.process_active_tasks() {
+            Ok(_) => {
+                if self.active_tasks.is_empty() {
+                    info!("all tasks finished");
+                    self.history.close();
+                    self.state = next(&self.state, Fork::Success);
+                }
+            }
+            Err(err) => {
+                error!("error while checking active tasks: {}", err);
+                self.history.close();
+                self.state = next(&self.state, Fork::Failure);
+            }
+        }
+    }
+
+    /// Processes results from the url retriever for every active task.
+    ///
+    /// Updates the graph with the retrieved urls.
+    ///
+    /// If any task is finished, removes it from the active tasks.
+    ///
+    /// If we got any url, function returns true, otherwise false. If an error was got function returns error.
+    fn process_active_tasks(&mut self) -> Result<(), Error> {
+        let mut received = Vec::new();
+        let mut finished_tasks = Vec::new();
+        self.active_tasks
+            .iter()
+            .for_each(|((parent_idx, retrieval), (receiver, join_handle))| {
+                match receiver.try_recv() {
+                    Ok(Ok(link)) => received.push((*parent_idx, *retrieval, link)),
+                    Ok(Err(err)) => {
+                        error!("got error from the retriver: {}", err);
+                    }
+                    Err(_) => {
+                        if join_handle.is_finished() {
+                            finished_tasks.push((*parent_idx, *retrieval));
+                        }
+                    }
+                }
+            });
+
+        received.into_iter().for_each(
+            |(
+                parent_idx,
+                retrieval,
+                Link {
+                    url,
+                    context,
+                    revision,
+                },
+            )| {
+                info!("got new url from the retriver: {}", url.val());
+                self.metrics.invalidate();
+                self.communities.invalidate();
+
+                let parent = self.g.g.node_weight_mut(parent_idx).unwrap();
+                if revision.is_some() {
+                    parent.payload_mut().set_revision(revision);
+                }
+                let parent_loc = parent.location();
+
+                let idx = match self.node_by_url.get(&url) {
+                    Some(idx) => *idx,
+                    None => self.insert_node(parent_loc, &node::Node::new(url)),
+                };
+
+                match retrieval.direction() {
+                    Outgoing => self.insert_link(parent_idx, idx, context),
+                    Incoming => self.insert_link(idx, parent_idx, context),
+                }
+            },
+        );
+
+        finished_tasks.iter().for_each(|finished| {
+            info!(
+                "task finished; received all {:?} urls for: {}",
+                finished.1,
+                self.g
+                    .g
+                    .node_weight(finished.0)
+                    .unwrap()
+                    .payload()
+                    .url()
+                    .val()
+            );
+            self.active_tasks.remove(finished);
+        });
+
+        Ok(())
+    }
+
+    /// Starts retrieving for the selected node. Without the retrieval given categories get their
+    /// members, articles get their categories in the category mode and their links otherwise.
+    /// Disambiguation pages ask for the intended article instead if configured so.
+    pub fn expand_selected(
+        &mut self,
+        retrieval: Option<Retrieval>,
+        handling: Handling,
+        category_mode: bool,
+    ) {
+        if let Some(idx) = self.selected_node {
+            if retrieval.is_none()
+                && handling == Handling::Prompt
+                && self.disambiguations.contains(idx)
+            {
+                self.prompt_disambiguation(idx);
+                return;
+            }
+
+            self.record(Edit::Expand);
+            self.retrieve_selected(retrieval, category_mode);
+        }
+    }
+
+    /// Starts the retrieval for the selected node as a part of the recorded expansion.
+    fn retrieve_selected(&mut self, retrieval: Option<Retrieval>, category_mode: bool) {
+        if let Some(idx) = self.selected_node {
+            let n = self.g.g.node_weight(idx).unwrap().payload();
+            let retrieval = retrieval.unwrap_or(match n.url().url_type() {
+                url::Type::Category => Retrieval::Members,
+                _ if category_mode => Retrieval::Categories,
+                _ => Retrieval::Links,
+            });
+
+            let url = n.url().clone();
+            self.create_new_task(idx, url, retrieval);
+            self.state = State::GraphAndLoading;
+        }
+    }
+
+    pub fn select_node(&mut self, idx: NodeIndex) {
+        if let Some(selected) = self.selected_node {
+            let n = self.g.g.node_weight_mut(selected).unwrap();
+            n.set_selected(false);
+        }
+
+        let n = self.g.g.node_weight_mut(idx).unwrap();
+        n.set_selected(true);
+        self.selected_node = Some(idx);
+
+        self.request_summary(idx);
+        self.request_thumbnail(idx);
+    }
+
+    /// Starts retrieving summary of the article unless it is already known or being retrieved.
+    fn request_summary(&mut self, idx: NodeIndex) {
+        if self.summaries.contains_key(&idx) || self.summary_tasks.contains_key(&idx) {
+            return;
+        }
+
+        let url = self.g.g.node_weight(idx).unwrap().payload().url().clone();
+        if url.url_type() != url::Type::Article {
+            return;
+        }
+
+        let (sender, receiver) = unbounded();
+        let source = self.source.clone();
+        tokio::spawn(async move {
+            sender.send(source.summary(&url).await).unwrap();
+        });
+        self.summary_tasks.insert(idx, receiver);
+    }
+
+    /// Starts comparing links of the selected article across the entered languages.
+    pub fn compare_selected(&mut self, langs: &str) {
+        let url = match self.selected_node {
+            Some(idx) => self.g.g.node_weight(idx).unwrap().payload().url().clone(),
+            None => return,
+        };
+        let langs = langs
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|l| !l.is_empty())
+            .map(str::to_lowercase)
+            .collect::<Vec<_>>();
+
+        let (sender, receiver) = unbounded();
+        let source = self.source.clone();
+        tokio::spawn(async move {
+            sender.send(compare(source, url, langs).await).unwrap();
+        });
+        self.comparison_task = Some(receiver);
+    }
+
+    pub fn process_comparison_task(&mut self) {
+        let result = match &self.comparison_task {
+            Some(receiver) => match receiver.try_recv() {
+                Ok(result) => result,
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => {
+                    self.comparison_task = None;
+                    return;
+                }
+            },
+            None => return,
+        };
+
+        self.comparison_task = None;
+        match result {
+            Ok(comparison) => self.comparison = Some(comparison),
+            Err(err) => error!("failed to compare languages: {}", err),
+        }
+    }
+
+    /// Starts retrieving thumbnail of the media unless it is already known or being retrieved.
+    /// Other nodes and audio have no thumbnails, which is stored so they are not checked again.
+    fn request_thumbnail(&mut self, idx: NodeIndex) {
+        if self.thumbnails.contains_key(&idx) || self.thumbnail_tasks.contains_key(&idx) {
+            return;
+        }
+
+        let url = self.g.g.node_weight(idx).unwrap().payload().url().clone();
+        if url.url_type() != url::Type::File || url.media() == Some(url::Media::Audio) {
+            self.thumbnails.insert(idx, None);
+            return;
+        }
+
+        let (sender, receiver) = unbounded();
+        let source = self.source.clone();
+        tokio::spawn(async move {
+            let thumbnail = match source.thumbnail(&url).await {
+                Ok(bytes) => tokio::task::spawn_blocking(move || thumbnail::decode(&bytes))
+                    .await
+                    .unwrap(),
+                Err(err) => Err(err),
+            };
+            sender.send(thumbnail).unwrap();
+        });
+        self.thumbnail_tasks.insert(idx, receiver);
+    }
+
+    pub fn process_thumbnail_tasks(&mut self, ctx: &Context, show_thumbnails: bool) {
+        let mut finished = vec![];
+        self.thumbnail_tasks
+            .iter()
+            .for_each(|(idx, receiver)| match receiver.try_recv() {
+                Ok(Ok(image)) => finished.push((*idx, Some(image))),
+                Ok(Err(err)) => {
+                    info!("no thumbnail: {}", err);
+                    finished.push((*idx, None));
+                }
+                Err(_) => (),
+            });
+
+        finished.into_iter().for_each(|(idx, image)| {
+            self.thumbnail_tasks.remove(&idx);
+            let texture = image.map(|image| {
+                let name = self.g.g.node_weight(idx).unwrap().payload().url().val();
+                ctx.load_texture(name, image, TextureOptions::LINEAR)
+            });
+            self.thumbnails.insert(idx, texture);
+        });
+
+        if !show_thumbnails {
+            return;
+        }
+        let pending = self
+            .g
+            .g
+            .node_indices()
+            .filter(|idx| !self.thumbnails.contains_key(idx))
+            .filter(|idx| !self.thumbnail_tasks.contains_key(idx))
+            .collect::<Vec<_>>();
+        for idx in pending {
+            if self.thumbnail_tasks.len() >= MAX_THUMBNAIL_TASKS {
+                break;
+            }
+            self.request_thumbnail(idx);
+        }
+    }
+
+    /// Draws media nodes as their thumbnails if enabled.
+    pub fn apply_thumbnails(&mut self, show_thumbnails: bool) {
+        let indices = self.g.g.node_indices().collect::<Vec<_>>();
+        indices.into_iter().for_each(|idx| {
+            let thumbnail = match show_thumbnails {
+                true => self
+                    .thumbnails
+                    .get(&idx)
+                    .and_then(Option::as_ref)
+                    .map(|t| (t.id(), t.size_vec2())),
+                false => None,
+            };
+            self.g
+                .g
+                .node_weight_mut(idx)
+                .unwrap()
+                .payload_mut()
+                .set_thumbnail(thumbnail);
+        });
+    }
+
+    /// Marks the best connected nodes, their labels are drawn at any zoom.
+    pub fn apply_labels(&mut self) {
+        let mut degrees = self
+            .g
+            .g
+            .node_indices()
+            .map(|idx| (idx, self.g.g.neighbors_undirected(idx).count()))
+            .collect::<Vec<_>>();
+        degrees.sort_by(|(_, l), (_, r)| r.cmp(l));
+
+        let labeled = degrees
+            .iter()
+            .take(LABELED_NODES)
+            .map(|(idx, _)| *idx)
+            .collect::<HashSet<_>>();
+        degrees.into_iter().for_each(|(idx, _)| {
+            self.g
+                .g
+                .node_weight_mut(idx)
+                .unwrap()
+                .payload_mut()
+                .set_labeled(labeled.contains(&idx));
+        });
+    }
+
+    /// Fades nodes and edges which are not adjacent to the hovered node, or to the selected one
+    /// if nothing is hovered.
+    pub fn apply_neighbourhood(&mut self, ctx: &Context, highlight: bool) {
+        let hovered = node_shape::hovered_node(ctx, &self.node_by_url);
+        let focus = match highlight {
+            true => hovered.or(self.selected_node),
+            false => None,
+        };
+
+        let neighbours = focus.map(|idx| {
+            let mut neighbours = self.g.g.neighbors_undirected(idx).collect::<HashSet<_>>();
+            neighbours.insert(idx);
+            neighbours
+        });
+        if let Some(neighbours) = &neighbours {
+            let indices = self.g.g.node_indices().collect::<Vec<_>>();
+            indices
+                .into_iter()
+                .filter(|idx| !neighbours.contains(idx))
+                .for_each(|idx| {
+                    self.g
+                        .g
+                        .node_weight_mut(idx)
+                        .unwrap()
+                        .payload_mut()
+                        .set_faded(true)
+                });
+        }
+
+        let edges = self
+            .g
+            .g
+            .edge_references()
+            .map(|e| {
+                (
+                    e.id(),
+                    focus.is_some_and(|f| e.source() != f && e.target() != f),
+                )
+            })
+            .collect::<Vec<_>>();
+        edges.into_iter().for_each(|(idx, faded)| {
+            self.g
+                .g
+                .edge_weight_mut(idx)
+                .unwrap()
+                .payload_mut()
+                .set_faded(faded)
+        });
+    }
+
+    pub fn process_summary_tasks(&mut self) {
+        let mut finished = vec![];
+        self.summary_tasks
+            .iter()
+            .for_each(|(idx, receiver)| match receiver.try_recv() {
+                Ok(Ok(summary)) => finished.push((*idx, summary)),
+                Ok(Err(err)) => {
+                    error!("failed to get summary: {}", err);
+                    finished.push((*idx, String::new()));
+                }
+                Err(_) => (),
+            });
+
+        finished.into_iter().for_each(|(idx, summary)| {
+            self.summary_tasks.remove(&idx);
+            self.summaries.insert(idx, summary);
+        });
+    }
+
+    pub fn select_next(&mut self) -> NodeIndex {
+        let cursor = self.cursor.as_mut().unwrap();
+        let next = cursor.next_child();
+
+        self.select_node(next);
+
+        next
+    }
+
+    pub fn select_prev(&mut self) -> NodeIndex {
+        let cursor = self.cursor.as_mut().unwrap();
+        let prev = cursor.prev_child();
+
+        self.select_node(prev);
+
+        prev
+    }
+
+    pub fn select_next_article(&mut self) {
+        let cursor = self.cursor.as_mut().unwrap();
+        let mut next = || cursor.next_child();
+        let mut n = next();
+        loop {
+            if self.g.g.node_weight(n).unwrap().payload().url().url_type() == url::Type::Article {
+                break;
+            }
+
+            n = next();
+        }
+
+        self.select_node(n);
+    }
+
+    pub fn select_prev_article(&mut self) {
+        let cursor = self.cursor.as_mut().unwrap();
+        let mut prev = || cursor.prev_child();
+
+        let mut p = prev();
+        loop {
+            if self.g.g.node_weight(p).unwrap().payload().url().url_type() == url::Type::Article {
+                break;
+            }
+
+            p = prev();
+        }
+
+        self.select_node(p);
+    }
+
+    /// Shows links of the revision the timeline is at: articles it does not link are hidden,
+    /// links it added or removed are colored.
+    pub fn apply_timeline(&mut self, ctx: &Context) {
+        let timeline = match self.timeline.as_mut() {
+            Some(timeline) => timeline,
+            None => return,
+        };
+        if timeline.playing.is_some() {
+            timeline.advance(ctx.input(|i| i.time));
+            ctx.request_repaint();
+        }
+
+        let origin = self.node_by_url.get(&timeline.article).copied();
+        timeline.targets().into_iter().for_each(|url| {
+            let idx = match self.node_by_url.get(url) {
+                Some(idx) => *idx,
+                None => return,
+            };
+            let (hidden, change) = match timeline.linked(url) {
+                Linked::Kept => (false, None),
+                Linked::Added => (false, Some(Change::Added)),
+                Linked::Removed => (false, Some(Change::Removed)),
+                Linked::Absent => (true, None),
+            };
+
+            let n = self.g.g.node_weight_mut(idx).unwrap().payload_mut();
+            if hidden {
+                n.set_hidden(true);
+            }
+            n.set_change(change);
+
+            if let Some(e) = origin.and_then(|origin| self.g.g.find_edge(origin, idx)) {
+                self.g
+                    .g
+                    .edge_weight_mut(e)
+                    .unwrap()
+                    .payload_mut()
+                    .set_change(change);
+            }
+        });
+    }
+
+    fn create_new_task(&mut self, idx: NodeIndex, url: Url, retrieval: Retrieval) {
+        let (sender, receiver) = unbounded();
+        let retriever = UrlRetriever::new(self.source.clone(), sender).at(self.as_of);
+
+        info!("started {:?} retriever for {}", retrieval, url.val());
+
+        let handle = retriever.run(url, retrieval);
+        self.active_tasks
+            .insert((idx, retrieval), (receiver, handle));
+    }
+
+    /// Drops the explored graph and everything known about its nodes, so the next exploration
+    /// starts as if the tab was just opened. The entered article and date are kept.
+    fn reset(&mut self) {
+        let mut old = mem::replace(self, Exploration::new(self.source.clone()));
+        old.abort();
+
+        self.root_article_url = old.root_article_url;
+        self.state = old.state;
+        self.as_of = old.as_of;
+    }
+
+    /// Starts the exploration from the article or, if the tab already has a graph, adds the
+    /// article to it as a new root.
+    pub fn open_root(&mut self, u: Url, handling: Handling, category_mode: bool) {
+        match self.state {
+            State::Input | State::InputError => {
+                self.root_article_url = u.val().to_string();
+                self.start_exploration(u);
+                self.state = State::GraphAndLoading;
+            }
+            State::Graph => match self.node_by_url.get(&u) {
+                Some(idx) => {
+                    self.select_node(*idx);
+                    self.expand_selected(None, handling, category_mode);
+                }
+                None => {
+                    // the added root is a step of its expansion
+                    self.record(Edit::Expand);
+                    let origin = self.cursor.as_ref().unwrap().origin();
+                    let loc = self.g.g.node_weight(origin).unwrap().location();
+                    let idx = self.insert_node(loc, &node::Node::new(u));
+                    self.metrics.invalidate();
+                    self.communities.invalidate();
+                    self.select_node(idx);
+                    self.retrieve_selected(None, category_mode);
+                }
+            },
+            _ => info!("tab is still loading, {} is not added", u.val()),
+        }
+    }
+
+    /// Starts the graph from the root article and retrieves its links.
+    pub fn start_exploration(&mut self, u: Url) {
+        let idx = self.add_root(u.clone());
+        self.create_new_task(idx, u, Retrieval::Links);
+    }
+
+    /// Leaves the current graph and asks for the article to start a new exploration from.
+    pub fn new_exploration(&mut self) {
+        info!("starting new exploration");
+        self.reset();
+        self.state = State::Input;
+    }
+
+    /// Starts the graph from the root article.
+    pub fn add_root(&mut self, u: Url) -> NodeIndex {
+        self.reset();
+        let mut rng = rand::thread_rng();
+        let loc = egui::Pos2 {
+            x: rng.gen_range(-100.0..100.),
+            y: rng.gen_range(-100.0..100.),
+        };
+
+        let n = node::Node::new(u.clone());
+        let label = n.label();
+        let idx: NodeIndex = self.g.add_node_with_label_and_location(n, label, loc);
+
+        self.node_by_url.insert(u, idx);
+
+        self.layout.add_node(idx, loc);
+        self.metrics.invalidate();
+        self.communities.invalidate();
+
+        idx
+    }
+
+    /// Runs the layout without drawing, so the graph can be exported without a window.
+    pub fn settle_layout(&mut self, steps: usize) {
+        (0..steps).for_each(|_| {
+            self.layout.sync(&mut self.g);
+            self.layout.update();
+        });
+        self.layout.sync(&mut self.g);
+    }
+
+    pub fn select_next_root(&mut self) {
+        let cursor = self.cursor.as_mut().unwrap();
+        let curr_root = cursor.position().0;
+        if let Some(next) = cursor.next_root() {
+            self.select_node(next);
+        } else {
+            self.select_node(curr_root);
+        }
+    }
+
+    pub fn select_prev_root(&mut self) {
+        let cursor = self.cursor.as_mut().unwrap();
+        let curr_root = cursor.position().0;
+        if let Some(prev) = cursor.prev_root() {
+            self.select_node(prev);
+        } else {
+            self.select_node(curr_root);
+        }
+    }
+
+    /// Loads graph imported from the dumps as if all its links were retrieved.
+    pub fn load_import(&mut self, imported: ImportedGraph) {
+        let mut seeds = imported.seeds.into_iter();
+        let root = match seeds.next() {
+            Some(root) => root,
+            None => return,
+        };
+        self.root_article_url = root.val().to_string();
+        let root_idx = self.add_root(root);
+
+        // links of every seed can come before the links leading to it
+        let loc = self.g.g.node_weight(root_idx).unwrap().location();
+        seeds.for_each(|seed| {
+            let idx = add_node(
+                &mut self.g,
+                &mut self.layout,
+                loc,
+                &node::Node::new(seed.clone()),
+            );
+            self.node_by_url.insert(seed, idx);
+        });
+
+        imported.links.into_iter().for_each(|(from, to)| {
+            let parent_idx = self.node_by_url[&from];
+            let idx = match self.node_by_url.get(&to) {
+                Some(idx) => *idx,
+                None => {
+                    let parent_loc = self.g.g.node_weight(parent_idx).unwrap().location();
+                    let idx = add_node(
+                        &mut self.g,
+                        &mut self.layout,
+                        parent_loc,
+                        &node::Node::new(to.clone()),
+                    );
+                    self.node_by_url.insert(to, idx);
+                    idx
+                }
+            };
+
+            // dumps do not keep anchors, wikipedia shows the title by default
+            let context = LinkContext {
+                anchor: self.g.g.node_weight(idx).unwrap().payload().label(),
+                ..Default::default()
+            };
+            add_edge(&mut self.g, &mut self.layout, parent_idx, idx, context);
+        });
+
+        self.metrics.invalidate();
+        self.communities.invalidate();
+        self.state = State::GraphLoaded;
+    }
+
+    /// Starts the graph from the root of the saved session. Roots other than the first one are
+    /// placed under it.
+    pub fn open_session(&mut self, root: Url, session: &Session) {
+        self.root_article_url = root.val().to_string();
+        let origin = self.add_root(root);
+        let loc = self.g.g.node_weight(origin).unwrap().location();
+        session
+            .nodes
+            .iter()
+            .filter_map(|n| Url::new(n).ok())
+            .for_each(|url| {
+                if self.node_by_url.contains_key(&url) {
+                    return;
+                }
+                let idx = add_node(
+                    &mut self.g,
+                    &mut self.layout,
+                    loc,
+                    &node::Node::new(url.clone()),
+                );
+                self.node_by_url.insert(url, idx);
+            });
+
+        session.links.iter().for_each(|link| {
+            let (start, end) = match (Url::new(&link.from), Url::new(&link.to)) {
+                (Ok(from), Ok(to)) => {
+                    match (self.node_by_url.get(&from), self.node_by_url.get(&to)) {
+                        (Some(start), Some(end)) => (*start, *end),
+                        _ => return,
+                    }
+                }
+                _ => return,
+            };
+            link.contexts.iter().for_each(|context| {
+                add_edge(&mut self.g, &mut self.layout, start, end, context.clone());
+            });
+            // links saved without their context get the title, as wikipedia shows by default
+            if link.contexts.is_empty() {
+                let context = LinkContext {
+                    anchor: self.g.g.node_weight(end).unwrap().payload().label(),
+                    ..Default::default()
+                };
+                add_edge(&mut self.g, &mut self.layout, start, end, context);
+            }
+        });
+
+        let mut cursor = Cursor::new(origin, &self.g);
+        session
+            .roots
+            .iter()
+            .skip(1)
+            .filter_map(|r| self.node_by_url.get(&Url::new(r).ok()?))
+            .for_each(|root| cursor.attach(*root, origin, &self.g));
+        self.cursor = Some(cursor);
+
+        self.metrics.invalidate();
+        self.communities.invalidate();
+        self.state = State::Graph;
+        self.select_node(origin);
+    }
+
+    /// Colors articles and links added or removed between the compared sessions.
+    pub fn mark_changes(&mut self, diff: &Diff) {
+        let changed_nodes = diff
+            .added_nodes
+            .iter()
+            .map(|n| (n, Change::Added))
+            .chain(diff.removed_nodes.iter().map(|n| (n, Change::Removed)))
+            .filter_map(|(n, change)| Some((Url::new(n).ok()?, change)));
+        let changed_links = diff
+            .added_links
+            .iter()
+            .map(|l| (l, Change::Added))
+            .chain(diff.removed_links.iter().map(|l| (l, Change::Removed)))
+            .filter_map(|((from, to), change)| {
+                Some((Url::new(from).ok()?, Url::new(to).ok()?, change))
+            });
+
+        changed_nodes.for_each(|(url, change)| {
+            if let Some(idx) = self.node_by_url.get(&url) {
+                let n = self.g.g.node_weight_mut(*idx).unwrap().payload_mut();
+                n.set_change(Some(change));
+            }
+        });
+        changed_links.for_each(|(from, to, change)| {
+            let edge = match (self.node_by_url.get(&from), self.node_by_url.get(&to)) {
+                (Some(start), Some(end)) => self.g.g.find_edge(*start, *end),
+                _ => None,
+            };
+            if let Some(idx) = edge {
+                let e = self.g.g.edge_weight_mut(idx).unwrap().payload_mut();
+                e.set_change(Some(change));
+            }
+        });
+    }
+
+    /// Starts the graph from the article linked to every article any of its revisions links, so
+    /// the layout stays the same while the timeline moves between revisions.
+    pub fn open_timeline(&mut self, timeline: Timeline) {
+        self.root_article_url = timeline.article.val().to_string();
+        let origin = self.add_root(timeline.article.clone());
+        let loc = self.g.g.node_weight(origin).unwrap().location();
+        timeline.targets().into_iter().for_each(|url| {
+            let idx = add_node(
+                &mut self.g,
+                &mut self.layout,
+                loc,
+                &node::Node::new(url.clone()),
+            );
+            self.node_by_url.insert(url.clone(), idx);
+
+            let context = LinkContext {
+                anchor: url.val_for_type(),
+                ..Default::default()
+            };
+            add_edge(&mut self.g, &mut self.layout, origin, idx, context);
+        });
+
+        self.cursor = Some(Cursor::new(origin, &self.g));
+        self.timeline = Some(timeline);
+        self.metrics.invalidate();
+        self.communities.invalidate();
+        self.state = State::Graph;
+        self.select_node(origin);
+    }
+
+    /// Adds the graph of another exploration to this one and stops its retrievals. Articles
+    /// found in both become one node and roots of the merged exploration become roots under
+    /// the origin. Undoing the merge removes the merged nodes.
+    pub fn merge(&mut self, mut other: Exploration) {
+        self.record(Edit::MergeTab);
+        other.abort();
+        info!(
+            "merging tab with {} nodes into the shown one",
+            other.g.g.node_count()
+        );
+
+        // merged graph is placed beside the shown one
+        let origin = self.cursor.as_ref().unwrap().origin();
+        let other_origin = other.cursor.as_ref().unwrap().origin();
+        let offset = self.g.g.node_weight(origin).unwrap().location()
+            + Vec2::new(MERGED_TAB_OFFSET, 0.)
+            - other.g.g.node_weight(other_origin).unwrap().location();
+
+        let mut merged = HashMap::new();
+        other.g.g.node_indices().for_each(|other_idx| {
+            let n = other.g.g.node_weight(other_idx).unwrap();
+            let url = n.payload().url().clone();
+            let idx = match self.node_by_url.get(&url) {
+                Some(idx) => *idx,
+                None => {
+                    let loc = n.location() + offset;
+                    let idx = self.g.add_node_with_label_and_location(
+                        n.payload().clone(),
+                        n.label(),
+                        loc,
+                    );
+                    self.layout.add_node(idx, loc);
+                    self.node_by_url.insert(url, idx);
+                    self.history.log(Step::AddNode {
+                        node: n.payload().clone(),
+                        location: loc,
+                        merged_into: None,
+                    });
+                    idx
+                }
+            };
+            merged.insert(other_idx, idx);
+        });
+
+        // links known in both tabs are kept as they are in the shown one
+        other
+            .g
+            .g
+            .edge_references()
+            .map(|e| {
+                (
+                    merged[&e.source()],
+                    merged[&e.target()],
+                    e.weight().payload(),
+                )
+            })
+            .filter(|(start, end, _)| self.g.g.find_edge(*start, *end).is_none())
+            .collect::<Vec<_>>()
+            .into_iter()
+            .for_each(|(start, end, edge)| {
+                edge.occurrences()
+                    .iter()
+                    .for_each(|context| self.insert_link(start, end, context.clone()));
+            });
+
+        let cursor = self.cursor.as_mut().unwrap();
+        other
+            .cursor
+            .unwrap()
+            .roots_with_parents()
+            .into_iter()
+            .for_each(|(root, parent)| {
+                let root = merged[&root];
+                if cursor.is_root(root) {
+                    return;
+                }
+                let parent = parent.map_or(origin, |p| merged[&p]);
+                cursor.attach(root, parent, &self.g);
+            });
+
+        self.metrics.invalidate();
+        self.communities.invalidate();
+    }
+}
+
+/// Nodes linked with the node in any direction, without the node itself.
+fn distinct_neighbours(g: &Graph, idx: NodeIndex) -> HashSet<NodeIndex> {
+    g.g.neighbors_undirected(idx)
+        .filter(|n| *n != idx)
+        .collect()
+}
+
+fn add_node(g: &mut Graph, layout: &mut Layout, loc_center: Pos2, n: &node::Node) -> NodeIndex {
+    let mut rng = rand::thread_rng();
+    let loc = egui::Pos2 {
+        x: loc_center.x + rng.gen_range(-100.0..100.),
+        y: loc_center.y + rng.gen_range(-100.0..100.),
+    };
+
+    let idx = g.add_node_with_label_and_location(n.clone(), n.label(), loc);
+    layout.add_node(idx, loc);
+    idx
+}
+
+/// Adds edge for the link or, if the article already links the target, records one more
+/// occurrence and makes the simulation edge proportionally stronger.
+fn add_edge(
+    g: &mut Graph,
+    layout: &mut Layout,
+    start: NodeIndex,
+    end: NodeIndex,
+    context: LinkContext,
+) {
+    let existing = match g.g.find_edge(start, end) {
+        Some(idx) => idx,
+        None => {
+            g.add_edge(start, end, Edge::new(context));
+            layout.set_edge(start, end, 1);
+            return;
+        }
+    };
+
+    // edge is readded so its display picks up the new multiplicity
+    let mut edge = g.g.remove_edge(existing).unwrap().payload().clone();
+    edge.add(context);
+    let count = edge.count();
+    g.add_edge(start, end, edge);
+
+    layout.set_edge(start, end, count);
+}
@@ -0,0 +1,439 @@
+use std::{
+    fmt::Write,
+    fs,
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+use egui::{Color32, Pos2, Rect, Vec2, Visuals};
+use petgraph::visit::{EdgeRef, IntoEdgeReferences};
+use resvg::{
+    tiny_skia::{Pixmap, Transform},
+    usvg::{self, fontdb, PostProcessingSteps, TreeParsing, TreePostProc},
+};
+
+use crate::{
+    graph::Graph,
+    url::Type,
+    views::{
+        edge_shape, legend,
+        node_shape::{self, Form},
+        style::{change_color, Theme},
+    },
+};
+
+/// Space around the exported graph in canvas units.
+const MARGIN: f32 = 40.;
+const LABEL_SIZE: f32 = 12.;
+/// Bounds of the node label size in the image, labels follow the scale of the nodes within them.
+const LABEL_MIN_SIZE: f32 = 8.;
+const LABEL_MAX_SIZE: f32 = 48.;
+const EDGE_OPACITY: f32 = 0.3;
+const TIP_SIZE: f32 = 6.;
+const LEGEND_ICON_RADIUS: f32 = 6.;
+const LEGEND_ROW: f32 = 18.;
+const LEGEND_PADDING: f32 = 10.;
+const LEGEND_WIDTH: f32 = 180.;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Png,
+    Svg,
+}
+
+impl Format {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "png" => Some(Format::Png),
+            "svg" => Some(Format::Svg),
+            _ => None,
+        }
+    }
+}
+
+/// What part of the graph is exported and how.
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// Width of the image in pixels, the height follows the exported area.
+    pub width: u32,
+    pub legend: bool,
+    /// Exported area in canvas coordinates, the whole graph if not set.
+    pub area: Option<Rect>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            width: 1920,
+            legend: true,
+            area: None,
+        }
+    }
+}
+
+/// Writes the graph to the path as PNG or SVG depending on the extension.
+///
+/// Nodes are drawn at their layout locations with labels and with the colors they have in the
+/// graph, which makes it usable without a window.
+pub fn export(g: &Graph, theme: &Theme, path: &Path, options: &Options) -> io::Result<()> {
+    let format = Format::from_path(path).ok_or_else(|| {
+        io::Error::new(
+            ErrorKind::InvalidInput,
+            format!("unknown image format: {}", path.display()),
+        )
+    })?;
+
+    let svg = svg(g, theme, options);
+    match format {
+        Format::Svg => fs::write(path, svg),
+        Format::Png => fs::write(path, png(&svg)?),
+    }
+}
+
+/// Renders the graph to an SVG document which pixel size is set by the options.
+pub fn svg(g: &Graph, theme: &Theme, options: &Options) -> String {
+    let visuals = match theme.dark {
+        true => Visuals::dark(),
+        false => Visuals::light(),
+    };
+    let fg = visuals.text_color();
+
+    let area = options.area.unwrap_or_else(|| bounds(g));
+    let scale = options.width as f32 / area.width().max(f32::EPSILON);
+    let height = (area.height() * scale).ceil().max(1.) as u32;
+    let to_image = |p: Pos2| ((p - area.min) * scale).to_pos2();
+    let label_size = (LABEL_SIZE * scale).clamp(LABEL_MIN_SIZE, LABEL_MAX_SIZE);
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = options.width,
+        h = height
+    )
+    .unwrap();
+    writeln!(
+        out,
+        r#"<rect width="100%" height="100%" fill="{}"/>"#,
+        hex(visuals.panel_fill)
+    )
+    .unwrap();
+
+    let default_edge_color = fg.gamma_multiply(EDGE_OPACITY);
+    out.push_str("<g>\n");
+    g.g.edge_references().for_each(|e| {
+        let (start, end) = (
+            g.g.node_weight(e.source()).unwrap(),
+            g.g.node_weight(e.target()).unwrap(),
+        );
+        if start.payload().hidden() || end.payload().hidden() {
+            return;
+        }
+
+        let edge_color = e
+            .weight()
+            .payload()
+            .change()
+            .map_or(default_edge_color, |c| change_color(c).gamma_multiply(EDGE_OPACITY));
+        let width = edge_shape::width(e.weight().payload()) * scale;
+        let dir = end.location() - start.location();
+        if dir.length() < f32::EPSILON {
+            return;
+        }
+        let dir = dir.normalized();
+        let from = to_image(start.display().boundary_point(dir));
+        let to = to_image(end.display().boundary_point(-dir));
+        // anchor, section and sentence of every occurrence of the link
+        let contexts = serde_json::to_string(e.weight().payload().occurrences()).unwrap();
+        writeln!(
+            out,
+            r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="{}" stroke-opacity="{:.2}" stroke-width="{:.2}" data-contexts="{}"/>"#,
+            from.x,
+            from.y,
+            to.x,
+            to.y,
+            hex(edge_color),
+            opacity(edge_color),
+            width,
+            escape(&contexts)
+        )
+        .unwrap();
+
+        let tip = TIP_SIZE * scale;
+        let back = to - dir * tip;
+        let side = dir.rot90() * tip / 2.;
+        out.push_str(&polygon(&[to, back + side, back - side], edge_color, false));
+    });
+    out.push_str("</g>\n<g>\n");
+
+    g.g.node_weights().for_each(|n| {
+        let payload = n.payload();
+        if payload.hidden() {
+            return;
+        }
+
+        let t = payload.url().url_type();
+        let color = payload
+            .change()
+            .map(change_color)
+            .or(payload.color())
+            .unwrap_or_else(|| theme.type_color(&t));
+        let center = to_image(n.location());
+        let radius = node_shape::radius(payload) * scale;
+        match payload.community() {
+            Some(id) => writeln!(out, r#"<g data-community="{}">"#, id).unwrap(),
+            None => out.push_str("<g>\n"),
+        }
+        out.push_str(&form(
+            Form::of(&t),
+            center,
+            radius,
+            color,
+            payload.disambiguation(),
+        ));
+        out.push_str(&text(
+            Pos2::new(center.x, center.y - radius - label_size / 2.),
+            &n.label(),
+            label_size,
+            fg,
+            true,
+        ));
+        out.push_str("</g>\n");
+    });
+    out.push_str("</g>\n");
+
+    if options.legend {
+        out.push_str(&legend_group(theme, &visuals));
+    }
+
+    out.push_str("</svg>\n");
+    out
+}
+
+/// Rasterizes the SVG document. Labels are drawn with the system fonts.
+pub fn png(svg: &str) -> io::Result<Vec<u8>> {
+    let mut tree = usvg::Tree::from_str(svg, &usvg::Options::default())
+        .map_err(|err| io::Error::new(ErrorKind::InvalidData, err.to_string()))?;
+    let mut fonts = fontdb::Database::new();
+    fonts.load_system_fonts();
+    // generic monospace defaults to a font which is often missing, any installed one is used
+    let monospace = fonts
+        .faces()
+        .find(|f| f.monospaced)
+        .and_then(|f| f.families.first())
+        .map(|(name, _)| name.clone());
+    if let Some(monospace) = monospace {
+        fonts.set_monospace_family(monospace);
+    }
+    tree.postprocess(PostProcessingSteps::default(), &fonts);
+
+    let size = tree.size.to_int_size();
+    let mut pixmap = Pixmap::new(size.width(), size.height()).ok_or_else(|| {
+        io::Error::new(
+            ErrorKind::InvalidInput,
+            format!("image is too large: {}x{}", size.width(), size.height()),
+        )
+    })?;
+    resvg::render(&tree, Transform::default(), &mut pixmap.as_mut());
+
+    pixmap
+        .encode_png()
+        .map_err(|err| io::Error::new(ErrorKind::Other, err.to_string()))
+}
+
+/// Area taken by the drawn nodes with a margin for labels.
+fn bounds(g: &Graph) -> Rect {
+    let rect =
+        g.g.node_weights()
+            .filter(|n| !n.payload().hidden())
+            .fold(Rect::NOTHING, |rect, n| {
+                let radius = node_shape::radius(n.payload());
+                rect.union(Rect::from_center_size(
+                    n.location(),
+                    Vec2::splat(radius * 2.),
+                ))
+            });
+
+    match rect.is_positive() {
+        true => rect.expand(MARGIN),
+        false => Rect::from_center_size(rect.center(), Vec2::splat(MARGIN * 2.)),
+    }
+}
+
+/// Form and color of every url type in the top left corner.
+fn legend_group(theme: &Theme, visuals: &Visuals) -> String {
+    let height = LEGEND_PADDING * 2. + LEGEND_ROW * (Type::all().len() + 1) as f32;
+    let mut out = String::from("<g>\n");
+    writeln!(
+        out,
+        r#"<rect x="{p}" y="{p}" width="{w}" height="{h}" fill="{}" stroke="{}"/>"#,
+        hex(visuals.extreme_bg_color),
+        hex(visuals.widgets.noninteractive.bg_stroke.color),
+        p = LEGEND_PADDING,
+        w = LEGEND_WIDTH,
+        h = height,
+    )
+    .unwrap();
+
+    let row = |i: usize| LEGEND_PADDING * 2. + LEGEND_ROW * (i as f32 + 0.5);
+    let icon_x = LEGEND_PADDING * 2. + LEGEND_ICON_RADIUS;
+    let text_x = icon_x + LEGEND_ICON_RADIUS * 2.;
+    Type::all().iter().enumerate().for_each(|(i, t)| {
+        out.push_str(&form(
+            Form::of(t),
+            Pos2::new(icon_x, row(i)),
+            LEGEND_ICON_RADIUS,
+            theme.type_color(t),
+            false,
+        ));
+        out.push_str(&text(
+            Pos2::new(text_x, row(i)),
+            legend::title(t),
+            LABEL_SIZE,
+            visuals.text_color(),
+            false,
+        ));
+    });
+
+    let i = Type::all().len();
+    out.push_str(&form(
+        Form::Circle,
+        Pos2::new(icon_x, row(i)),
+        LEGEND_ICON_RADIUS,
+        theme.article,
+        true,
+    ));
+    out.push_str(&text(
+        Pos2::new(text_x, row(i)),
+        "disambiguation page",
+        LABEL_SIZE,
+        visuals.text_color(),
+        false,
+    ));
+
+    out.push_str("</g>\n");
+    out
+}
+
+fn form(form: Form, center: Pos2, radius: f32, color: Color32, hollow: bool) -> String {
+    match form {
+        Form::Circle => {
+            format!(
+                r#"<circle cx="{:.1}" cy="{:.1}" r="{:.2}" {}/>"#,
+                center.x,
+                center.y,
+                radius,
+                paint(color, hollow)
+            ) + "\n"
+        }
+        Form::Square => {
+            format!(
+                r#"<rect x="{:.1}" y="{:.1}" width="{:.2}" height="{:.2}" {}/>"#,
+                center.x - radius,
+                center.y - radius,
+                radius * 2.,
+                radius * 2.,
+                paint(color, hollow)
+            ) + "\n"
+        }
+        Form::Diamond => polygon(
+            &[
+                center + Vec2::new(0., -radius),
+                center + Vec2::new(radius, 0.),
+                center + Vec2::new(0., radius),
+                center + Vec2::new(-radius, 0.),
+            ],
+            color,
+            hollow,
+        ),
+    }
+}
+
+fn polygon(points: &[Pos2], color: Color32, hollow: bool) -> String {
+    let points = points
+        .iter()
+        .map(|p| format!("{:.1},{:.1}", p.x, p.y))
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!(r#"<polygon points="{}" {}/>"#, points, paint(color, hollow)) + "\n"
+}
+
+fn text(pos: Pos2, text: &str, size: f32, color: Color32, centered: bool) -> String {
+    format!(
+        r#"<text x="{:.1}" y="{:.1}" font-family="monospace" font-size="{:.1}" fill="{}" text-anchor="{}" dominant-baseline="middle">{}</text>"#,
+        pos.x,
+        pos.y,
+        size,
+        hex(color),
+        match centered {
+            true => "middle",
+            false => "start",
+        },
+        escape(&text.replace('_', " "))
+    ) + "\n"
+}
+
+fn paint(color: Color32, hollow: bool) -> String {
+    match hollow {
+        true => format!(
+            r#"fill="none" stroke="{}" stroke-opacity="{:.2}" stroke-width="1.5""#,
+            hex(color),
+            opacity(color)
+        ),
+        false => format!(
+            r#"fill="{}" fill-opacity="{:.2}""#,
+            hex(color),
+            opacity(color)
+        ),
+    }
+}
+
+fn hex(color: Color32) -> String {
+    let [r, g, b, _] = color.to_srgba_unmultiplied();
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+fn opacity(color: Color32) -> f32 {
+    color.a() as f32 / 255.
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::Pos2;
+    use petgraph::stable_graph::StableGraph;
+
+    use super::*;
+    use crate::{edge::Edge, node::Node, testing::url};
+
+    #[test]
+    fn svg_has_labels_links_and_communities() {
+        let mut g = Graph::new(StableGraph::new());
+        let mut add = |title: &str, loc: Pos2| {
+            let mut n = Node::new(url(title));
+            n.set_community(Some(7));
+            let label = n.label();
+            g.add_node_with_label_and_location(n, label, loc)
+        };
+        let (a, b) = (
+            add("Graph_theory", Pos2::ZERO),
+            add("Topology", Pos2::new(100., 0.)),
+        );
+        g.add_edge(a, b, Edge::new(Default::default()));
+
+        let svg = svg(&g, &Theme::default(), &Options::default());
+
+        assert!(svg.contains(">Graph theory</text>"));
+        assert!(svg.contains(">Topology</text>"));
+        assert_eq!(svg.matches("<line ").count(), 1);
+        assert_eq!(svg.matches(r#"data-community="7""#).count(), 2);
+        // the graph is scaled up to the width, the labels with it up to the bound
+        assert!(svg.contains(&format!(r#"font-size="{:.1}""#, LABEL_MAX_SIZE)));
+    }
+}
@@ -0,0 +1,71 @@
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// Page fetched from the network as it is stored in the fixtures directory.
+///
+/// Every page is two files named after the hash of the requested url: `<hash>.toml` with the
+/// response meta and `<hash>.body` with the response body.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Page {
+    /// Url of the response, differs from the requested one if there were redirects.
+    pub url: String,
+    pub status: u16,
+    pub headers: BTreeMap<String, String>,
+    /// Raw bytes, as thumbnails are recorded too.
+    #[serde(skip)]
+    pub body: Vec<u8>,
+}
+
+impl Page {
+    pub fn text(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.body)
+    }
+
+    pub fn save(&self, dir: &Path, requested: &str) -> io::Result<()> {
+        fs::create_dir_all(dir)?;
+
+        let meta = toml::to_string_pretty(self)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        let (meta_path, body_path) = paths(dir, requested);
+        fs::write(meta_path, meta)?;
+        fs::write(body_path, &self.body)
+    }
+
+    /// Loads the page recorded for the requested url. Returns `None` if it was not recorded.
+    pub fn load(dir: &Path, requested: &str) -> io::Result<Option<Self>> {
+        let (meta_path, body_path) = paths(dir, requested);
+        let meta = match fs::read_to_string(meta_path) {
+            Ok(meta) => meta,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+
+        let mut page: Page =
+            toml::from_str(&meta).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        page.body = fs::read(body_path)?;
+
+        Ok(Some(page))
+    }
+}
+
+/// File names are the FNV-1a hash of the url so they are the same on every run and platform.
+fn paths(dir: &Path, requested: &str) -> (PathBuf, PathBuf) {
+    let hash = requested
+        .bytes()
+        .fold(FNV_OFFSET, |h, b| (h ^ b as u64).wrapping_mul(FNV_PRIME));
+    let name = format!("{:016x}", hash);
+
+    (
+        dir.join(format!("{}.toml", name)),
+        dir.join(format!("{}.body", name)),
+    )
+}
@@ -0,0 +1,10 @@
+use petgraph::{stable_graph::DefaultIx, Directed};
+
+use crate::{
+    edge::Edge,
+    node::Node,
+    views::{edge_shape::EdgeShape, node_shape::NodeShape},
+};
+
+/// Links graph drawn by the app.
+pub type Graph = egui_graphs::Graph<Node, Edge, Directed, DefaultIx, NodeShape, EdgeShape>;
@@ -0,0 +1,200 @@
+use std::collections::VecDeque;
+
+use egui::Pos2;
+
+use crate::{link::LinkContext, node::Node, url::Url};
+
+/// Max number of edits which can be undone.
+const MAX_EDITS: usize = 50;
+
+/// Graph mutations which can be undone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edit {
+    Expand,
+    Delete,
+    Collapse,
+    Merge,
+    MergeTab,
+    /// Nodes of the same wikidata item merged without asking.
+    MergeDuplicates,
+}
+
+impl Edit {
+    pub fn title(&self) -> &'static str {
+        match self {
+            Edit::Expand => "expand",
+            Edit::Delete => "delete",
+            Edit::Collapse => "collapse",
+            Edit::Merge => "merge",
+            Edit::MergeTab => "merge tabs",
+            Edit::MergeDuplicates => "merge duplicates",
+        }
+    }
+}
+
+/// Change of the graph made by an edit. Nodes are referred to by their urls, as a node removed
+/// and added back gets another index.
+#[derive(Clone)]
+pub enum Step {
+    /// The node was added at the location. A node merged into another one leaves its url
+    /// leading to that node while it is removed.
+    AddNode {
+        node: Node,
+        location: Pos2,
+        merged_into: Option<Url>,
+    },
+    RemoveNode {
+        node: Node,
+        location: Pos2,
+        merged_into: Option<Url>,
+    },
+    /// Occurrences were added to the link, which was created if there was none.
+    AddLink {
+        from: Url,
+        to: Url,
+        contexts: Vec<LinkContext>,
+    },
+    /// The last occurrences were removed from the link, which was dropped if none was left.
+    RemoveLink {
+        from: Url,
+        to: Url,
+        contexts: Vec<LinkContext>,
+    },
+}
+
+impl Step {
+    /// Step which reverts this one.
+    pub fn inverse(self) -> Step {
+        match self {
+            Step::AddNode {
+                node,
+                location,
+                merged_into,
+            } => Step::RemoveNode {
+                node,
+                location,
+                merged_into,
+            },
+            Step::RemoveNode {
+                node,
+                location,
+                merged_into,
+            } => Step::AddNode {
+                node,
+                location,
+                merged_into,
+            },
+            Step::AddLink { from, to, contexts } => Step::RemoveLink { from, to, contexts },
+            Step::RemoveLink { from, to, contexts } => Step::AddLink { from, to, contexts },
+        }
+    }
+}
+
+/// Cursor roots with their parents, collapsed nodes and the selection, which refer to nodes by
+/// their indices, stored by urls.
+#[derive(Clone, Default)]
+pub struct Marks {
+    pub roots: Vec<(Url, Option<Url>)>,
+    pub position: Option<(Url, Url)>,
+    pub collapsed_children: Vec<(Url, Vec<Url>)>,
+    pub selected: Option<Url>,
+}
+
+/// Edit together with the steps it made and the marks before them.
+pub struct Entry {
+    pub edit: Edit,
+    pub steps: Vec<Step>,
+    pub marks: Marks,
+}
+
+/// Undo and redo stacks.
+#[derive(Default)]
+pub struct History {
+    undo: VecDeque<Entry>,
+    redo: Vec<Entry>,
+    /// Steps are added to the last edit until it is closed, undone or another one starts, e.g.
+    /// links retrieved for an expansion arrive one by one.
+    open: bool,
+}
+
+impl History {
+    /// Starts the edit with the marks before it. Undone edits can not be redone after it.
+    pub fn record(&mut self, edit: Edit, marks: Marks) {
+        self.redo.clear();
+        self.push_undo(Entry {
+            edit,
+            steps: vec![],
+            marks,
+        });
+        self.open = true;
+    }
+
+    /// Adds the step to the edit in progress. Steps made outside of edits, such as the first
+    /// load of the graph, can not be undone.
+    pub fn log(&mut self, step: Step) {
+        if !self.open {
+            return;
+        }
+        let steps = match self.undo.back_mut() {
+            Some(entry) => &mut entry.steps,
+            None => return,
+        };
+
+        // occurrences of the same link found one after another are one step
+        if let (
+            Some(Step::AddLink { from, to, contexts }),
+            Step::AddLink {
+                from: next_from,
+                to: next_to,
+                contexts: next,
+            },
+        ) = (steps.last_mut(), &step)
+        {
+            if from == next_from && to == next_to {
+                contexts.extend(next.iter().cloned());
+                return;
+            }
+        }
+        steps.push(step);
+    }
+
+    /// Ends the edit in progress, e.g. once all links of an expansion arrived. Later steps are
+    /// not added to it.
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+
+    #[cfg(test)]
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    /// Takes the last edit. Once reverted it is given back with [`History::undone`].
+    pub fn undo(&mut self) -> Option<Entry> {
+        self.open = false;
+        self.undo.pop_back()
+    }
+
+    /// Keeps the reverted edit, its steps revert the undo.
+    pub fn undone(&mut self, entry: Entry) {
+        self.redo.push(entry);
+    }
+
+    /// Takes the last undone edit. Once reverted it is given back with [`History::redone`].
+    pub fn redo(&mut self) -> Option<Entry> {
+        self.open = false;
+        self.redo.pop()
+    }
+
+    /// Keeps the edit made again, its steps revert the redo.
+    pub fn redone(&mut self, entry: Entry) {
+        self.push_undo(entry);
+    }
+
+    fn push_undo(&mut self, entry: Entry) {
+        self.undo.push_back(entry);
+        if self.undo.len() > MAX_EDITS {
+            self.undo.pop_front();
+        }
+    }
+}
@@ -0,0 +1,290 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+use egui::{Event, InputState, Key, Modifiers};
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::command::Command;
+
+/// A key with modifiers, e.g. `Ctrl+P`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Binding {
+    key: Key,
+    modifiers: Modifiers,
+}
+
+impl Binding {
+    pub fn new(key: Key, modifiers: Modifiers) -> Self {
+        Self { key, modifiers }
+    }
+
+    pub fn key(key: Key) -> Self {
+        Self::new(key, Modifiers::NONE)
+    }
+
+    pub fn pressed(&self, i: &InputState) -> bool {
+        i.modifiers.matches_exact(self.modifiers) && i.key_pressed(self.key)
+    }
+}
+
+impl Display for Binding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.modifiers.command || self.modifiers.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.alt {
+            write!(f, "Alt+")?;
+        }
+        if self.modifiers.shift {
+            write!(f, "Shift+")?;
+        }
+        write!(f, "{}", self.key.name())
+    }
+}
+
+impl TryFrom<String> for Binding {
+    type Error = String;
+
+    fn try_from(val: String) -> Result<Self, Self::Error> {
+        let mut modifiers = Modifiers::NONE;
+        let mut parts = val.split('+').map(str::trim).collect::<Vec<_>>();
+        let key_name = parts.pop().unwrap_or_default();
+
+        for part in parts {
+            match part.to_lowercase().as_str() {
+                "ctrl" | "cmd" | "command" => modifiers.command = true,
+                "alt" | "option" => modifiers.alt = true,
+                "shift" => modifiers.shift = true,
+                _ => return Err(format!("unknown modifier {} in binding {}", part, val)),
+            }
+        }
+
+        match Key::from_name(key_name) {
+            Some(key) => Ok(Self::new(key, modifiers)),
+            None => Err(format!("unknown key {} in binding {}", key_name, val)),
+        }
+    }
+}
+
+impl From<Binding> for String {
+    fn from(val: Binding) -> Self {
+        val.to_string()
+    }
+}
+
+/// Maps commands to their key bindings. A command can have several bindings.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct Keymap {
+    bindings: BTreeMap<Command, Vec<Binding>>,
+}
+
+/// Bindings from the config replace the default bindings of their commands only, so commands
+/// missing in the config keep their defaults. Keys bound in the config are taken from the
+/// default bindings of other commands.
+impl<'de> Deserialize<'de> for Keymap {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let configured = BTreeMap::<Command, Vec<Binding>>::deserialize(deserializer)?;
+
+        let mut keymap = Keymap::default();
+        keymap.bindings.values_mut().for_each(|bindings| {
+            bindings.retain(|b| !configured.values().flatten().any(|c| c == b))
+        });
+        keymap.bindings.extend(configured);
+
+        Ok(keymap)
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let mut bindings = BTreeMap::new();
+        bindings.insert(Command::NextChild, vec![Binding::key(Key::L)]);
+        bindings.insert(Command::PrevChild, vec![Binding::key(Key::H)]);
+        bindings.insert(Command::NextArticle, vec![Binding::key(Key::ArrowRight)]);
+        bindings.insert(Command::PrevArticle, vec![Binding::key(Key::ArrowLeft)]);
+        bindings.insert(
+            Command::NextRoot,
+            vec![Binding::key(Key::ArrowDown), Binding::key(Key::J)],
+        );
+        bindings.insert(
+            Command::PrevRoot,
+            vec![Binding::key(Key::ArrowUp), Binding::key(Key::K)],
+        );
+        bindings.insert(Command::Expand, vec![Binding::key(Key::Enter)]);
+        bindings.insert(Command::ExpandBacklinks, vec![Binding::key(Key::B)]);
+        bindings.insert(Command::DeleteNode, vec![Binding::key(Key::Delete)]);
+        bindings.insert(
+            Command::DeleteSubtree,
+            vec![Binding::new(Key::Delete, Modifiers::SHIFT)],
+        );
+        bindings.insert(Command::CollapseChildren, vec![Binding::key(Key::X)]);
+        bindings.insert(
+            Command::Undo,
+            vec![Binding::new(Key::Z, Modifiers::COMMAND)],
+        );
+        bindings.insert(
+            Command::Redo,
+            vec![Binding::new(Key::Z, Modifiers::COMMAND | Modifiers::SHIFT)],
+        );
+        bindings.insert(Command::ToggleCategoryMode, vec![Binding::key(Key::C)]);
+        bindings.insert(Command::ToggleThumbnails, vec![Binding::key(Key::T)]);
+        bindings.insert(Command::ToggleNeighbourhood, vec![Binding::key(Key::N)]);
+        bindings.insert(Command::OpenUrl, vec![Binding::key(Key::O)]);
+        bindings.insert(
+            Command::CommandPalette,
+            vec![Binding::new(Key::P, Modifiers::COMMAND)],
+        );
+        bindings.insert(
+            Command::Settings,
+            vec![Binding::new(Key::Comma, Modifiers::COMMAND)],
+        );
+        bindings.insert(
+            Command::Export,
+            vec![Binding::new(Key::E, Modifiers::COMMAND)],
+        );
+        bindings.insert(
+            Command::NewExploration,
+            vec![Binding::new(Key::N, Modifiers::COMMAND)],
+        );
+        bindings.insert(
+            Command::NewTab,
+            vec![Binding::new(Key::T, Modifiers::COMMAND)],
+        );
+        bindings.insert(
+            Command::CloseTab,
+            vec![Binding::new(Key::W, Modifiers::COMMAND)],
+        );
+        bindings.insert(
+            Command::OpenInNewTab,
+            vec![Binding::new(Key::Enter, Modifiers::SHIFT)],
+        );
+        bindings.insert(
+            Command::SaveSnapshot,
+            vec![Binding::new(Key::S, Modifiers::COMMAND)],
+        );
+        bindings.insert(
+            Command::Snapshots,
+            vec![Binding::new(Key::S, Modifiers::COMMAND | Modifiers::SHIFT)],
+        );
+        bindings.insert(
+            Command::RevisionTimeline,
+            vec![Binding::new(Key::R, Modifiers::COMMAND)],
+        );
+
+        Self { bindings }
+    }
+}
+
+impl Keymap {
+    pub fn bindings(&self, cmd: Command) -> &[Binding] {
+        self.bindings
+            .get(&cmd)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Replaces all the bindings of the command with the provided one.
+    ///
+    /// If the binding was used by another command it is removed from there.
+    pub fn rebind(&mut self, cmd: Command, binding: Binding) {
+        self.bindings
+            .values_mut()
+            .for_each(|bindings| bindings.retain(|b| *b != binding));
+        self.bindings.insert(cmd, vec![binding]);
+    }
+
+    /// Unbinds the command. It stays in the map so the default is not restored on load.
+    pub fn clear(&mut self, cmd: Command) {
+        self.bindings.insert(cmd, vec![]);
+    }
+
+    /// Returns all the commands whose bindings were pressed this frame.
+    pub fn pressed(&self, i: &InputState) -> Vec<Command> {
+        self.bindings
+            .iter()
+            .filter(|(_, bindings)| bindings.iter().any(|b| b.pressed(i)))
+            .map(|(cmd, _)| *cmd)
+            .collect()
+    }
+
+    /// Formats bindings of the command for display, e.g. `Down, J`.
+    pub fn describe(&self, cmd: Command) -> String {
+        self.bindings(cmd)
+            .iter()
+            .map(|b| b.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Returns the first key press of this frame as a binding. Used to capture a new binding.
+pub fn captured_binding(i: &InputState) -> Option<Binding> {
+    i.events.iter().find_map(|e| match e {
+        Event::Key {
+            key,
+            pressed: true,
+            modifiers,
+            ..
+        } => Some(Binding::new(
+            *key,
+            Modifiers {
+                command: modifiers.command || modifiers.ctrl,
+                ctrl: false,
+                mac_cmd: false,
+                ..*modifiers
+            },
+        )),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_bindings() {
+        assert_eq!(
+            Binding::try_from("Ctrl+Shift+S".to_string()),
+            Ok(Binding::new(Key::S, Modifiers::COMMAND | Modifiers::SHIFT))
+        );
+        assert_eq!(
+            Binding::try_from(" alt + Enter".to_string()),
+            Ok(Binding::new(Key::Enter, Modifiers::ALT))
+        );
+        assert_eq!(
+            Binding::try_from("Down".to_string()),
+            Ok(Binding::key(Key::ArrowDown))
+        );
+        assert!(Binding::try_from("Hyper+K".to_string()).is_err());
+        assert!(Binding::try_from("Ctrl+Nope".to_string()).is_err());
+
+        let binding = Binding::new(Key::P, Modifiers::COMMAND);
+        assert_eq!(Binding::try_from(binding.to_string()), Ok(binding));
+    }
+
+    #[test]
+    fn partial_keymap_keeps_defaults() {
+        let keymap: Keymap = toml::from_str(
+            r#"
+            expand = ["E"]
+            next_root = ["L"]
+            undo = []
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(keymap.bindings(Command::Expand), &[Binding::key(Key::E)]);
+        assert_eq!(keymap.bindings(Command::NextRoot), &[Binding::key(Key::L)]);
+        assert!(keymap.bindings(Command::Undo).is_empty());
+        // L was the default of the next child, the config moved it to the next root
+        assert!(keymap.bindings(Command::NextChild).is_empty());
+        assert_eq!(keymap.bindings(Command::PrevChild), &[Binding::key(Key::H)]);
+        assert_eq!(
+            keymap.bindings(Command::RevisionTimeline),
+            &[Binding::new(Key::R, Modifiers::COMMAND)]
+        );
+    }
+}
@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+
+use egui::Pos2;
+use fdg_sim::glam::Vec3;
+use fdg_sim::{ForceGraph, Simulation, SimulationParameters};
+use petgraph::stable_graph::NodeIndex;
+
+use crate::graph::Graph;
+
+const SIMULATION_DT: f32 = 0.035;
+const EDGE_WEIGHT: f32 = 0.1;
+const COOL_OFF: f32 = 0.5;
+const SCALE: f32 = 50.;
+
+/// Force directed layout of the graph.
+///
+/// Simulation nodes are found through the mapping from the graph nodes, not by the same index:
+/// both graphs reuse vacant indices of removed nodes on their own, so indices diverge as soon as
+/// nodes are removed from one of them in another order.
+pub struct Layout {
+    sim: Simulation<(), f32>,
+    /// Simulation node of every graph node.
+    sim_by_node: HashMap<NodeIndex, NodeIndex>,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        let mut params = SimulationParameters::default();
+        let force = fdg_sim::force::fruchterman_reingold_weighted(SCALE, COOL_OFF);
+        params.set_force(force);
+
+        Self {
+            sim: Simulation::from_graph(ForceGraph::default(), params),
+            sim_by_node: Default::default(),
+        }
+    }
+}
+
+impl Layout {
+    /// Adds simulation node for the graph node at the location.
+    pub fn add_node(&mut self, idx: NodeIndex, loc: Pos2) {
+        let mut sim_node = fdg_sim::Node::new(idx.index().to_string().as_str(), ());
+        sim_node.location = Vec3::new(loc.x, loc.y, 0.);
+        let sim_idx = self.sim.get_graph_mut().add_node(sim_node);
+        self.sim_by_node.insert(idx, sim_idx);
+    }
+
+    /// Removes simulation node of the graph node together with its edges.
+    pub fn remove_node(&mut self, idx: NodeIndex) {
+        if let Some(sim_idx) = self.sim_by_node.remove(&idx) {
+            self.sim.get_graph_mut().remove_node(sim_idx);
+        }
+    }
+
+    /// Adds edge between the nodes or, if they are already connected, makes it as strong as the
+    /// number of links between them.
+    pub fn set_edge(&mut self, start: NodeIndex, end: NodeIndex, count: usize) {
+        let (start, end) = match (self.sim_by_node.get(&start), self.sim_by_node.get(&end)) {
+            (Some(start), Some(end)) => (*start, *end),
+            _ => return,
+        };
+
+        let weight = EDGE_WEIGHT * count as f32;
+        let sim_g = self.sim.get_graph_mut();
+        match sim_g.find_edge(start, end) {
+            Some(sim_idx) => *sim_g.edge_weight_mut(sim_idx).unwrap() = weight,
+            None => {
+                sim_g.add_edge(start, end, weight);
+            }
+        }
+    }
+
+    /// Removes edge between the nodes if there is one.
+    pub fn remove_edge(&mut self, start: NodeIndex, end: NodeIndex) {
+        let (start, end) = match (self.sim_by_node.get(&start), self.sim_by_node.get(&end)) {
+            (Some(start), Some(end)) => (*start, *end),
+            _ => return,
+        };
+
+        let sim_g = self.sim.get_graph_mut();
+        if let Some(sim_idx) = sim_g.find_edge(start, end) {
+            sim_g.remove_edge(sim_idx);
+        }
+    }
+
+    /// Location of the graph node in the simulation.
+    #[cfg(test)]
+    pub fn location(&self, idx: NodeIndex) -> Option<Pos2> {
+        let sim_idx = self.sim_by_node.get(&idx)?;
+        let loc = self.sim.get_graph().node_weight(*sim_idx)?.location;
+        Some(Pos2::new(loc.x, loc.y))
+    }
+
+    #[cfg(test)]
+    pub fn node_count(&self) -> usize {
+        self.sim_by_node.len()
+    }
+
+    /// Runs one step of the simulation.
+    pub fn update(&mut self) {
+        // the following manipulations is a hack to avoid having looped edges in the simulation
+        // because they cause the simulation to blow up;
+        // this is the issue of the fdg_sim engine we use for the simulation
+        // https://github.com/grantshandy/fdg/issues/10
+        // * remove loop edges
+        // * update simulation
+        // * restore loop edges
+
+        // remove looped edges
+        let looped_nodes = {
+            let graph = self.sim.get_graph_mut();
+            let mut looped_nodes = vec![];
+            let mut looped_edges = vec![];
+            graph.edge_indices().for_each(|idx| {
+                let edge = graph.edge_endpoints(idx).unwrap();
+                let looped = edge.0 == edge.1;
+                if looped {
+                    looped_nodes.push((edge.0, *graph.edge_weight(idx).unwrap()));
+                    looped_edges.push(idx);
+                }
+            });
+
+            for idx in looped_edges {
+                graph.remove_edge(idx);
+            }
+
+            self.sim.update(SIMULATION_DT);
+
+            looped_nodes
+        };
+
+        // restore looped edges
+        let graph = self.sim.get_graph_mut();
+        for (idx, weight) in looped_nodes.iter() {
+            graph.add_edge(*idx, *idx, *weight);
+        }
+    }
+
+    /// Syncs the graph with the simulation.
+    ///
+    /// Changes location of nodes in `g` according to the locations in the simulation. If node
+    /// from `g` is dragged its location is prioritized over the location of the corresponding
+    /// simulation node and this location is set to the simulation node. Nodes without a
+    /// simulation node keep their location.
+    pub fn sync(&mut self, g: &mut Graph) {
+        let g_indices = g.g.node_indices().collect::<Vec<_>>();
+        g_indices.iter().for_each(|g_n_idx| {
+            let sim_n = match self
+                .sim_by_node
+                .get(g_n_idx)
+                .and_then(|sim_idx| self.sim.get_graph_mut().node_weight_mut(*sim_idx))
+            {
+                Some(sim_n) => sim_n,
+                None => return,
+            };
+            let g_n = g.g.node_weight_mut(*g_n_idx).unwrap();
+
+            if g_n.dragged() {
+                let loc = g_n.location();
+                sim_n.location = Vec3::new(loc.x, loc.y, 0.);
+                return;
+            }
+
+            let loc = sim_n.location;
+            g_n.set_location(Pos2::new(loc.x, loc.y));
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use petgraph::stable_graph::StableGraph;
+
+    use super::*;
+    use crate::{node::Node, testing::url};
+
+    fn add_graph_node(g: &mut Graph, title: &str, loc: Pos2) -> NodeIndex {
+        let n = Node::new(url(title));
+        let label = n.label();
+        g.add_node_with_label_and_location(n, label, loc)
+    }
+
+    #[test]
+    fn mapping_survives_removals() {
+        let mut layout = Layout::default();
+        (0..3).for_each(|i| {
+            layout.add_node(NodeIndex::new(i), Pos2::new(i as f32, 0.));
+        });
+
+        layout.remove_node(NodeIndex::new(0));
+        // the simulation reuses the vacant index for a graph node with another index
+        layout.add_node(NodeIndex::new(7), Pos2::new(7., 0.));
+
+        assert_eq!(layout.node_count(), 3);
+        assert_eq!(layout.location(NodeIndex::new(0)), None);
+        assert_eq!(layout.location(NodeIndex::new(1)), Some(Pos2::new(1., 0.)));
+        assert_eq!(layout.location(NodeIndex::new(2)), Some(Pos2::new(2., 0.)));
+        assert_eq!(layout.location(NodeIndex::new(7)), Some(Pos2::new(7., 0.)));
+    }
+
+    #[test]
+    fn edges_connect_mapped_nodes() {
+        let mut layout = Layout::default();
+        (0..3).for_each(|i| layout.add_node(NodeIndex::new(i), Pos2::ZERO));
+        layout.remove_node(NodeIndex::new(0));
+        layout.add_node(NodeIndex::new(5), Pos2::ZERO);
+
+        layout.set_edge(NodeIndex::new(5), NodeIndex::new(2), 1);
+        layout.set_edge(NodeIndex::new(5), NodeIndex::new(2), 3);
+        // edges to unknown nodes are ignored
+        layout.set_edge(NodeIndex::new(0), NodeIndex::new(2), 1);
+
+        let sim_g = layout.sim.get_graph();
+        assert_eq!(sim_g.edge_count(), 1);
+        let sim_idx = sim_g
+            .find_edge(
+                layout.sim_by_node[&NodeIndex::new(5)],
+                layout.sim_by_node[&NodeIndex::new(2)],
+            )
+            .unwrap();
+        assert_eq!(sim_g.edge_weight(sim_idx), Some(&(EDGE_WEIGHT * 3.)));
+    }
+
+    #[test]
+    fn sync_moves_nodes_after_removals() {
+        let mut g = Graph::new(StableGraph::new());
+        let mut layout = Layout::default();
+
+        let a = add_graph_node(&mut g, "A", Pos2::ZERO);
+        let b = add_graph_node(&mut g, "B", Pos2::ZERO);
+        layout.add_node(b, Pos2::new(1., 1.));
+        layout.add_node(a, Pos2::new(2., 2.));
+
+        g.g.remove_node(a);
+        layout.remove_node(a);
+        let c = add_graph_node(&mut g, "C", Pos2::ZERO);
+        layout.add_node(c, Pos2::new(3., 3.));
+
+        layout.sync(&mut g);
+
+        assert_eq!(g.g.node_weight(b).unwrap().location(), Pos2::new(1., 1.));
+        assert_eq!(g.g.node_weight(c).unwrap().location(), Pos2::new(3., 3.));
+    }
+}
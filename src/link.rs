@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+
+use crate::url::Url;
+
+/// Link found in an article.
+#[derive(Clone)]
+pub struct Link {
+    pub url: Url,
+    pub context: LinkContext,
+    /// Revision of the article the link was found in, `None` for the live article.
+    pub revision: Option<u64>,
+}
+
+/// Where and how the link appears in the article.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct LinkContext {
+    /// Text of the `a` element.
+    pub anchor: String,
+    /// Heading of the section the link appears under. `None` for the lead section.
+    pub section: Option<String>,
+    /// Sentence containing the link. Empty if the link is not inside a paragraph or a list.
+    pub sentence: String,
+}
+
+impl LinkContext {
+    /// Checks if anchor, section or sentence contains the lowercase query.
+    pub fn matches(&self, query: &str) -> bool {
+        self.anchor.to_lowercase().contains(query)
+            || self.sentence.to_lowercase().contains(query)
+            || self
+                .section
+                .as_ref()
+                .map_or(false, |s| s.to_lowercase().contains(query))
+    }
+}
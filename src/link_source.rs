@@ -0,0 +1,326 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    hash::Hash,
+    io,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Semaphore;
+
+use crate::{
+    entities::Entity,
+    link::Link,
+    url::Url,
+    url_retriever::{Mode, WebSource},
+    zim::{Zim, ZimSource},
+};
+
+/// Backend the articles, their links and summaries are retrieved from.
+///
+/// Everything the app knows about articles goes through this trait, so a backend can be
+/// replaced with a fake one. Only links and summaries are required, other retrievals fail with
+/// [`Error::Unsupported`] unless the backend provides them.
+#[async_trait]
+pub trait LinkSource: Send + Sync {
+    /// Gets links of the article in the order they appear.
+    async fn links(&self, url: &Url) -> Result<Vec<Link>, Error>;
+
+    /// Gets links of the revision of the article which was current at the time, together with
+    /// the id of that revision.
+    async fn links_as_of(&self, _: &Url, _: SystemTime) -> Result<(u64, Vec<Link>), Error> {
+        Err(Error::Unsupported("past revisions"))
+    }
+
+    /// Gets ids and times of the latest revisions of the article, the newest first.
+    async fn revisions(&self, _: &Url, _: usize) -> Result<Vec<(u64, SystemTime)>, Error> {
+        Err(Error::Unsupported("past revisions"))
+    }
+
+    /// Gets links of the revision of the article in the order they appear.
+    async fn revision_links(&self, _: &Url, _: u64) -> Result<Vec<Link>, Error> {
+        Err(Error::Unsupported("past revisions"))
+    }
+
+    /// Gets articles which link to the article.
+    async fn backlinks(&self, _: &Url) -> Result<Vec<Url>, Error> {
+        Err(Error::Unsupported("backlinks"))
+    }
+
+    /// Gets visible categories of the article.
+    async fn categories(&self, _: &Url) -> Result<Vec<Url>, Error> {
+        Err(Error::Unsupported("categories"))
+    }
+
+    /// Gets articles and subcategories of the category.
+    async fn category_members(&self, _: &Url) -> Result<Vec<Url>, Error> {
+        Err(Error::Unsupported("categories"))
+    }
+
+    /// Gets the editions of the article in other languages.
+    async fn langlinks(&self, _: &Url) -> Result<Vec<Url>, Error> {
+        Err(Error::Unsupported("interlanguage links"))
+    }
+
+    /// Gets the first paragraph of the article as plain text.
+    async fn summary(&self, url: &Url) -> Result<String, Error>;
+
+    /// Resolves articles of the same wiki to their wikidata entities. Articles without an
+    /// entity are left out.
+    async fn entities(&self, _: &[Url]) -> Result<Vec<(Url, Entity)>, Error> {
+        Err(Error::Unsupported("entities"))
+    }
+
+    /// Resolves articles of the same wiki to the ids of their wikidata items only, which is
+    /// cheaper than getting whole entities.
+    async fn qids(&self, urls: &[Url]) -> Result<Vec<(Url, String)>, Error> {
+        Ok(self
+            .entities(urls)
+            .await?
+            .into_iter()
+            .map(|(url, entity)| (url, entity.qid))
+            .collect())
+    }
+
+    /// Picks disambiguation pages out of the articles of the same wiki.
+    async fn disambiguations(&self, _: &[Url]) -> Result<Vec<Url>, Error> {
+        Err(Error::Unsupported("disambiguation pages"))
+    }
+
+    /// Gets encoded thumbnail image of the media file.
+    async fn thumbnail(&self, _: &Url) -> Result<Vec<u8>, Error> {
+        Err(Error::Unsupported("thumbnails"))
+    }
+}
+
+/// Backend selected in the config.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Backend {
+    #[default]
+    Web,
+    Zim {
+        path: PathBuf,
+    },
+    /// Web which stores every fetched page to the directory.
+    Record {
+        dir: PathBuf,
+    },
+    /// Pages recorded before, for reproducible demos and tests.
+    Replay {
+        dir: PathBuf,
+    },
+}
+
+impl Backend {
+    pub fn open(&self) -> Result<Arc<dyn LinkSource>, Error> {
+        Ok(match self {
+            Backend::Web => Arc::new(WebSource::default()),
+            Backend::Record { dir } => Arc::new(WebSource::new(Mode::Record(dir.clone()))),
+            Backend::Replay { dir } => Arc::new(WebSource::new(Mode::Replay(dir.clone()))),
+            Backend::Zim { path } => Arc::new(ZimSource::new(Zim::open(path)?)),
+        })
+    }
+}
+
+/// Max number of requests to the source running at once, for all tabs together.
+const MAX_REQUESTS: usize = 8;
+
+/// Source shared by all the tabs. Requests wait for their turn so opening several tabs does not
+/// flood the backend, and links, lists and summaries retrieved in one tab are reused in the
+/// others. Failed requests are not cached.
+pub struct SharedSource {
+    source: Arc<dyn LinkSource>,
+    permits: Semaphore,
+    links: Mutex<HashMap<Url, Vec<Link>>>,
+    /// Links of past revisions by the article and the time in seconds since the unix epoch.
+    links_as_of: Mutex<HashMap<(Url, u64), RevisionLinks>>,
+    revision_links: Mutex<HashMap<(Url, u64), Vec<Link>>>,
+    lists: Mutex<HashMap<(List, Url), Vec<Url>>>,
+    summaries: Mutex<HashMap<Url, String>>,
+}
+
+/// Id of the revision current at some time and its links.
+type RevisionLinks = (u64, Vec<Link>);
+
+/// Kinds of the url lists kept in the cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum List {
+    Backlinks,
+    Categories,
+    Members,
+    Langlinks,
+}
+
+impl SharedSource {
+    pub fn new(source: Arc<dyn LinkSource>) -> Self {
+        Self {
+            source,
+            permits: Semaphore::new(MAX_REQUESTS),
+            links: Default::default(),
+            links_as_of: Default::default(),
+            revision_links: Default::default(),
+            lists: Default::default(),
+            summaries: Default::default(),
+        }
+    }
+
+    /// Source without the cache and the limit, for retrievals which must be fresh.
+    pub fn uncached(&self) -> Arc<dyn LinkSource> {
+        self.source.clone()
+    }
+
+    /// Takes the value from the cache or retrieves it once a request is allowed to run.
+    async fn cached<K, V, F>(
+        &self,
+        cache: &Mutex<HashMap<K, V>>,
+        key: K,
+        retrieve: F,
+    ) -> Result<V, Error>
+    where
+        K: Eq + Hash,
+        V: Clone,
+        F: std::future::Future<Output = Result<V, Error>>,
+    {
+        if let Some(val) = cache.lock().unwrap().get(&key) {
+            return Ok(val.clone());
+        }
+
+        let val = self.scheduled(retrieve).await?;
+        cache.lock().unwrap().insert(key, val.clone());
+        Ok(val)
+    }
+
+    async fn scheduled<V>(
+        &self,
+        retrieve: impl std::future::Future<Output = Result<V, Error>>,
+    ) -> Result<V, Error> {
+        let _permit = self.permits.acquire().await.unwrap();
+        retrieve.await
+    }
+}
+
+#[async_trait]
+impl LinkSource for SharedSource {
+    async fn links(&self, url: &Url) -> Result<Vec<Link>, Error> {
+        self.cached(&self.links, url.clone(), self.source.links(url))
+            .await
+    }
+
+    async fn links_as_of(&self, url: &Url, at: SystemTime) -> Result<(u64, Vec<Link>), Error> {
+        let secs = at.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        self.cached(
+            &self.links_as_of,
+            (url.clone(), secs),
+            self.source.links_as_of(url, at),
+        )
+        .await
+    }
+
+    /// The history grows with every edit, so only the revisions themselves are cached.
+    async fn revisions(&self, url: &Url, limit: usize) -> Result<Vec<(u64, SystemTime)>, Error> {
+        self.scheduled(self.source.revisions(url, limit)).await
+    }
+
+    async fn revision_links(&self, url: &Url, revision: u64) -> Result<Vec<Link>, Error> {
+        self.cached(
+            &self.revision_links,
+            (url.clone(), revision),
+            self.source.revision_links(url, revision),
+        )
+        .await
+    }
+
+    async fn backlinks(&self, url: &Url) -> Result<Vec<Url>, Error> {
+        let key = (List::Backlinks, url.clone());
+        self.cached(&self.lists, key, self.source.backlinks(url))
+            .await
+    }
+
+    async fn categories(&self, url: &Url) -> Result<Vec<Url>, Error> {
+        let key = (List::Categories, url.clone());
+        self.cached(&self.lists, key, self.source.categories(url))
+            .await
+    }
+
+    async fn category_members(&self, url: &Url) -> Result<Vec<Url>, Error> {
+        let key = (List::Members, url.clone());
+        self.cached(&self.lists, key, self.source.category_members(url))
+            .await
+    }
+
+    async fn langlinks(&self, url: &Url) -> Result<Vec<Url>, Error> {
+        let key = (List::Langlinks, url.clone());
+        self.cached(&self.lists, key, self.source.langlinks(url))
+            .await
+    }
+
+    async fn summary(&self, url: &Url) -> Result<String, Error> {
+        self.cached(&self.summaries, url.clone(), self.source.summary(url))
+            .await
+    }
+
+    async fn entities(&self, urls: &[Url]) -> Result<Vec<(Url, Entity)>, Error> {
+        self.scheduled(self.source.entities(urls)).await
+    }
+
+    async fn qids(&self, urls: &[Url]) -> Result<Vec<(Url, String)>, Error> {
+        self.scheduled(self.source.qids(urls)).await
+    }
+
+    async fn disambiguations(&self, urls: &[Url]) -> Result<Vec<Url>, Error> {
+        self.scheduled(self.source.disambiguations(urls)).await
+    }
+
+    async fn thumbnail(&self, url: &Url) -> Result<Vec<u8>, Error> {
+        self.scheduled(self.source.thumbnail(url)).await
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Http(reqwest::Error),
+    Io(io::Error),
+    /// The source does not have the article.
+    NotFound(String),
+    /// The source returned something it was not expected to.
+    Parse(String),
+    /// The source can not provide this kind of data.
+    Unsupported(&'static str),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Http(err) => write!(f, "http error: {}", err),
+            Error::Io(err) => write!(f, "io error: {}", err),
+            Error::NotFound(url) => write!(f, "article not found: {}", url),
+            Error::Parse(msg) => write!(f, "unexpected response: {}", msg),
+            Error::Unsupported(what) => write!(f, "{} are not supported by the source", what),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<reqwest::Error> for Error {
+    fn from(err: reqwest::Error) -> Self {
+        Error::Http(err)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Parse(err.to_string())
+    }
+}
@@ -1,26 +1,77 @@
+use std::{env, path::PathBuf, process, sync::Arc};
+
 use eframe::{run_native, CreationContext, Frame, NativeOptions};
 use egui::Context;
+use log::error;
+
+use dump::{DumpImport, ImportedGraph};
+use link_source::{Backend, LinkSource};
 
 const APP_NAME: &str = "Wiki Links";
+const USAGE: &str = "usage: wikilinks [--zim <file> | --record <dir> | --replay <dir>] \
+    [--dump <dir> --seed <title>... [--depth <n>]]
+       wikilinks --dump <dir> --seed <title>... [--depth <n>] \
+    --export <file.png|file.svg> [--width <px>] [--no-legend]
+
+the export is made from the dump only, without a window and without any source";
+const DEFAULT_DEPTH: usize = 1;
+/// Simulation steps run before the headless export.
+const LAYOUT_STEPS: usize = 500;
 
 mod app;
+mod batcher;
+mod command;
+mod communities;
+mod comparison;
+mod config;
 mod cursor;
+mod diff;
+mod disambiguation;
+mod dump;
+mod edge;
+mod entities;
+mod exploration;
+mod export;
+mod fixture;
+mod graph;
+mod history;
+mod keymap;
+mod layout;
+mod link;
+mod link_source;
+mod metrics;
 mod node;
+mod revision;
+mod session;
 mod state;
+#[cfg(test)]
+mod testing;
+mod thumbnail;
 mod url;
 mod url_retriever;
 mod views;
 mod utils;
+mod zim;
 
 pub struct App {
     app: app::App,
 }
 
 impl App {
-    fn new(_: &CreationContext<'_>) -> Self {
-        Self {
-            app: app::App::default(),
+    fn new(
+        _: &CreationContext<'_>,
+        source: Option<Arc<dyn LinkSource>>,
+        imported: Option<ImportedGraph>,
+    ) -> Self {
+        let mut app = app::App::default();
+        if let Some(source) = source {
+            app.set_source(source);
+        }
+        if let Some(imported) = imported {
+            app.load_import(imported);
         }
+
+        Self { app }
     }
 }
 
@@ -34,11 +85,99 @@ impl eframe::App for App {
 async fn main() {
     env_logger::init();
 
+    let args = parse_args();
+    let source = args.source.map(|backend| match backend.open() {
+        Ok(source) => source,
+        Err(err) => {
+            error!("failed to open source: {}", err);
+            process::exit(1);
+        }
+    });
+    let imported = args.import.map(|import| match import.import() {
+        Ok(imported) => imported,
+        Err(err) => {
+            error!("failed to import dump: {}", err);
+            process::exit(1);
+        }
+    });
+
+    if let Some((path, options)) = args.export {
+        let mut app = app::App::default();
+        app.load_import(imported.unwrap());
+        app.settle_layout(LAYOUT_STEPS);
+        if let Err(err) = app.export_image(&path, &options) {
+            error!("failed to export graph: {}", err);
+            process::exit(1);
+        }
+        return;
+    }
+
     let native_options = NativeOptions::default();
     run_native(
         APP_NAME,
         native_options,
-        Box::new(|cc| Box::new(App::new(cc))),
+        Box::new(|cc| Box::new(App::new(cc, source, imported))),
     )
     .unwrap();
 }
+
+struct Args {
+    /// Overrides the configured source.
+    source: Option<Backend>,
+    /// Dump import to start from instead of the url input.
+    import: Option<DumpImport>,
+    /// Image the imported graph is exported to without opening the window.
+    export: Option<(PathBuf, export::Options)>,
+}
+
+fn parse_args() -> Args {
+    let mut source = None;
+    let mut dir = None;
+    let mut seeds = vec![];
+    let mut depth = DEFAULT_DEPTH;
+    let mut export = None;
+    let mut export_options = export::Options::default();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--no-legend" {
+            export_options.legend = false;
+            continue;
+        }
+
+        let val = args.next().unwrap_or_else(|| exit_with_usage());
+        match arg.as_str() {
+            "--zim" => source = Some(Backend::Zim { path: val.into() }),
+            "--record" => source = Some(Backend::Record { dir: val.into() }),
+            "--replay" => source = Some(Backend::Replay { dir: val.into() }),
+            "--dump" => dir = Some(PathBuf::from(val)),
+            "--seed" => seeds.push(val),
+            "--depth" => depth = val.parse().unwrap_or_else(|_| exit_with_usage()),
+            "--export" => export = Some(PathBuf::from(val)),
+            "--width" => export_options.width = val.parse().unwrap_or_else(|_| exit_with_usage()),
+            _ => exit_with_usage(),
+        }
+    }
+
+    let import = match (dir, seeds.is_empty()) {
+        (None, true) => None,
+        (Some(dir), false) => Some(DumpImport { dir, seeds, depth }),
+        _ => exit_with_usage(),
+    };
+
+    if export.is_some() && (import.is_none() || source.is_some()) {
+        exit_with_usage();
+    }
+    let export = export.map(|path| (path, export_options));
+
+    Args {
+        source,
+        import,
+        export,
+    }
+}
+
+fn exit_with_usage() -> ! {
+    eprintln!("{}", USAGE);
+    process::exit(2);
+}
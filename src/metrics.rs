@@ -0,0 +1,421 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use crossbeam::channel::{unbounded, Receiver, TryRecvError};
+use petgraph::{stable_graph::NodeIndex, Direction::Incoming, Direction::Outgoing};
+
+use crate::graph::Graph;
+
+const DAMPING: f32 = 0.85;
+const PAGE_RANK_EPSILON: f32 = 1e-6;
+const PAGE_RANK_MAX_ITERATIONS: usize = 100;
+
+/// Betweenness and closeness are O(V*E) so they are computed on a blocking thread and, while
+/// links are arriving, not more often than this.
+const CENTRALITY_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Metric {
+    #[default]
+    PageRank,
+    InDegree,
+    OutDegree,
+    Betweenness,
+    Closeness,
+}
+
+impl Metric {
+    pub fn all() -> &'static [Metric] {
+        &[
+            Metric::PageRank,
+            Metric::InDegree,
+            Metric::OutDegree,
+            Metric::Betweenness,
+            Metric::Closeness,
+        ]
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Metric::PageRank => "page rank",
+            Metric::InDegree => "in degree",
+            Metric::OutDegree => "out degree",
+            Metric::Betweenness => "betweenness",
+            Metric::Closeness => "closeness",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NodeMetrics {
+    pub page_rank: f32,
+    pub in_degree: usize,
+    pub out_degree: usize,
+    /// Normalized betweenness centrality.
+    pub betweenness: f32,
+    /// Harmonic closeness centrality over outgoing links, so it is defined for disconnected graphs.
+    pub closeness: f32,
+}
+
+impl NodeMetrics {
+    pub fn get(&self, metric: Metric) -> f32 {
+        match metric {
+            Metric::PageRank => self.page_rank,
+            Metric::InDegree => self.in_degree as f32,
+            Metric::OutDegree => self.out_degree as f32,
+            Metric::Betweenness => self.betweenness,
+            Metric::Closeness => self.closeness,
+        }
+    }
+
+    pub fn format(&self, metric: Metric) -> String {
+        match metric {
+            Metric::InDegree | Metric::OutDegree => format!("{}", self.get(metric)),
+            Metric::PageRank | Metric::Betweenness | Metric::Closeness => {
+                format!("{:.4}", self.get(metric))
+            }
+        }
+    }
+}
+
+/// Metrics of the nodes of the links graph.
+///
+/// Metrics are updated incrementally: degrees are recounted on every change, page rank continues
+/// iterating from the previous ranks and centralities are recomputed periodically in the
+/// background.
+#[derive(Default)]
+pub struct Metrics {
+    by_node: HashMap<NodeIndex, NodeMetrics>,
+
+    /// Graph changed since the last degrees and page rank update.
+    dirty: bool,
+    /// Graph changed since the last betweenness and closeness computation.
+    centrality_dirty: bool,
+    centrality_computed_at: Option<Instant>,
+    /// Betweenness and closeness of every node being computed.
+    centrality_task: Option<Receiver<HashMap<NodeIndex, (f32, f32)>>>,
+}
+
+impl Metrics {
+    /// Marks metrics as outdated. Should be called on every graph change.
+    pub fn invalidate(&mut self) {
+        self.dirty = true;
+        self.centrality_dirty = true;
+    }
+
+    pub fn get(&self, idx: NodeIndex) -> Option<&NodeMetrics> {
+        self.by_node.get(&idx)
+    }
+
+    pub fn max(&self, metric: Metric) -> f32 {
+        self.by_node
+            .values()
+            .map(|m| m.get(metric))
+            .fold(0., f32::max)
+    }
+
+    /// Returns nodes accepted by the filter sorted by the metric in descending order.
+    pub fn top(
+        &self,
+        metric: Metric,
+        n: usize,
+        filter: impl Fn(&NodeIndex) -> bool,
+    ) -> Vec<(NodeIndex, NodeMetrics)> {
+        let mut res = self
+            .by_node
+            .iter()
+            .filter(|(idx, _)| filter(idx))
+            .map(|(idx, m)| (*idx, *m))
+            .collect::<Vec<_>>();
+        res.sort_by(|(_, l), (_, r)| r.get(metric).total_cmp(&l.get(metric)));
+        res.truncate(n);
+        res
+    }
+
+    /// Updates outdated metrics and starts computing the centralities if they are due. Returns
+    /// true if any value changed.
+    pub fn update(&mut self, g: &Graph) -> bool {
+        let received = self.receive_centralities();
+        let centrality_due = self.centrality_dirty
+            && self.centrality_task.is_none()
+            && self
+                .centrality_computed_at
+                .map_or(true, |at| at.elapsed() >= CENTRALITY_INTERVAL);
+
+        if !self.dirty && !centrality_due {
+            return received;
+        }
+
+        let nodes = g.g.node_indices().collect::<Vec<_>>();
+        let adj = adjacency(g, &nodes);
+
+        if self.dirty {
+            self.by_node.retain(|idx, _| g.g.contains_node(*idx));
+            self.update_degrees(g, &nodes);
+            self.update_page_rank(&nodes, &adj);
+            self.dirty = false;
+        }
+
+        if centrality_due {
+            let (sender, receiver) = unbounded();
+            tokio::task::spawn_blocking(move || {
+                // the tab can be closed in the meantime
+                let _ = sender.send(centralities(&nodes, &adj));
+            });
+            self.centrality_task = Some(receiver);
+            self.centrality_dirty = false;
+            self.centrality_computed_at = Some(Instant::now());
+        }
+
+        true
+    }
+
+    /// Takes the computed centralities of the nodes which are still in the graph.
+    fn receive_centralities(&mut self) -> bool {
+        let computed = match &self.centrality_task {
+            Some(receiver) => match receiver.try_recv() {
+                Ok(computed) => computed,
+                Err(TryRecvError::Empty) => return false,
+                Err(TryRecvError::Disconnected) => {
+                    self.centrality_task = None;
+                    return false;
+                }
+            },
+            None => return false,
+        };
+
+        self.centrality_task = None;
+        computed
+            .into_iter()
+            .for_each(|(idx, (betweenness, closeness))| {
+                if let Some(m) = self.by_node.get_mut(&idx) {
+                    m.betweenness = betweenness;
+                    m.closeness = closeness;
+                }
+            });
+        true
+    }
+
+    fn update_degrees(&mut self, g: &Graph, nodes: &[NodeIndex]) {
+        nodes.iter().for_each(|idx| {
+            let m = self.by_node.entry(*idx).or_default();
+            m.in_degree = g.g.edges_directed(*idx, Incoming).count();
+            m.out_degree = g.g.edges_directed(*idx, Outgoing).count();
+        });
+    }
+
+    fn update_page_rank(&mut self, nodes: &[NodeIndex], adj: &HashMap<NodeIndex, Vec<NodeIndex>>) {
+        let ranks = page_rank(nodes, adj, |idx| self.by_node[idx].page_rank);
+        ranks.into_iter().for_each(|(idx, rank)| {
+            self.by_node.get_mut(&idx).unwrap().page_rank = rank;
+        });
+    }
+}
+
+/// Power iteration which starts from the previous ranks, so after adding a few links it
+/// converges in a couple of iterations. Nodes without a previous rank start from the uniform one.
+fn page_rank(
+    nodes: &[NodeIndex],
+    adj: &HashMap<NodeIndex, Vec<NodeIndex>>,
+    prev: impl Fn(&NodeIndex) -> f32,
+) -> HashMap<NodeIndex, f32> {
+    if nodes.is_empty() {
+        return HashMap::new();
+    }
+
+    let n = nodes.len() as f32;
+    let mut ranks = nodes
+        .iter()
+        .map(|idx| {
+            let prev = prev(idx);
+            (*idx, if prev > 0. { prev } else { 1. / n })
+        })
+        .collect::<HashMap<_, _>>();
+    let sum = ranks.values().sum::<f32>();
+    ranks.values_mut().for_each(|r| *r /= sum);
+
+    for _ in 0..PAGE_RANK_MAX_ITERATIONS {
+        let dangling = nodes
+            .iter()
+            .filter(|idx| adj[idx].is_empty())
+            .map(|idx| ranks[idx])
+            .sum::<f32>();
+        let base = (1. - DAMPING) / n + DAMPING * dangling / n;
+
+        let mut next = nodes
+            .iter()
+            .map(|idx| (*idx, base))
+            .collect::<HashMap<_, _>>();
+        nodes.iter().for_each(|idx| {
+            let out = &adj[idx];
+            if out.is_empty() {
+                return;
+            }
+
+            let share = DAMPING * ranks[idx] / out.len() as f32;
+            out.iter()
+                .for_each(|to| *next.get_mut(to).unwrap() += share);
+        });
+
+        let delta = nodes
+            .iter()
+            .map(|idx| (next[idx] - ranks[idx]).abs())
+            .sum::<f32>();
+        ranks = next;
+
+        if delta < PAGE_RANK_EPSILON {
+            break;
+        }
+    }
+
+    ranks
+}
+
+/// Normalized betweenness and closeness of every node. Brandes algorithm for betweenness,
+/// closeness is computed from the same BFS runs.
+fn centralities(
+    nodes: &[NodeIndex],
+    adj: &HashMap<NodeIndex, Vec<NodeIndex>>,
+) -> HashMap<NodeIndex, (f32, f32)> {
+    let mut betweenness = nodes
+        .iter()
+        .map(|idx| (*idx, 0.))
+        .collect::<HashMap<NodeIndex, f32>>();
+    let mut closeness = HashMap::new();
+
+    nodes.iter().for_each(|s| {
+        let mut stack = vec![];
+        let mut preds: HashMap<NodeIndex, Vec<NodeIndex>> = HashMap::new();
+        let mut sigma: HashMap<NodeIndex, f32> = HashMap::from([(*s, 1.)]);
+        let mut dist: HashMap<NodeIndex, usize> = HashMap::from([(*s, 0)]);
+        let mut queue = VecDeque::from([*s]);
+
+        while let Some(v) = queue.pop_front() {
+            stack.push(v);
+            adj[&v].iter().for_each(|w| {
+                if !dist.contains_key(w) {
+                    dist.insert(*w, dist[&v] + 1);
+                    queue.push_back(*w);
+                }
+                if dist[w] == dist[&v] + 1 {
+                    *sigma.entry(*w).or_default() += sigma[&v];
+                    preds.entry(*w).or_default().push(v);
+                }
+            });
+        }
+
+        let mut delta: HashMap<NodeIndex, f32> = HashMap::new();
+        while let Some(w) = stack.pop() {
+            let delta_w = delta.get(&w).copied().unwrap_or_default();
+            if let Some(ps) = preds.get(&w) {
+                ps.iter().for_each(|v| {
+                    *delta.entry(*v).or_default() += sigma[v] / sigma[&w] * (1. + delta_w);
+                });
+            }
+            if w != *s {
+                *betweenness.get_mut(&w).unwrap() += delta_w;
+            }
+        }
+
+        let c = match nodes.len() > 1 {
+            true => {
+                dist.values()
+                    .filter(|d| **d > 0)
+                    .map(|d| 1. / *d as f32)
+                    .sum::<f32>()
+                    / (nodes.len() - 1) as f32
+            }
+            false => 0.,
+        };
+        closeness.insert(*s, c);
+    });
+
+    let n = nodes.len() as f32;
+    let norm = match n > 2. {
+        true => (n - 1.) * (n - 2.),
+        false => 1.,
+    };
+    betweenness
+        .into_iter()
+        .map(|(idx, val)| (idx, (val / norm, closeness[&idx])))
+        .collect()
+}
+
+/// Outgoing neighbours of every node without duplicates and loops.
+fn adjacency(g: &Graph, nodes: &[NodeIndex]) -> HashMap<NodeIndex, Vec<NodeIndex>> {
+    nodes
+        .iter()
+        .map(|idx| {
+            let mut neighbors =
+                g.g.neighbors_directed(*idx, Outgoing)
+                    .filter(|n| n != idx)
+                    .collect::<Vec<_>>();
+            neighbors.sort();
+            neighbors.dedup();
+            (*idx, neighbors)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Adjacency of nodes `0..n` with the directed links.
+    fn graph(
+        n: usize,
+        links: &[(usize, usize)],
+    ) -> (Vec<NodeIndex>, HashMap<NodeIndex, Vec<NodeIndex>>) {
+        let nodes = (0..n).map(NodeIndex::new).collect::<Vec<_>>();
+        let mut adj = nodes
+            .iter()
+            .map(|idx| (*idx, vec![]))
+            .collect::<HashMap<_, _>>();
+        links.iter().for_each(|(from, to)| {
+            adj.get_mut(&NodeIndex::new(*from))
+                .unwrap()
+                .push(NodeIndex::new(*to))
+        });
+        (nodes, adj)
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < 1e-4,
+            "{} is not {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn page_rank_of_star() {
+        // leaves link the center, whose rank is spread over all nodes as it links nothing
+        let (nodes, adj) = graph(4, &[(1, 0), (2, 0), (3, 0)]);
+        let ranks = page_rank(&nodes, &adj, |_| 0.);
+
+        // r_leaf = (1 - d) / 4 + d * r_center / 4 and r_center = r_leaf + 3 * d * r_leaf
+        let r_leaf = 1. / (4. + 3. * DAMPING);
+        assert_close(ranks[&NodeIndex::new(0)], r_leaf * (1. + 3. * DAMPING));
+        (1..4).for_each(|i| assert_close(ranks[&NodeIndex::new(i)], r_leaf));
+    }
+
+    #[test]
+    fn centralities_of_path() {
+        let (nodes, adj) = graph(3, &[(0, 1), (1, 2)]);
+        let c = centralities(&nodes, &adj);
+
+        assert_eq!(c[&NodeIndex::new(0)], (0., 0.75));
+        assert_eq!(c[&NodeIndex::new(1)], (0.5, 0.5));
+        assert_eq!(c[&NodeIndex::new(2)], (0., 0.));
+    }
+
+    #[test]
+    fn centralities_of_star() {
+        let (nodes, adj) = graph(4, &[(0, 1), (0, 2), (0, 3)]);
+        let c = centralities(&nodes, &adj);
+
+        assert_eq!(c[&NodeIndex::new(0)], (0., 1.));
+        (1..4).for_each(|i| assert_eq!(c[&NodeIndex::new(i)], (0., 0.)));
+    }
+}
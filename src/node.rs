@@ -1,13 +1,49 @@
-use crate::url::Url;
+use egui::{Color32, TextureId, Vec2};
+
+use crate::{diff::Change, url::Url};
 
 #[derive(Clone)]
 pub struct Node {
     url: Url,
+    /// Multiplier of the default node radius.
+    size: f32,
+    color: Option<Color32>,
+
+    community: Option<usize>,
+    /// Node is drawn faded when it is filtered out.
+    faded: bool,
+    /// Node is not drawn when it is collapsed into another node.
+    hidden: bool,
+    /// Number of nodes collapsed into this one.
+    collapsed: usize,
+    /// Article is a disambiguation page listing articles of the same name.
+    disambiguation: bool,
+    /// Texture and size of the thumbnail drawn instead of the node.
+    thumbnail: Option<(TextureId, Vec2)>,
+    /// Label is drawn even when the graph is zoomed out.
+    labeled: bool,
+    /// Article was added or removed between the compared sessions.
+    change: Option<Change>,
+    /// Revision of the article its links were retrieved from, `None` for the live article.
+    revision: Option<u64>,
 }
 
 impl Node {
     pub fn new(url: Url) -> Self {
-        Self { url }
+        Self {
+            url,
+            size: 1.,
+            color: None,
+            community: None,
+            faded: false,
+            hidden: false,
+            collapsed: 0,
+            disambiguation: false,
+            thumbnail: None,
+            labeled: false,
+            change: None,
+            revision: None,
+        }
     }
 
     pub fn url(&self) -> &Url {
@@ -17,4 +53,92 @@ impl Node {
     pub fn label(&self) -> String {
         self.url.val_for_type()
     }
+
+    pub fn size(&self) -> f32 {
+        self.size
+    }
+
+    pub fn set_size(&mut self, size: f32) {
+        self.size = size;
+    }
+
+    pub fn color(&self) -> Option<Color32> {
+        self.color
+    }
+
+    pub fn set_color(&mut self, color: Option<Color32>) {
+        self.color = color;
+    }
+
+    pub fn community(&self) -> Option<usize> {
+        self.community
+    }
+
+    pub fn set_community(&mut self, community: Option<usize>) {
+        self.community = community;
+    }
+
+    pub fn faded(&self) -> bool {
+        self.faded
+    }
+
+    pub fn set_faded(&mut self, faded: bool) {
+        self.faded = faded;
+    }
+
+    pub fn hidden(&self) -> bool {
+        self.hidden
+    }
+
+    pub fn set_hidden(&mut self, hidden: bool) {
+        self.hidden = hidden;
+    }
+
+    pub fn collapsed(&self) -> usize {
+        self.collapsed
+    }
+
+    pub fn set_collapsed(&mut self, collapsed: usize) {
+        self.collapsed = collapsed;
+    }
+
+    pub fn disambiguation(&self) -> bool {
+        self.disambiguation
+    }
+
+    pub fn set_disambiguation(&mut self, disambiguation: bool) {
+        self.disambiguation = disambiguation;
+    }
+
+    pub fn thumbnail(&self) -> Option<(TextureId, Vec2)> {
+        self.thumbnail
+    }
+
+    pub fn set_thumbnail(&mut self, thumbnail: Option<(TextureId, Vec2)>) {
+        self.thumbnail = thumbnail;
+    }
+
+    pub fn labeled(&self) -> bool {
+        self.labeled
+    }
+
+    pub fn set_labeled(&mut self, labeled: bool) {
+        self.labeled = labeled;
+    }
+
+    pub fn change(&self) -> Option<Change> {
+        self.change
+    }
+
+    pub fn set_change(&mut self, change: Option<Change>) {
+        self.change = change;
+    }
+
+    pub fn revision(&self) -> Option<u64> {
+        self.revision
+    }
+
+    pub fn set_revision(&mut self, revision: Option<u64>) {
+        self.revision = revision;
+    }
 }
@@ -0,0 +1,265 @@
+use std::{collections::HashSet, io, sync::Arc, time::SystemTime};
+
+use log::error;
+
+use crate::{
+    link_source::{Error, LinkSource},
+    url::{self, Url},
+};
+
+/// Number of the latest revisions the timeline is built from by default.
+pub const REVISIONS_LIMIT: usize = 50;
+/// Most revisions the api lists at once.
+pub const MAX_REVISIONS: usize = 500;
+/// Seconds every revision is shown for while the timeline plays.
+const STEP_SECS: f64 = 1.;
+
+/// Parses the date articles are explored as of: a day like `2015-01-01` or a timestamp like
+/// `2015-01-01T12:00:00Z`, both in UTC.
+pub fn parse_date(val: &str) -> Option<SystemTime> {
+    let val = val.trim();
+    humantime::parse_rfc3339_weak(val)
+        .or_else(|_| humantime::parse_rfc3339_weak(&format!("{} 00:00:00", val)))
+        .ok()
+}
+
+/// Address of the revision of the article as it was shown then.
+pub fn url(url: &Url, revision: u64) -> String {
+    format!(
+        "{}://{}/w/index.php?oldid={}",
+        url.scheme(),
+        url.host(),
+        revision
+    )
+}
+
+/// Day of the time in UTC, e.g. `2015-01-01`.
+pub fn date_label(at: SystemTime) -> String {
+    humantime::format_rfc3339_seconds(at).to_string()[..10].to_string()
+}
+
+/// Articles linked by one revision of the article.
+pub struct Revision {
+    pub id: u64,
+    pub at: SystemTime,
+    pub links: HashSet<Url>,
+}
+
+/// How the shown revision links an article compared with the revision before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Linked {
+    Kept,
+    Added,
+    Removed,
+    /// Neither the shown revision nor the one before links the article.
+    Absent,
+}
+
+/// Links of the article at every revision of its recent history, played back one revision at a
+/// time.
+pub struct Timeline {
+    pub article: Url,
+    /// Revisions the oldest first.
+    pub revisions: Vec<Revision>,
+    /// Index of the shown revision.
+    pub position: usize,
+    /// Time playing started at and the revision it started from.
+    pub playing: Option<(f64, usize)>,
+}
+
+impl Timeline {
+    pub fn new(article: Url, revisions: Vec<Revision>) -> Self {
+        Self {
+            article,
+            revisions,
+            position: 0,
+            playing: None,
+        }
+    }
+
+    pub fn shown(&self) -> &Revision {
+        &self.revisions[self.position]
+    }
+
+    /// Every article linked by any of the revisions, in the order they were first linked.
+    pub fn targets(&self) -> Vec<&Url> {
+        let mut seen = HashSet::new();
+        self.revisions
+            .iter()
+            .flat_map(|r| {
+                let mut added = r
+                    .links
+                    .iter()
+                    .filter(|u| !seen.contains(u))
+                    .collect::<Vec<_>>();
+                added.sort_by(|a, b| a.val().cmp(b.val()));
+                seen.extend(added.iter().cloned());
+                added
+            })
+            .collect()
+    }
+
+    /// The first revision keeps all its links, as nothing is known of the revisions before it.
+    pub fn linked(&self, target: &Url) -> Linked {
+        let now = self.shown().links.contains(target);
+        let before = match self.position {
+            0 => now,
+            i => self.revisions[i - 1].links.contains(target),
+        };
+        match (before, now) {
+            (true, true) => Linked::Kept,
+            (false, true) => Linked::Added,
+            (true, false) => Linked::Removed,
+            (false, false) => Linked::Absent,
+        }
+    }
+
+    /// Articles added and removed by the shown revision.
+    pub fn changes(&self) -> (Vec<&Url>, Vec<&Url>) {
+        let targets = self.targets();
+        let of = |linked| {
+            targets
+                .iter()
+                .filter(|t| self.linked(t) == linked)
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        (of(Linked::Added), of(Linked::Removed))
+    }
+
+    /// Starts playing from the shown revision or from the first one once the last is reached.
+    pub fn play(&mut self, now: f64) {
+        if self.position + 1 >= self.revisions.len() {
+            self.position = 0;
+        }
+        self.playing = Some((now, self.position));
+    }
+
+    /// Moves to the revision due at the time while playing, stops at the last one.
+    pub fn advance(&mut self, now: f64) {
+        let (started_at, from) = match self.playing {
+            Some(playing) => playing,
+            None => return,
+        };
+
+        let steps = ((now - started_at) / STEP_SECS).max(0.) as usize;
+        let last = self.revisions.len() - 1;
+        self.position = (from + steps).min(last);
+        if self.position == last {
+            self.playing = None;
+        }
+    }
+}
+
+/// Retrieves the latest revisions of the article and the articles every one of them links.
+/// Revisions which can not be retrieved, e.g. hidden ones, are left out.
+pub async fn timeline(
+    source: Arc<dyn LinkSource>,
+    article: Url,
+    limit: usize,
+) -> Result<Timeline, Error> {
+    let listed = source.revisions(&article, limit).await?;
+
+    // revisions are retrieved at once, the source limits how many requests run
+    let tasks = listed
+        .iter()
+        .map(|(id, _)| {
+            let (source, article, id) = (source.clone(), article.clone(), *id);
+            tokio::spawn(async move { source.revision_links(&article, id).await })
+        })
+        .collect::<Vec<_>>();
+
+    let mut revisions = vec![];
+    for ((id, at), task) in listed.into_iter().zip(tasks) {
+        let links = match task.await.map_err(io::Error::from)? {
+            Ok(links) => links,
+            Err(err) => {
+                error!("failed to retrieve revision {}: {}", id, err);
+                continue;
+            }
+        };
+        revisions.push(Revision {
+            id,
+            at,
+            links: links
+                .into_iter()
+                .map(|l| l.url)
+                .filter(|u| u.url_type() == url::Type::Article && u.host() == article.host())
+                .filter(|u| *u != article)
+                .collect(),
+        });
+    }
+
+    if revisions.is_empty() {
+        return Err(Error::NotFound(format!("revisions of {}", article.val())));
+    }
+    revisions.sort_by_key(|r| r.at);
+    Ok(Timeline::new(article, revisions))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::testing::url;
+
+    fn timeline(links: &[&[&str]]) -> Timeline {
+        let revisions = links
+            .iter()
+            .enumerate()
+            .map(|(i, links)| Revision {
+                id: i as u64,
+                at: SystemTime::UNIX_EPOCH + Duration::from_secs(i as u64),
+                links: links.iter().map(|t| url(t)).collect(),
+            })
+            .collect();
+        Timeline::new(url("Article"), revisions)
+    }
+
+    fn titles(urls: Vec<&Url>) -> Vec<String> {
+        urls.into_iter().map(Url::val_for_type).collect()
+    }
+
+    #[test]
+    fn parses_days_and_timestamps() {
+        let day = parse_date(" 2015-03-01 ").unwrap();
+        assert_eq!(date_label(day), "2015-03-01");
+        assert_eq!(
+            parse_date("2015-03-01T12:30:00Z"),
+            Some(day + Duration::from_secs(12 * 3600 + 30 * 60))
+        );
+        assert_eq!(parse_date("2015-13-01"), None);
+        assert_eq!(parse_date("yesterday"), None);
+    }
+
+    #[test]
+    fn revisions_add_and_remove_links() {
+        let mut timeline = timeline(&[&["B", "A"], &["A", "C"], &["A", "B", "C"]]);
+        assert_eq!(titles(timeline.targets()), vec!["A", "B", "C"]);
+        assert_eq!(timeline.linked(&url("B")), Linked::Kept);
+        assert_eq!(timeline.linked(&url("C")), Linked::Absent);
+
+        timeline.position = 1;
+        let (added, removed) = timeline.changes();
+        assert_eq!(titles(added), vec!["C"]);
+        assert_eq!(titles(removed), vec!["B"]);
+        assert_eq!(timeline.linked(&url("A")), Linked::Kept);
+    }
+
+    #[test]
+    fn playing_stops_at_last_revision() {
+        let mut timeline = timeline(&[&["A"], &["B"], &["C"]]);
+        timeline.play(10.);
+        timeline.advance(11.5);
+        assert_eq!(timeline.position, 1);
+        assert!(timeline.playing.is_some());
+
+        timeline.advance(20.);
+        assert_eq!(timeline.position, 2);
+        assert!(timeline.playing.is_none());
+
+        timeline.play(30.);
+        assert_eq!(timeline.position, 0);
+    }
+}
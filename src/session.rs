@@ -0,0 +1,247 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use log::error;
+use petgraph::visit::{EdgeRef, IntoEdgeReferences};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cursor::Cursor,
+    graph::Graph,
+    link::LinkContext,
+    link_source::{Error, LinkSource},
+    url::Url,
+};
+
+/// Directory in the working directory the sessions are saved to.
+pub const DIR: &str = "sessions";
+
+/// Exploration saved at some moment: its articles and the links between them. Sessions saved
+/// at different times can be compared to see how the articles changed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    /// Seconds since the unix epoch.
+    pub saved_at: u64,
+    /// Expanded articles, the exploration started from the first one.
+    pub roots: Vec<String>,
+    pub nodes: Vec<String>,
+    pub links: Vec<SessionLink>,
+    /// Community ids of the articles, as found when the session was saved.
+    #[serde(default)]
+    pub communities: BTreeMap<String, usize>,
+}
+
+/// Link between two articles of the session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionLink {
+    pub from: String,
+    pub to: String,
+    /// Anchor, section and sentence of every occurrence of the link in the article.
+    #[serde(default)]
+    pub contexts: Vec<LinkContext>,
+}
+
+impl SessionLink {
+    /// Articles the link connects, the same link in two sessions has the same key.
+    pub fn key(&self) -> (String, String) {
+        (self.from.clone(), self.to.clone())
+    }
+}
+
+impl Session {
+    /// Takes articles and links of the graph as they are now.
+    pub fn of(g: &Graph, cursor: &Cursor) -> Self {
+        let url = |idx| {
+            g.g.node_weight(idx)
+                .unwrap()
+                .payload()
+                .url()
+                .val()
+                .to_string()
+        };
+        // articles expanded twice are roots twice
+        let mut seen = HashSet::new();
+        Self {
+            saved_at: now(),
+            roots: cursor
+                .roots_with_parents()
+                .into_iter()
+                .map(|(root, _)| url(root))
+                .filter(|root| seen.insert(root.clone()))
+                .collect(),
+            nodes: g.g.node_indices().map(url).collect(),
+            links: g
+                .g
+                .edge_references()
+                .map(|e| SessionLink {
+                    from: url(e.source()),
+                    to: url(e.target()),
+                    contexts: e.weight().payload().occurrences().to_vec(),
+                })
+                .collect(),
+            communities: g
+                .g
+                .node_indices()
+                .filter_map(|idx| {
+                    let community = g.g.node_weight(idx).unwrap().payload().community()?;
+                    Some((url(idx), community))
+                })
+                .collect(),
+        }
+    }
+
+    /// Article the exploration started from.
+    pub fn root(&self) -> &str {
+        &self.roots[0]
+    }
+
+    pub fn title(&self) -> String {
+        match Url::new(self.root()) {
+            Ok(url) => url.val_for_type().replace('_', " "),
+            Err(_) => self.root().to_string(),
+        }
+    }
+
+    /// Time of saving in UTC, e.g. `2024-03-01T12:00:00Z`.
+    pub fn saved_at_label(&self) -> String {
+        humantime::format_rfc3339_seconds(UNIX_EPOCH + Duration::from_secs(self.saved_at))
+            .to_string()
+    }
+
+    /// Writes the session to the directory, the file is named after the root and the time.
+    pub fn save(&self, dir: &Path) -> io::Result<PathBuf> {
+        fs::create_dir_all(dir)?;
+
+        let name = self
+            .title()
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '_' })
+            .collect::<String>();
+        let path = dir.join(format!("{}_{}.toml", name, self.saved_at));
+        let content =
+            toml::to_string(self).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        fs::write(&path, content)?;
+        Ok(path)
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let session: Self = toml::from_str(&content)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        if session.roots.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("session without root: {}", path.display()),
+            ));
+        }
+        Ok(session)
+    }
+}
+
+/// Sessions saved to the directory, the oldest first. Files which can not be read are skipped.
+pub fn list(dir: &Path) -> Vec<(PathBuf, Session)> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+
+    let mut sessions = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+        .filter_map(|path| match Session::load(&path) {
+            Ok(session) => Some((path, session)),
+            Err(err) => {
+                error!("failed to load session {}: {}", path.display(), err);
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    sessions.sort_by_key(|(_, session)| session.saved_at);
+    sessions
+}
+
+/// Retrieves links of the expanded articles of the session again, as they are now. The source
+/// should not be cached.
+///
+/// Only outgoing links are retrieved, articles reached through backlinks or categories show
+/// up as removed.
+pub async fn recrawl(source: &dyn LinkSource, session: &Session) -> Result<Session, Error> {
+    let mut recrawled = Session {
+        saved_at: now(),
+        roots: session.roots.clone(),
+        nodes: session.roots.clone(),
+        links: vec![],
+        communities: Default::default(),
+    };
+
+    let mut seen = session.roots.iter().cloned().collect::<HashSet<_>>();
+    let mut linked = HashMap::new();
+    for root in session.roots.iter() {
+        let url = Url::new(root).map_err(|err| Error::Parse(err.to_string()))?;
+        source.links(&url).await?.into_iter().for_each(|l| {
+            let target = l.url.val().to_string();
+            if seen.insert(target.clone()) {
+                recrawled.nodes.push(target.clone());
+            }
+            let i = *linked
+                .entry((root.clone(), target.clone()))
+                .or_insert_with(|| {
+                    recrawled.links.push(SessionLink {
+                        from: root.clone(),
+                        to: target,
+                        contexts: vec![],
+                    });
+                    recrawled.links.len() - 1
+                });
+            recrawled.links[i].contexts.push(l.context);
+        });
+    }
+
+    Ok(recrawled)
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_link_contexts() {
+        let context = LinkContext {
+            anchor: "graphs".to_string(),
+            section: Some("History".to_string()),
+            sentence: "Euler studied graphs.".to_string(),
+        };
+        let session = Session {
+            saved_at: 0,
+            roots: vec!["A".to_string()],
+            nodes: vec!["A".to_string(), "B".to_string()],
+            links: vec![SessionLink {
+                from: "A".to_string(),
+                to: "B".to_string(),
+                contexts: vec![context.clone(), LinkContext::default()],
+            }],
+            communities: [("B".to_string(), 1)].into_iter().collect(),
+        };
+
+        let loaded: Session = toml::from_str(&toml::to_string(&session).unwrap()).unwrap();
+
+        assert_eq!(loaded.links[0].key(), ("A".to_string(), "B".to_string()));
+        assert_eq!(
+            loaded.links[0].contexts,
+            vec![context, LinkContext::default()]
+        );
+        assert_eq!(loaded.communities, session.communities);
+    }
+}
@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+
+use crate::{
+    link::{Link, LinkContext},
+    link_source::{Error, LinkSource},
+    url::Url,
+};
+
+/// Url of the english wikipedia article.
+pub fn url(title: &str) -> Url {
+    Url::new(&format!("https://en.wikipedia.org/wiki/{}", title)).unwrap()
+}
+
+/// Source which knows only the links it was created with, so the app can be tested without
+/// the network.
+pub struct FakeSource {
+    links: HashMap<Url, Vec<Url>>,
+}
+
+impl FakeSource {
+    /// Takes titles of the articles and of the articles they link.
+    pub fn new(links: &[(&str, &[&str])]) -> Self {
+        Self {
+            links: links
+                .iter()
+                .map(|(from, to)| (url(from), to.iter().map(|t| url(t)).collect()))
+                .collect(),
+        }
+    }
+
+    fn linked(&self, url: &Url) -> Result<&Vec<Url>, Error> {
+        self.links
+            .get(url)
+            .ok_or_else(|| Error::NotFound(url.val().to_string()))
+    }
+}
+
+#[async_trait]
+impl LinkSource for FakeSource {
+    async fn links(&self, url: &Url) -> Result<Vec<Link>, Error> {
+        Ok(self
+            .linked(url)?
+            .iter()
+            .map(|to| Link {
+                url: to.clone(),
+                context: LinkContext {
+                    anchor: to.val_for_type(),
+                    ..Default::default()
+                },
+                revision: None,
+            })
+            .collect())
+    }
+
+    async fn backlinks(&self, url: &Url) -> Result<Vec<Url>, Error> {
+        Ok(self
+            .links
+            .iter()
+            .filter(|(_, to)| to.contains(url))
+            .map(|(from, _)| from.clone())
+            .collect())
+    }
+
+    async fn summary(&self, url: &Url) -> Result<String, Error> {
+        self.linked(url)?;
+        Ok(format!("{} is an article.", url.val_for_type()))
+    }
+}
@@ -0,0 +1,12 @@
+use egui::ColorImage;
+
+use crate::link_source::Error;
+
+/// Decodes thumbnail retrieved from the source into an image egui can upload as a texture.
+pub fn decode(bytes: &[u8]) -> Result<ColorImage, Error> {
+    let image = image::load_from_memory(bytes)
+        .map_err(|err| Error::Parse(format!("thumbnail: {}", err)))?
+        .to_rgba8();
+    let size = [image.width() as usize, image.height() as usize];
+    Ok(ColorImage::from_rgba_unmultiplied(size, image.as_raw()))
+}
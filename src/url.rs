@@ -3,20 +3,49 @@ use regex::Regex;
 use url::{self, ParseError};
 
 const WIKIPEDIA_HOST: &str = "wikipedia.org";
+const FILE_NAMESPACE: &str = "File:";
 
-#[derive(Eq, PartialEq, Hash, Clone)]
+const IMAGE_EXTENSIONS: [&str; 7] = ["png", "jpg", "jpeg", "gif", "svg", "webp", "tif"];
+const AUDIO_EXTENSIONS: [&str; 6] = ["ogg", "oga", "mp3", "wav", "flac", "opus"];
+const VIDEO_EXTENSIONS: [&str; 3] = ["webm", "ogv", "mp4"];
+
+#[derive(Debug, Eq, PartialEq, Hash, Clone)]
 pub struct Url {
     val: url::Url,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Type {
     Article,
+    Category,
+    /// Red link to an article which does not exist yet.
+    Missing,
     File,
     ExternalArticle,
     Other,
 }
 
+impl Type {
+    pub fn all() -> &'static [Type] {
+        &[
+            Type::Article,
+            Type::Category,
+            Type::Missing,
+            Type::File,
+            Type::ExternalArticle,
+            Type::Other,
+        ]
+    }
+}
+
+/// Kind of the media file by its extension.
+#[derive(Debug, PartialEq)]
+pub enum Media {
+    Image,
+    Audio,
+    Video,
+}
+
 impl Url {
     pub fn new(val: &str) -> Result<Self, ParseError> {
         let val = url::Url::parse(val)?;
@@ -24,6 +53,16 @@ impl Url {
         Ok(Self { val })
     }
 
+    /// Builds article url from its title as it is stored by wikipedia, e.g. `Graph_theory`.
+    pub fn wiki_article(host: &str, title: &str) -> Result<Self, ParseError> {
+        let title = title
+            .replace(' ', "_")
+            .replace('%', "%25")
+            .replace('?', "%3F")
+            .replace('#', "%23");
+        Self::new(&format!("https://{}/wiki/{}", host, title))
+    }
+
     pub fn is_wiki(&self) -> bool {
         self.val.host_str().unwrap().contains(WIKIPEDIA_HOST)
     }
@@ -32,22 +71,87 @@ impl Url {
         self.val.as_str()
     }
 
+    pub fn scheme(&self) -> &str {
+        self.val.scheme()
+    }
+
+    pub fn host(&self) -> &str {
+        self.val.host_str().unwrap_or_default()
+    }
+
+    /// Language of the wikipedia edition, e.g. `en` for `en.wikipedia.org`.
+    pub fn lang(&self) -> Option<&str> {
+        match self.is_wiki() {
+            true => self.host().split('.').next(),
+            false => None,
+        }
+    }
+
+    /// Last path segment as it is in the url, without decoding.
+    pub fn last_segment(&self) -> &str {
+        self.val.path().rsplit('/').next().unwrap_or_default()
+    }
+
     pub fn val_for_type(&self) -> String {
         match self.url_type() {
-            Type::Article | Type::File => String::from(
+            Type::Article | Type::Category | Type::File => String::from(
                 urlencoding::decode(self.val.path())
                     .unwrap()
                     .split('/')
                     .last()
                     .unwrap(),
             ),
+            Type::Missing => self
+                .val
+                .query_pairs()
+                .find(|(key, _)| key == "title")
+                .map(|(_, title)| title.to_string())
+                .unwrap_or_default(),
             Type::ExternalArticle | Type::Other => self.val().to_string(),
         }
     }
 
+    pub fn media(&self) -> Option<Media> {
+        let path = self.val.path().to_lowercase();
+        let ext = path.rsplit_once('.')?.1;
+        if IMAGE_EXTENSIONS.contains(&ext) {
+            Some(Media::Image)
+        } else if AUDIO_EXTENSIONS.contains(&ext) {
+            Some(Media::Audio)
+        } else if VIDEO_EXTENSIONS.contains(&ext) {
+            Some(Media::Video)
+        } else {
+            None
+        }
+    }
+
+    /// Title of the file page, e.g. `File:Example.jpg`, for both file pages and direct links to
+    /// the uploaded files. Links to thumbnails point to the file they were made of.
+    pub fn file_title(&self) -> Option<String> {
+        if self.url_type() != Type::File {
+            return None;
+        }
+
+        let path = urlencoding::decode(self.val.path()).ok()?;
+        let mut segments = path.rsplit('/');
+        let last = segments.next()?;
+        let name = match path.contains("/thumb/") {
+            true => segments.next()?,
+            false => last,
+        };
+        match name.starts_with(FILE_NAMESPACE) {
+            true => Some(name.to_string()),
+            false => Some(format!("{}{}", FILE_NAMESPACE, name)),
+        }
+    }
+
     pub fn url_type(&self) -> Type {
         if self.is_wiki_article() {
             Type::Article
+        } else if self.is_category() {
+            Type::Category
+        } else if self.is_missing_article() {
+            Type::Missing
         } else if self.is_file() {
             Type::File
         } else if self.is_external_article() {
@@ -70,13 +174,34 @@ impl Url {
         false
     }
 
+    /// Only english namespace name is recognized.
+    fn is_category(&self) -> bool {
+        lazy_static! {
+            static ref RE: Regex =
+                Regex::new(r"https://[a-z]{2}\.wikipedia\.org/wiki/Category:[^/]+$").unwrap();
+        }
+
+        RE.is_match(self.val())
+    }
+
+    /// Red links point to the edit page, e.g. `/w/index.php?title=X&action=edit&redlink=1`.
+    fn is_missing_article(&self) -> bool {
+        self.is_wiki()
+            && self.val.path() == "/w/index.php"
+            && self
+                .val
+                .query_pairs()
+                .any(|(key, val)| key == "redlink" && val == "1")
+            && self.val.query_pairs().any(|(key, _)| key == "title")
+    }
+
+    /// File pages are recognized by the namespace, other links by the media extension.
     fn is_file(&self) -> bool {
-        let s = self.val.to_string();
-        s.ends_with(".png")
-            || s.ends_with(".jpg")
-            || s.ends_with(".jpeg")
-            || s.ends_with(".gif")
-            || s.ends_with(".svg")
+        let file_page = self.is_wiki()
+            && urlencoding::decode(self.val.path()).map_or(false, |path| {
+                path.starts_with(&format!("/wiki/{}", FILE_NAMESPACE))
+            });
+        file_page || self.media().is_some()
     }
 
     fn is_external_article(&self) -> bool {
@@ -84,3 +209,88 @@ impl Url {
         s.contains("arxiv.org") || s.contains("doi.org")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_url_types() {
+        let cases = [
+            (
+                "https://en.wikipedia.org/wiki/Graph_theory",
+                Type::Article,
+                "Graph_theory",
+            ),
+            (
+                "https://en.wikipedia.org/wiki/Category:Graph_theory",
+                Type::Category,
+                "Category:Graph_theory",
+            ),
+            (
+                "https://en.wikipedia.org/w/index.php?title=Graph_sketch&action=edit&redlink=1",
+                Type::Missing,
+                "Graph_sketch",
+            ),
+            (
+                "https://en.wikipedia.org/w/index.php?title=Graph_theory&action=edit&section=1",
+                Type::Other,
+                "https://en.wikipedia.org/w/index.php?title=Graph_theory&action=edit&section=1",
+            ),
+            (
+                "https://en.wikipedia.org/wiki/File:K%C3%B6nigsberg_bridges.png",
+                Type::File,
+                "File:Königsberg_bridges.png",
+            ),
+            (
+                "https://upload.wikimedia.org/wikipedia/commons/5/5d/Konigsberg_bridges.png",
+                Type::File,
+                "Konigsberg_bridges.png",
+            ),
+        ];
+
+        cases.iter().for_each(|(val, url_type, title)| {
+            let url = Url::new(val).unwrap();
+            assert_eq!(url.url_type(), *url_type, "{}", val);
+            assert_eq!(url.val_for_type(), *title, "{}", val);
+        });
+    }
+
+    #[test]
+    fn resolves_file_titles_and_media() {
+        let cases = [
+            (
+                "https://en.wikipedia.org/wiki/File:Example.jpg",
+                Some("File:Example.jpg"),
+                Some(Media::Image),
+            ),
+            (
+                "https://upload.wikimedia.org/wikipedia/commons/a/a9/Example.jpg",
+                Some("File:Example.jpg"),
+                Some(Media::Image),
+            ),
+            (
+                "https://upload.wikimedia.org/wikipedia/commons/thumb/a/a9/Example.jpg/220px-Example.jpg",
+                Some("File:Example.jpg"),
+                Some(Media::Image),
+            ),
+            (
+                "https://upload.wikimedia.org/wikipedia/commons/c/c8/Example.ogg",
+                Some("File:Example.ogg"),
+                Some(Media::Audio),
+            ),
+            (
+                "https://upload.wikimedia.org/wikipedia/commons/4/47/Example.webm",
+                Some("File:Example.webm"),
+                Some(Media::Video),
+            ),
+            ("https://en.wikipedia.org/wiki/Graph_theory", None, None),
+        ];
+
+        cases.into_iter().for_each(|(val, title, media)| {
+            let url = Url::new(val).unwrap();
+            assert_eq!(url.file_title().as_deref(), title, "{}", val);
+            assert_eq!(url.media(), media, "{}", val);
+        });
+    }
+}
@@ -1,68 +1,143 @@
+use std::{collections::HashMap, path::PathBuf, sync::Arc, time::SystemTime};
+
+use async_trait::async_trait;
 use crossbeam::channel::Sender;
+use lazy_static::lazy_static;
 use log::{error, info};
-use reqwest::Error;
-use scraper::{Html, Selector};
+use petgraph::Direction::{self, Incoming, Outgoing};
+use regex::Regex;
+use scraper::{ElementRef, Html, Node, Selector};
 use tokio::task::JoinHandle;
 
-use crate::url::Url;
+use crate::{
+    entities::{
+        is_item, parse_claims, parse_labels, parse_pageprops, Entity, Statement, BATCH_SIZE,
+    },
+    fixture::Page,
+    link::{Link, LinkContext},
+    link_source::{Error, LinkSource},
+    revision,
+    url::Url,
+};
+
+const WIKIDATA_API: &str = "https://www.wikidata.org/w/api.php";
+/// Files linked directly are described on commons.
+const COMMONS_HOST: &str = "commons.wikimedia.org";
+
+/// Width of the requested thumbnails in pixels.
+const THUMBNAIL_WIDTH: usize = 320;
+/// Thumbnails larger than this are dropped, e.g. animated gifs.
+const THUMBNAIL_MAX_BYTES: usize = 1 << 20;
+
+/// Max number of backlinks requested from the api.
+const BACKLINKS_LIMIT: usize = 500;
+
+/// Page properties of the wikidata item and of disambiguation pages.
+const WIKIBASE_ITEM: &str = "wikibase_item";
+const DISAMBIGUATION: &str = "disambiguation";
+
+lazy_static! {
+    static ref CITATION: Regex = Regex::new(r"\[\d+\]").unwrap();
+}
+
+/// Section of the links between articles and their categories.
+const CATEGORIES_SECTION: &str = "Categories";
+
+/// What is retrieved for the node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Retrieval {
+    Links,
+    /// Articles linking to the node.
+    Backlinks,
+    /// Categories of the article.
+    Categories,
+    /// Articles and subcategories of the category.
+    Members,
+}
+
+impl Retrieval {
+    /// Direction of the retrieved links relative to the node.
+    pub fn direction(&self) -> Direction {
+        match self {
+            Retrieval::Backlinks => Incoming,
+            Retrieval::Links | Retrieval::Categories | Retrieval::Members => Outgoing,
+        }
+    }
+}
 
 /// Retrieves urls from wikipedia article
 pub struct UrlRetriever {
-    client: reqwest::Client,
-    results: Sender<Result<Url, Error>>,
+    source: Arc<dyn LinkSource>,
+    results: Sender<Result<Link, Error>>,
+    as_of: Option<SystemTime>,
 }
 
 impl UrlRetriever {
-    pub fn new(results: Sender<Result<Url, Error>>) -> Self {
+    pub fn new(source: Arc<dyn LinkSource>, results: Sender<Result<Link, Error>>) -> Self {
         Self {
-            client: reqwest::Client::new(),
+            source,
             results,
+            as_of: None,
         }
     }
 
-    pub fn run(self, task: Url) -> JoinHandle<()> {
-        tokio::spawn(async move {
-            let results = self.get_links(&task).await;
+    /// Retrieves links of the revision current at the time instead of the live article.
+    /// Backlinks and categories are always the live ones.
+    pub fn at(mut self, at: Option<SystemTime>) -> Self {
+        self.as_of = at;
+        self
+    }
 
-            match results {
-                Ok(urls) => {
-                    for url in urls {
-                        self.results.send(Ok(url)).unwrap();
-                    }
-                }
-                Err(err) => {
-                    self.results.send(Err(err)).unwrap();
+    pub fn run(self, task: Url, retrieval: Retrieval) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let title = task.val_for_type();
+            let results = match (retrieval, self.as_of) {
+                (Retrieval::Links, None) => self.source.links(&task).await,
+                (Retrieval::Links, Some(at)) => {
+                    self.source
+                        .links_as_of(&task, at)
+                        .await
+                        .map(|(revision, links)| {
+                            links
+                                .into_iter()
+                                .map(|l| Link {
+                                    revision: Some(revision),
+                                    ..l
+                                })
+                                .collect()
+                        })
                 }
-            }
+                (Retrieval::Backlinks, _) => self
+                    .source
+                    .backlinks(&task)
+                    .await
+                    .map(|urls| links_without_text(urls, None, |_| title.clone())),
+                (Retrieval::Categories, _) => self.source.categories(&task).await.map(|urls| {
+                    links_without_text(urls, Some(CATEGORIES_SECTION), Url::val_for_type)
+                }),
+                (Retrieval::Members, _) => self.source.category_members(&task).await.map(|urls| {
+                    links_without_text(urls, Some(CATEGORIES_SECTION), Url::val_for_type)
+                }),
+            };
+            self.send(results);
         })
     }
 
-    pub async fn get_links(&self, url: &Url) -> Result<Vec<Url>, Error> {
-        let mut links = Vec::new();
-        let res = self.client.get(url.val()).send().await?;
-
-        let protocol = format!("{}://", res.url().scheme());
-        let host = res.url().host().unwrap().to_string();
-
-        let text = res.text().await?;
-        let doc = Html::parse_document(text.as_str());
-        let a_selector = Selector::parse("a").unwrap();
-
-        doc.select(&a_selector).for_each(|selection| {
-            let href_val = selection.value().attr("href").unwrap_or_default();
-
-            if let Some(url) =
-                self.parse_href_val(protocol.clone(), host.clone(), href_val.to_string())
-            {
-                info!("found url: {}", url.val());
-                links.push(url);
+    /// Stops once the receiver is gone, e.g. when the tab of the task was closed.
+    fn send(&self, results: Result<Vec<Link>, Error>) {
+        let results = match results {
+            Ok(links) => links.into_iter().map(Ok).collect(),
+            Err(err) => vec![Err(err)],
+        };
+        for result in results {
+            if self.results.send(result).is_err() {
+                info!("dropped results of a task nobody waits for");
+                return;
             }
-        });
-
-        Ok(links)
+        }
     }
 
-    pub fn parse_href_val(&self, protocol: String, host: String, href_val: String) -> Option<Url> {
+    pub fn parse_href_val(protocol: String, host: String, href_val: String) -> Option<Url> {
         let mut href_val_final = href_val;
 
         if href_val_final.starts_with("/wiki") | href_val_final.starts_with("/w") {
@@ -102,3 +177,763 @@ impl UrlRetriever {
         }
     }
 }
+
+/// How [`WebSource`] treats the network.
+#[derive(Debug, Clone, Default)]
+pub enum Mode {
+    #[default]
+    Live,
+    /// Fetches pages and stores every one of them to the directory.
+    Record(PathBuf),
+    /// Reads pages only from the directory recorded before, never touches the network.
+    Replay(PathBuf),
+}
+
+/// Scrapes articles from wikipedia.
+#[derive(Default)]
+pub struct WebSource {
+    client: reqwest::Client,
+    mode: Mode,
+}
+
+impl WebSource {
+    pub fn new(mode: Mode) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            mode,
+        }
+    }
+
+    /// Gets the page from the network or from the fixtures depending on the mode.
+    async fn fetch(&self, url: &str, query: &[(&str, &str)]) -> Result<Page, Error> {
+        self.fetch_limited(url, query, usize::MAX).await
+    }
+
+    /// Gets the page like [`Self::fetch`] but fails as soon as its body is larger than the limit,
+    /// without downloading the rest.
+    async fn fetch_limited(
+        &self,
+        url: &str,
+        query: &[(&str, &str)],
+        max_bytes: usize,
+    ) -> Result<Page, Error> {
+        let requested = if query.is_empty() {
+            reqwest::Url::parse(url)
+        } else {
+            reqwest::Url::parse_with_params(url, query)
+        }
+        .map_err(|err| Error::Parse(format!("{}: {}", url, err)))?;
+
+        if let Mode::Replay(dir) = &self.mode {
+            let page = Page::load(dir, requested.as_str())?
+                .ok_or_else(|| Error::NotFound(requested.to_string()))?;
+            if page.body.len() > max_bytes {
+                return Err(too_large(&requested, max_bytes));
+            }
+            return Ok(page);
+        }
+
+        let mut res = self.client.get(requested.clone()).send().await?;
+        if res
+            .content_length()
+            .is_some_and(|len| len > max_bytes as u64)
+        {
+            return Err(too_large(&requested, max_bytes));
+        }
+        let mut page = Page {
+            url: res.url().to_string(),
+            status: res.status().as_u16(),
+            headers: res
+                .headers()
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_str().unwrap_or_default().to_string()))
+                .collect(),
+            body: vec![],
+        };
+        // the length is not always sent, e.g. for compressed bodies
+        while let Some(chunk) = res.chunk().await? {
+            if page.body.len() + chunk.len() > max_bytes {
+                return Err(too_large(&requested, max_bytes));
+            }
+            page.body.extend_from_slice(&chunk);
+        }
+
+        if let Mode::Record(dir) = &self.mode {
+            if let Err(err) = page.save(dir, requested.as_str()) {
+                error!("failed to record {}: {}", requested, err);
+            }
+        }
+
+        Ok(page)
+    }
+
+    /// Wikidata items of the articles of the same wiki by their titles, redirects are followed.
+    async fn qid_by_title(&self, urls: &[Url]) -> Result<HashMap<String, String>, Error> {
+        let first = match urls.first() {
+            Some(first) => first,
+            None => return Ok(HashMap::new()),
+        };
+
+        let api = format!("{}://{}/w/api.php", first.scheme(), first.host());
+        let titles = urls.iter().map(Url::val_for_type).collect::<Vec<_>>();
+        let page = self
+            .fetch(
+                &api,
+                &[
+                    ("action", "query"),
+                    ("prop", "pageprops"),
+                    ("ppprop", WIKIBASE_ITEM),
+                    ("redirects", "1"),
+                    ("titles", titles.join("|").as_str()),
+                    ("format", "json"),
+                    ("formatversion", "2"),
+                ],
+            )
+            .await?;
+        Ok(parse_pageprops(
+            &serde_json::from_slice(&page.body)?,
+            WIKIBASE_ITEM,
+        ))
+    }
+}
+
+#[async_trait]
+impl LinkSource for WebSource {
+    async fn links(&self, url: &Url) -> Result<Vec<Link>, Error> {
+        let page = self.fetch(url.val(), &[]).await?;
+        let res_url = Url::new(&page.url).map_err(|err| Error::Parse(err.to_string()))?;
+
+        let protocol = format!("{}://", res_url.scheme());
+        Ok(parse_links(&page.text(), &protocol, res_url.host()))
+    }
+
+    /// Finds the revision current at the time with the api and scrapes it like the live
+    /// article.
+    async fn links_as_of(&self, url: &Url, at: SystemTime) -> Result<(u64, Vec<Link>), Error> {
+        let api = format!("{}://{}/w/api.php", url.scheme(), url.host());
+        let title = url.val_for_type();
+        let start = humantime::format_rfc3339_seconds(at).to_string();
+        let page = self
+            .fetch(
+                &api,
+                &[
+                    ("action", "query"),
+                    ("prop", "revisions"),
+                    ("titles", title.as_str()),
+                    ("rvprop", "ids"),
+                    ("rvlimit", "1"),
+                    ("rvstart", start.as_str()),
+                    ("rvdir", "older"),
+                    ("redirects", "1"),
+                    ("format", "json"),
+                    ("formatversion", "2"),
+                ],
+            )
+            .await?;
+        let revision = parse_revision_id(&serde_json::from_slice(&page.body)?)
+            .ok_or_else(|| Error::NotFound(format!("{} as of {}", url.val(), start)))?;
+
+        Ok((revision, self.revision_links(url, revision).await?))
+    }
+
+    async fn revisions(&self, url: &Url, limit: usize) -> Result<Vec<(u64, SystemTime)>, Error> {
+        let api = format!("{}://{}/w/api.php", url.scheme(), url.host());
+        let title = url.val_for_type();
+        let limit = limit.to_string();
+        let page = self
+            .fetch(
+                &api,
+                &[
+                    ("action", "query"),
+                    ("prop", "revisions"),
+                    ("titles", title.as_str()),
+                    ("rvprop", "ids|timestamp"),
+                    ("rvlimit", limit.as_str()),
+                    ("redirects", "1"),
+                    ("format", "json"),
+                    ("formatversion", "2"),
+                ],
+            )
+            .await?;
+
+        Ok(parse_revisions(&serde_json::from_slice(&page.body)?))
+    }
+
+    /// Scrapes the revision like the live article.
+    async fn revision_links(&self, url: &Url, revision: u64) -> Result<Vec<Link>, Error> {
+        let page = self.fetch(&revision::url(url, revision), &[]).await?;
+
+        let protocol = format!("{}://", url.scheme());
+        Ok(parse_links(&page.text(), &protocol, url.host()))
+    }
+
+    /// Uses mediawiki api as backlinks are not present in the article html.
+    async fn backlinks(&self, url: &Url) -> Result<Vec<Url>, Error> {
+        let api = format!("{}://{}/w/api.php", url.scheme(), url.host());
+        let title = url.val_for_type();
+        let limit = BACKLINKS_LIMIT.to_string();
+        let page = self
+            .fetch(
+                &api,
+                &[
+                    ("action", "query"),
+                    ("list", "backlinks"),
+                    ("bltitle", title.as_str()),
+                    ("blnamespace", "0"),
+                    ("bllimit", limit.as_str()),
+                    ("format", "json"),
+                ],
+            )
+            .await?;
+
+        let res: serde_json::Value = serde_json::from_slice(&page.body)?;
+        let backlinks = res
+            .pointer("/query/backlinks")
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| Error::Parse(format!("no backlinks in the response for {}", title)))?;
+
+        Ok(backlinks
+            .iter()
+            .filter_map(|b| b.get("title").and_then(|t| t.as_str()))
+            .filter_map(|t| Url::wiki_article(url.host(), t).ok())
+            .collect())
+    }
+
+    async fn categories(&self, url: &Url) -> Result<Vec<Url>, Error> {
+        let api = format!("{}://{}/w/api.php", url.scheme(), url.host());
+        let title = url.val_for_type();
+        let page = self
+            .fetch(
+                &api,
+                &[
+                    ("action", "query"),
+                    ("prop", "categories"),
+                    ("titles", title.as_str()),
+                    ("clshow", "!hidden"),
+                    ("cllimit", "max"),
+                    ("redirects", "1"),
+                    ("format", "json"),
+                    ("formatversion", "2"),
+                ],
+            )
+            .await?;
+
+        let res: serde_json::Value = serde_json::from_slice(&page.body)?;
+        Ok(titles_at(&res, "/query/pages/0/categories", url.host()))
+    }
+
+    async fn category_members(&self, url: &Url) -> Result<Vec<Url>, Error> {
+        let api = format!("{}://{}/w/api.php", url.scheme(), url.host());
+        let title = url.val_for_type();
+        let page = self
+            .fetch(
+                &api,
+                &[
+                    ("action", "query"),
+                    ("list", "categorymembers"),
+                    ("cmtitle", title.as_str()),
+                    ("cmtype", "page|subcat"),
+                    ("cmlimit", "max"),
+                    ("format", "json"),
+                    ("formatversion", "2"),
+                ],
+            )
+            .await?;
+
+        let res: serde_json::Value = serde_json::from_slice(&page.body)?;
+        Ok(titles_at(&res, "/query/categorymembers", url.host()))
+    }
+
+    async fn langlinks(&self, url: &Url) -> Result<Vec<Url>, Error> {
+        let api = format!("{}://{}/w/api.php", url.scheme(), url.host());
+        let title = url.val_for_type();
+        let page = self
+            .fetch(
+                &api,
+                &[
+                    ("action", "query"),
+                    ("prop", "langlinks"),
+                    ("titles", title.as_str()),
+                    ("lllimit", "max"),
+                    ("redirects", "1"),
+                    ("format", "json"),
+                    ("formatversion", "2"),
+                ],
+            )
+            .await?;
+
+        let res: serde_json::Value = serde_json::from_slice(&page.body)?;
+        let langlinks = res
+            .pointer("/query/pages/0/langlinks")
+            .and_then(|v| v.as_array())
+            .cloned()
+            .unwrap_or_default();
+
+        Ok(langlinks
+            .iter()
+            .filter_map(|l| {
+                let lang = l.get("lang")?.as_str()?;
+                let title = l.get("title")?.as_str()?;
+                Url::wiki_article(&format!("{}.wikipedia.org", lang), title).ok()
+            })
+            .collect())
+    }
+
+    /// Uses rest api summary which is much lighter than the article.
+    async fn summary(&self, url: &Url) -> Result<String, Error> {
+        let api = format!(
+            "{}://{}/api/rest_v1/page/summary/{}",
+            url.scheme(),
+            url.host(),
+            url.last_segment()
+        );
+        let page = self.fetch(&api, &[]).await?;
+
+        let res: serde_json::Value = serde_json::from_slice(&page.body)?;
+        res.get("extract")
+            .and_then(|e| e.as_str())
+            .map(str::to_string)
+            .ok_or_else(|| Error::NotFound(url.val().to_string()))
+    }
+
+    /// Gets qids from the page props of the articles, then key statements of the items from
+    /// wikidata and finally labels of the items the statements refer to.
+    async fn entities(&self, urls: &[Url]) -> Result<Vec<(Url, Entity)>, Error> {
+        let first = match urls.first() {
+            Some(first) => first,
+            None => return Ok(vec![]),
+        };
+        let lang = first.lang().unwrap_or_default();
+        let qid_by_title = self.qid_by_title(urls).await?;
+
+        let mut qids = qid_by_title.values().cloned().collect::<Vec<_>>();
+        qids.sort();
+        qids.dedup();
+        if qids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let page = self
+            .fetch(
+                WIKIDATA_API,
+                &[
+                    ("action", "wbgetentities"),
+                    ("ids", qids.join("|").as_str()),
+                    ("props", "claims"),
+                    ("format", "json"),
+                ],
+            )
+            .await?;
+        let claims = parse_claims(&serde_json::from_slice(&page.body)?);
+
+        let mut items = claims
+            .values()
+            .flatten()
+            .flat_map(|(_, values)| values.iter().filter(|v| is_item(v)).cloned())
+            .collect::<Vec<_>>();
+        items.sort();
+        items.dedup();
+
+        let mut labels = HashMap::new();
+        for chunk in items.chunks(BATCH_SIZE) {
+            let languages = format!("{}|en", lang);
+            let page = self
+                .fetch(
+                    WIKIDATA_API,
+                    &[
+                        ("action", "wbgetentities"),
+                        ("ids", chunk.join("|").as_str()),
+                        ("props", "labels"),
+                        ("languages", languages.as_str()),
+                        ("format", "json"),
+                    ],
+                )
+                .await?;
+            labels.extend(parse_labels(
+                &serde_json::from_slice(&page.body)?,
+                &[lang, "en"],
+            ));
+        }
+
+        Ok(urls
+            .iter()
+            .filter_map(|url| {
+                let qid = qid_by_title.get(&url.val_for_type())?;
+                let statements = claims
+                    .get(qid)
+                    .into_iter()
+                    .flatten()
+                    .map(|(property, values)| Statement {
+                        property,
+                        values: values
+                            .iter()
+                            .map(|v| labels.get(v).cloned().unwrap_or_else(|| v.clone()))
+                            .collect(),
+                    })
+                    .collect();
+                Some((
+                    url.clone(),
+                    Entity {
+                        qid: qid.clone(),
+                        statements,
+                    },
+                ))
+            })
+            .collect())
+    }
+
+    async fn qids(&self, urls: &[Url]) -> Result<Vec<(Url, String)>, Error> {
+        let qid_by_title = self.qid_by_title(urls).await?;
+        Ok(urls
+            .iter()
+            .filter_map(|url| Some((url.clone(), qid_by_title.get(&url.val_for_type())?.clone())))
+            .collect())
+    }
+
+    async fn disambiguations(&self, urls: &[Url]) -> Result<Vec<Url>, Error> {
+        let first = match urls.first() {
+            Some(first) => first,
+            None => return Ok(vec![]),
+        };
+
+        let api = format!("{}://{}/w/api.php", first.scheme(), first.host());
+        let titles = urls.iter().map(Url::val_for_type).collect::<Vec<_>>();
+        let page = self
+            .fetch(
+                &api,
+                &[
+                    ("action", "query"),
+                    ("prop", "pageprops"),
+                    ("ppprop", DISAMBIGUATION),
+                    ("redirects", "1"),
+                    ("titles", titles.join("|").as_str()),
+                    ("format", "json"),
+                    ("formatversion", "2"),
+                ],
+            )
+            .await?;
+        let pages = parse_pageprops(&serde_json::from_slice(&page.body)?, DISAMBIGUATION);
+
+        Ok(urls
+            .iter()
+            .filter(|url| pages.contains_key(&url.val_for_type()))
+            .cloned()
+            .collect())
+    }
+
+    /// Asks the wiki for a thumbnail of the file, audio files and documents may have none.
+    async fn thumbnail(&self, url: &Url) -> Result<Vec<u8>, Error> {
+        let title = url
+            .file_title()
+            .ok_or_else(|| Error::NotFound(format!("file of {}", url.val())))?;
+        let host = match url.is_wiki() {
+            true => url.host(),
+            false => COMMONS_HOST,
+        };
+
+        let api = format!("{}://{}/w/api.php", url.scheme(), host);
+        let width = THUMBNAIL_WIDTH.to_string();
+        let page = self
+            .fetch(
+                &api,
+                &[
+                    ("action", "query"),
+                    ("prop", "imageinfo"),
+                    ("titles", title.as_str()),
+                    ("iiprop", "url"),
+                    ("iiurlwidth", width.as_str()),
+                    ("format", "json"),
+                    ("formatversion", "2"),
+                ],
+            )
+            .await?;
+        let thumb_url = parse_thumbnail(&serde_json::from_slice(&page.body)?)
+            .ok_or_else(|| Error::NotFound(format!("thumbnail of {}", title)))?;
+
+        let page = self
+            .fetch_limited(&thumb_url, &[], THUMBNAIL_MAX_BYTES)
+            .await?;
+        Ok(page.body)
+    }
+}
+
+fn too_large(url: &reqwest::Url, max_bytes: usize) -> Error {
+    Error::Parse(format!("{} is larger than {} bytes", url, max_bytes))
+}
+
+/// Gets id of the latest listed revision from `prop=revisions` response, there is none if the
+/// article did not exist yet.
+fn parse_revision_id(res: &serde_json::Value) -> Option<u64> {
+    res.pointer("/query/pages/0/revisions/0/revid")?.as_u64()
+}
+
+/// Gets ids and times of the revisions listed in `prop=revisions` response.
+fn parse_revisions(res: &serde_json::Value) -> Vec<(u64, SystemTime)> {
+    res.pointer("/query/pages/0/revisions")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|r| {
+            let id = r.get("revid")?.as_u64()?;
+            let at = humantime::parse_rfc3339(r.get("timestamp")?.as_str()?).ok()?;
+            Some((id, at))
+        })
+        .collect()
+}
+
+/// Gets url of the thumbnail from `prop=imageinfo` response.
+fn parse_thumbnail(res: &serde_json::Value) -> Option<String> {
+    res.pointer("/query/pages/0/imageinfo/0/thumburl")?
+        .as_str()
+        .map(str::to_string)
+}
+
+/// Checks the article html for the disambiguation notice box or the disambiguation category.
+pub fn is_disambiguation(html: &str) -> bool {
+    let selector =
+        Selector::parse("#disambigbox, .dmbox, a[href$='Category:Disambiguation_pages']").unwrap();
+    Html::parse_document(html)
+        .select(&selector)
+        .next()
+        .is_some()
+}
+
+/// Links which do not appear in the article text. Wikipedia shows the title of the target
+/// as the anchor by default.
+fn links_without_text(
+    urls: Vec<Url>,
+    section: Option<&str>,
+    anchor: impl Fn(&Url) -> String,
+) -> Vec<Link> {
+    urls.into_iter()
+        .map(|url| Link {
+            context: LinkContext {
+                anchor: anchor(&url),
+                section: section.map(str::to_string),
+                sentence: String::new(),
+            },
+            url,
+            revision: None,
+        })
+        .collect()
+}
+
+/// Builds urls of the pages listed at the pointer of the api response.
+fn titles_at(res: &serde_json::Value, pointer: &str, host: &str) -> Vec<Url> {
+    res.pointer(pointer)
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|p| p.get("title").and_then(|t| t.as_str()))
+        .filter_map(|t| Url::wiki_article(host, t).ok())
+        .collect()
+}
+
+/// Gets links of the article html in the order they appear together with their anchor text and
+/// the section heading they appear under.
+pub fn parse_links(html: &str, protocol: &str, host: &str) -> Vec<Link> {
+    let mut links = Vec::new();
+    let doc = Html::parse_document(html);
+    let selector = Selector::parse("h2, h3, h4, a").unwrap();
+
+    let mut section = None;
+    doc.select(&selector).for_each(|selection| {
+        if selection.value().name() != "a" {
+            section = Some(heading_text(selection.text()));
+            return;
+        }
+
+        let href_val = resolve_relative(selection.value().attr("href").unwrap_or_default());
+
+        if let Some(url) =
+            UrlRetriever::parse_href_val(protocol.to_string(), host.to_string(), href_val)
+        {
+            info!("found url: {}", url.val());
+            links.push(Link {
+                url,
+                context: LinkContext {
+                    anchor: selection.text().collect::<String>().trim().to_string(),
+                    section: section.clone(),
+                    sentence: sentence_around(&selection),
+                },
+                revision: None,
+            });
+        }
+    });
+
+    links
+}
+
+/// Gets the first non empty paragraph of the article html without citations.
+pub fn parse_summary(html: &str) -> Option<String> {
+    let doc = Html::parse_document(html);
+    let selector = Selector::parse("p").unwrap();
+
+    doc.select(&selector)
+        .map(|p| {
+            CITATION
+                .replace_all(p.text().collect::<String>().trim(), "")
+                .to_string()
+        })
+        .find(|text| !text.is_empty())
+}
+
+/// Resolves href relative to the article, as used in ZIM archives, to `/wiki/<title>`.
+/// Absolute urls, paths, queries and anchors are returned as they are.
+fn resolve_relative(href_val: &str) -> String {
+    if href_val.is_empty()
+        || href_val.starts_with('/')
+        || href_val.starts_with('#')
+        || href_val.starts_with('?')
+        || href_val.contains(':')
+    {
+        return href_val.to_string();
+    }
+
+    let mut title = href_val.trim_start_matches("./");
+    while let Some(rest) = title.strip_prefix("../") {
+        title = rest;
+    }
+    if let Some(rest) = title
+        .strip_prefix("A/")
+        .or_else(|| title.strip_prefix("C/"))
+    {
+        title = rest;
+    }
+
+    format!("/wiki/{}", title)
+}
+
+/// Collects heading text without the `[edit]` link which wikipedia adds to every heading.
+fn heading_text<'a>(text: impl Iterator<Item = &'a str>) -> String {
+    text.collect::<String>()
+        .replace("[edit]", "")
+        .trim()
+        .to_string()
+}
+
+/// Finds the sentence containing the link in the enclosing paragraph or list item.
+fn sentence_around(a: &ElementRef) -> String {
+    let block = match a
+        .ancestors()
+        .filter_map(ElementRef::wrap)
+        .find(|el| matches!(el.value().name(), "p" | "li" | "dd" | "td"))
+    {
+        Some(block) => block,
+        None => return String::new(),
+    };
+
+    let mut text = String::new();
+    let mut start = 0;
+    block.descendants().for_each(|node| {
+        if node.id() == a.id() {
+            start = text.len();
+        }
+        if let Node::Text(t) = node.value() {
+            text.push_str(t);
+        }
+    });
+    let end = start + a.text().map(str::len).sum::<usize>();
+
+    let sentence_start = text[..start].rfind(". ").map_or(0, |i| i + 2);
+    let sentence_end = text[end..].find(". ").map_or(text.len(), |i| end + i + 1);
+
+    CITATION
+        .replace_all(text[sentence_start..sentence_end].trim(), "")
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::testing::url;
+
+    fn corpus() -> WebSource {
+        WebSource::new(Mode::Replay(
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/corpus"),
+        ))
+    }
+
+    #[test]
+    fn resolves_href_vals() {
+        let parse = |href: &str| {
+            UrlRetriever::parse_href_val(
+                "https://".to_string(),
+                "en.wikipedia.org".to_string(),
+                href.to_string(),
+            )
+            .map(|url| url.val().to_string())
+        };
+
+        assert_eq!(parse("/wiki/Euler").as_deref(), Some(url("Euler").val()));
+        assert_eq!(
+            parse("//en.wikipedia.org/wiki/Euler").as_deref(),
+            Some(url("Euler").val())
+        );
+        assert_eq!(parse("/wiki/ISBN_(identifier)"), None);
+        assert_eq!(parse("#cite_note-1"), None);
+        assert_eq!(parse("http://example.org/graphs"), None);
+    }
+
+    #[tokio::test]
+    async fn replays_recorded_links() {
+        let links = corpus().links(&url("Graph_theory")).await.unwrap();
+        let found = links
+            .iter()
+            .map(|link| link.url.val_for_type())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            found,
+            [
+                "Mathematics",
+                "Graph_(discrete_mathematics)",
+                "Vertex_(graph_theory)",
+                "Glossary_of_graph_theory",
+                "https://en.wikipedia.org/w/index.php?title=Graph_theory&action=edit&section=1",
+                "Leonhard_Euler",
+                "Seven_Bridges_of_Königsberg",
+                "https://example.org/graphs",
+                "https://en.wikipedia.org/wiki/Special:BookSources/978-0-486-67870-2",
+            ]
+        );
+
+        let euler = &links[found.iter().position(|t| t == "Leonhard_Euler").unwrap()];
+        assert_eq!(euler.context.anchor, "Leonhard Euler");
+        assert_eq!(euler.context.section.as_deref(), Some("History"));
+        assert_eq!(
+            euler.context.sentence,
+            "The paper written by Leonhard Euler on the Seven Bridges of Königsberg is regarded \
+             as the first paper in the history of graph theory."
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_bodies_over_limit() {
+        let source = corpus();
+        let graph_theory = url("Graph_theory");
+        assert!(source
+            .fetch_limited(graph_theory.val(), &[], 1 << 20)
+            .await
+            .is_ok());
+        assert!(matches!(
+            source.fetch_limited(graph_theory.val(), &[], 100).await,
+            Err(Error::Parse(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn replays_redirects_and_misses() {
+        let source = corpus();
+        let direct = source.links(&url("Graph_theory")).await.unwrap();
+        let redirected = source.links(&url("Graph_Theory")).await.unwrap();
+        assert_eq!(direct.len(), redirected.len());
+
+        assert!(matches!(
+            source.links(&url("Euler")).await,
+            Err(Error::NotFound(_))
+        ));
+    }
+}
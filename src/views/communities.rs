@@ -0,0 +1,91 @@
+use std::collections::HashSet;
+
+use egui::{Grid, RichText, ScrollArea, Ui};
+
+use crate::{communities::Communities, graph::Graph};
+
+use super::style::{community_color, header_accent};
+
+const HEADING: &str = "Communities";
+const LABEL_MAX_CHARS: usize = 24;
+const MSG_COLLAPSE: &str =
+    "members are only hidden behind the most central one, their links are not moved to it";
+
+pub struct State<'a> {
+    pub spacing: f32,
+    pub g: &'a Graph,
+    pub communities: &'a Communities,
+    pub color_by_community: &'a mut bool,
+    pub filter: Option<usize>,
+    pub collapsed: &'a HashSet<usize>,
+}
+
+/// Actions requested by the user in the communities list.
+pub enum Action {
+    Filter(Option<usize>),
+    ToggleCollapse(usize),
+}
+
+/// Draws communities labeled with their center article.
+///
+/// Communities of a single node are not listed.
+pub fn draw_view_communities(ui: &mut Ui, state: State) -> Option<Action> {
+    let mut action = None;
+
+    ui.add_space(state.spacing);
+    ui.label(header_accent(HEADING));
+    ui.add_space(state.spacing);
+
+    ui.checkbox(state.color_by_community, "color by community");
+    if state.filter.is_some() && ui.button("clear filter").clicked() {
+        action = Some(Action::Filter(None));
+    }
+
+    ui.separator();
+
+    ScrollArea::vertical()
+        .id_source("communities")
+        .show(ui, |ui| {
+            Grid::new("communities").striped(true).show(ui, |ui| {
+                state
+                    .communities
+                    .list()
+                    .iter()
+                    .filter(|c| c.members.len() > 1)
+                    .for_each(|c| {
+                        ui.label(RichText::new("●").color(community_color(c.id)));
+
+                        let label = state.g.g.node_weight(c.center).unwrap().payload().label();
+                        let short = label.chars().take(LABEL_MAX_CHARS).collect::<String>();
+                        let filtered = state.filter == Some(c.id);
+                        if ui
+                            .selectable_label(filtered, short)
+                            .on_hover_text(label)
+                            .clicked()
+                        {
+                            action = Some(Action::Filter(match filtered {
+                                true => None,
+                                false => Some(c.id),
+                            }));
+                        }
+
+                        ui.label(c.members.len().to_string());
+
+                        let collapse_label = match state.collapsed.contains(&c.id) {
+                            true => "restore",
+                            false => "collapse",
+                        };
+                        if ui
+                            .button(collapse_label)
+                            .on_hover_text(MSG_COLLAPSE)
+                            .clicked()
+                        {
+                            action = Some(Action::ToggleCollapse(c.id));
+                        }
+                        ui.end_row();
+                    });
+            });
+        });
+
+    action
+}
@@ -0,0 +1,74 @@
+use egui::{CollapsingHeader, Context, ScrollArea, TextEdit, Window};
+
+use crate::comparison::{Comparison, Target};
+
+const TITLE: &str = "compare languages";
+const HINT: &str = "languages, e.g. de fr ja";
+const MSG_LOADING: &str = "retrieving editions ...";
+
+pub struct State<'a> {
+    /// Title of the selected article, comparison is not available without it.
+    pub article: Option<String>,
+    pub langs: &'a mut String,
+    pub loading: bool,
+    pub comparison: Option<&'a Comparison>,
+}
+
+/// Draws comparison of the selected article links across languages. Returns true if the user
+/// requested a new comparison.
+pub fn draw_view_comparison(ctx: &Context, open: &mut bool, state: State) -> bool {
+    let mut compare = false;
+
+    Window::new(TITLE).open(open).show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            ui.add(TextEdit::singleline(&mut *state.langs).hint_text(HINT));
+
+            let label = match &state.article {
+                Some(article) => format!("compare {}", article),
+                None => "select an article".to_string(),
+            };
+            let enabled = state.article.is_some() && !state.loading;
+            if ui.add_enabled(enabled, egui::Button::new(label)).clicked() {
+                compare = true;
+            }
+        });
+
+        if state.loading {
+            ui.label(MSG_LOADING);
+            ui.spinner();
+            return;
+        }
+
+        let comparison = match state.comparison {
+            Some(comparison) => comparison,
+            None => return,
+        };
+
+        ui.separator();
+        ui.label(format!(
+            "{} in {}",
+            comparison.article,
+            comparison.langs.join(", ")
+        ));
+
+        ScrollArea::vertical().max_height(400.).show(ui, |ui| {
+            draw_targets(ui, "shared by all", comparison.shared());
+            comparison.langs.iter().for_each(|lang| {
+                draw_targets(ui, &format!("only in {}", lang), comparison.unique(lang));
+            });
+        });
+    });
+
+    compare
+}
+
+fn draw_targets<'a>(ui: &mut egui::Ui, title: &str, targets: impl Iterator<Item = &'a Target>) {
+    let targets = targets.collect::<Vec<_>>();
+    CollapsingHeader::new(format!("{} ({})", title, targets.len()))
+        .id_source(title)
+        .show(ui, |ui| {
+            targets.iter().for_each(|t| {
+                ui.label(&t.label).on_hover_text(&t.qid);
+            });
+        });
+}
@@ -0,0 +1,46 @@
+use egui::{Context, ScrollArea, Window};
+
+use crate::link::Link;
+
+const TITLE: &str = "choose article";
+const MSG_LOADING: &str = "retrieving meanings ...";
+
+pub struct State<'a> {
+    /// Title of the disambiguation page.
+    pub page: String,
+    pub loading: bool,
+    /// Articles listed on the page.
+    pub options: &'a [Link],
+}
+
+/// Draws articles listed on the disambiguation page. Returns the index of the option the user
+/// chose to replace the page with.
+pub fn draw_view_disambiguation(ctx: &Context, open: &mut bool, state: State) -> Option<usize> {
+    let mut chosen = None;
+
+    Window::new(TITLE).open(open).show(ctx, |ui| {
+        ui.label(format!("{} may refer to:", state.page));
+
+        if state.loading {
+            ui.label(MSG_LOADING);
+            ui.spinner();
+            return;
+        }
+
+        ui.separator();
+        ScrollArea::vertical().max_height(400.).show(ui, |ui| {
+            state.options.iter().enumerate().for_each(|(i, link)| {
+                let resp = ui.link(link.url.val_for_type().replace('_', " "));
+                let resp = match link.context.sentence.is_empty() {
+                    true => resp,
+                    false => resp.on_hover_text(&link.context.sentence),
+                };
+                if resp.clicked() {
+                    chosen = Some(i);
+                }
+            });
+        });
+    });
+
+    chosen
+}
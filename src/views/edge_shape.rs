@@ -0,0 +1,193 @@
+use egui::{show_tooltip_at_pointer, Color32, Context, Id, Pos2, RichText, Shape, Stroke, Vec2};
+use egui_graphs::{DisplayEdge, DrawContext, EdgeProps};
+use petgraph::{stable_graph::IndexType, EdgeType};
+
+use crate::{edge::Edge, link::LinkContext, node::Node, url::Url};
+
+use super::{node_shape::NodeShape, style::change_color};
+
+const EDGE_COLOR: Color32 = Color32::from_rgba_premultiplied(128, 128, 128, 64);
+const WIDTH: f32 = 1.;
+/// Multiplicity above this value does not make the edge thicker.
+const MAX_WIDTH_COUNT: usize = 10;
+const TIP_SIZE: f32 = 6.;
+const LOOP_SIZE: f32 = 6.;
+const FADED_OPACITY: f32 = 0.2;
+/// Max distance in points from the pointer to the edge line at which the edge is hovered.
+const HOVER_DISTANCE: f32 = 4.;
+const TOOLTIP_WIDTH: f32 = 400.;
+const HOVERED_EDGE: &str = "hovered_edge";
+
+type GraphNode<Ty, Ix> = egui_graphs::Node<Node, Edge, Ty, Ix, NodeShape>;
+
+/// Draws edge as a line which width is proportional to the link multiplicity. Edge is faded
+/// together with any of its nodes or when it is out of the highlighted neighbourhood.
+#[derive(Clone, Debug)]
+pub struct EdgeShape {
+    selected: bool,
+    width: f32,
+    faded: bool,
+    color: Color32,
+}
+
+impl From<EdgeProps<Edge>> for EdgeShape {
+    fn from(props: EdgeProps<Edge>) -> Self {
+        Self {
+            selected: props.selected,
+            width: width(&props.payload),
+            faded: props.payload.faded(),
+            color: color(&props.payload),
+        }
+    }
+}
+
+impl<Ty: EdgeType, Ix: IndexType> DisplayEdge<Node, Edge, Ty, Ix, NodeShape> for EdgeShape {
+    fn shapes(
+        &mut self,
+        start: &GraphNode<Ty, Ix>,
+        end: &GraphNode<Ty, Ix>,
+        ctx: &DrawContext,
+    ) -> Vec<Shape> {
+        if start.payload().hidden() || end.payload().hidden() {
+            return vec![];
+        }
+
+        let hovered = self.hovered(ctx, start, end);
+        if hovered {
+            set_hovered(ctx.ctx, start.payload().url(), end.payload().url());
+        }
+
+        let mut color = match self.selected || hovered {
+            true => ctx.ctx.style().visuals.selection.stroke.color,
+            false => self.color,
+        };
+        if self.faded || start.payload().faded() || end.payload().faded() {
+            color = color.gamma_multiply(FADED_OPACITY);
+        }
+        let stroke = Stroke::new(ctx.meta.canvas_to_screen_size(self.width), color);
+
+        let dir = end.location() - start.location();
+        if dir.length() < f32::EPSILON {
+            let boundary = start.display().boundary_point(Vec2::new(0., -1.));
+            let radius = ctx.meta.canvas_to_screen_size(LOOP_SIZE);
+            let center = ctx.meta.canvas_to_screen_pos(boundary) - Vec2::new(0., radius);
+            return vec![Shape::circle_stroke(center, radius, stroke)];
+        }
+
+        let dir = dir.normalized();
+        let from = ctx
+            .meta
+            .canvas_to_screen_pos(start.display().boundary_point(dir));
+        let to = ctx
+            .meta
+            .canvas_to_screen_pos(end.display().boundary_point(-dir));
+
+        let mut shapes = vec![Shape::line_segment([from, to], stroke)];
+        if ctx.is_directed {
+            let tip = ctx.meta.canvas_to_screen_size(TIP_SIZE);
+            let back = to - dir * tip;
+            let side = dir.rot90() * tip / 2.;
+            shapes.push(Shape::convex_polygon(
+                vec![to, back + side, back - side],
+                color,
+                Stroke::NONE,
+            ));
+        }
+
+        shapes
+    }
+
+    fn update(&mut self, state: &EdgeProps<Edge>) {
+        self.selected = state.selected;
+        self.width = width(&state.payload);
+        self.faded = state.payload.faded();
+        self.color = color(&state.payload);
+    }
+
+    fn is_inside(&self, start: &GraphNode<Ty, Ix>, end: &GraphNode<Ty, Ix>, pos: Pos2) -> bool {
+        let (a, b) = (start.location(), end.location());
+        let ab = b - a;
+        if ab.length_sq() < f32::EPSILON {
+            return false;
+        }
+
+        let t = ((pos - a).dot(ab) / ab.length_sq()).clamp(0., 1.);
+        let closest = a + ab * t;
+        (pos - closest).length() <= self.width.max(WIDTH * 2.)
+    }
+}
+
+impl EdgeShape {
+    fn hovered<Ty: EdgeType, Ix: IndexType>(
+        &self,
+        ctx: &DrawContext,
+        start: &GraphNode<Ty, Ix>,
+        end: &GraphNode<Ty, Ix>,
+    ) -> bool {
+        let pointer = match ctx.ctx.input(|i| i.pointer.hover_pos()) {
+            Some(pointer) => pointer,
+            None => return false,
+        };
+
+        let a = ctx.meta.canvas_to_screen_pos(start.location());
+        let b = ctx.meta.canvas_to_screen_pos(end.location());
+        let ab = b - a;
+        if ab.length_sq() < f32::EPSILON {
+            return false;
+        }
+
+        let t = ((pointer - a).dot(ab) / ab.length_sq()).clamp(0., 1.);
+        let width = ctx.meta.canvas_to_screen_size(self.width);
+        (pointer - (a + ab * t)).length() <= width.max(HOVER_DISTANCE)
+    }
+}
+
+/// Remembers articles of the hovered edge, the context of the link is shown once the graph is
+/// drawn. Several edges can be hovered at once, only the first one drawn is kept.
+fn set_hovered(ctx: &Context, from: &Url, to: &Url) {
+    let id = Id::new(HOVERED_EDGE);
+    let frame = ctx.frame_nr();
+    ctx.data_mut(|d| {
+        let kept = d.get_temp::<(u64, Url, Url)>(id);
+        if !matches!(kept, Some((kept_frame, _, _)) if kept_frame == frame) {
+            d.insert_temp(id, (frame, from.clone(), to.clone()));
+        }
+    });
+}
+
+/// Articles of the edge hovered in this frame.
+pub fn hovered(ctx: &Context) -> Option<(Url, Url)> {
+    let frame = ctx.frame_nr();
+    ctx.data(|d| d.get_temp::<(u64, Url, Url)>(Id::new(HOVERED_EDGE)))
+        .filter(|(hovered_frame, _, _)| *hovered_frame == frame)
+        .map(|(_, from, to)| (from, to))
+}
+
+/// Shows where and how the link appears in the article.
+pub fn show_context(ctx: &Context, occurrences: &[LinkContext]) {
+    show_tooltip_at_pointer(ctx, Id::new("edge_context"), |ui| {
+        ui.set_max_width(TOOLTIP_WIDTH);
+        occurrences.iter().for_each(|o| {
+            ui.label(
+                RichText::new(format!(
+                    "{}: {}",
+                    o.section.as_deref().unwrap_or("lead"),
+                    o.anchor
+                ))
+                .strong(),
+            );
+            if !o.sentence.is_empty() {
+                ui.label(&o.sentence);
+            }
+        });
+    });
+}
+
+/// Links added or removed between compared sessions are green or red.
+fn color(e: &Edge) -> Color32 {
+    e.change().map_or(EDGE_COLOR, change_color)
+}
+
+pub fn width(e: &Edge) -> f32 {
+    WIDTH * e.count().min(MAX_WIDTH_COUNT) as f32
+}
@@ -0,0 +1,61 @@
+use egui::{Grid, RichText, ScrollArea, Ui};
+
+use crate::entities::Entities;
+
+use super::style::{class_color, header_accent};
+
+const HEADING: &str = "Entities";
+const LABEL_MAX_CHARS: usize = 24;
+
+pub struct State<'a> {
+    pub spacing: f32,
+    pub entities: &'a Entities,
+    pub color_by_class: &'a mut bool,
+    pub filter: Option<&'a str>,
+}
+
+/// Draws wikidata classes of the articles. Returns the class filter if the user changed it.
+pub fn draw_view_entities(ui: &mut Ui, state: State) -> Option<Option<String>> {
+    let mut filter = None;
+
+    ui.add_space(state.spacing);
+    ui.label(header_accent(HEADING));
+    ui.add_space(state.spacing);
+
+    ui.checkbox(state.color_by_class, "color by instance of");
+    if state.filter.is_some() && ui.button("clear filter").clicked() {
+        filter = Some(None);
+    }
+
+    ui.separator();
+
+    ScrollArea::vertical().id_source("entities").show(ui, |ui| {
+        Grid::new("entities").striped(true).show(ui, |ui| {
+            state
+                .entities
+                .classes()
+                .into_iter()
+                .for_each(|(class, count)| {
+                    ui.label(RichText::new("●").color(class_color(&class)));
+
+                    let short = class.chars().take(LABEL_MAX_CHARS).collect::<String>();
+                    let filtered = state.filter == Some(class.as_str());
+                    if ui
+                        .selectable_label(filtered, short)
+                        .on_hover_text(&class)
+                        .clicked()
+                    {
+                        filter = Some(match filtered {
+                            true => None,
+                            false => Some(class.clone()),
+                        });
+                    }
+
+                    ui.label(count.to_string());
+                    ui.end_row();
+                });
+        });
+    });
+
+    filter
+}
@@ -0,0 +1,47 @@
+use egui::{Context, DragValue, TextEdit, Window};
+
+use crate::export::Options;
+
+const TITLE: &str = "export image";
+const HINT: &str = "graph.png or graph.svg";
+
+pub struct State<'a> {
+    /// File to write, the format is taken from the extension.
+    pub path: &'a mut String,
+    pub options: &'a mut Options,
+    /// Whole graph is exported instead of the visible part.
+    pub whole_graph: &'a mut bool,
+    /// Result of the last export.
+    pub message: Option<&'a str>,
+}
+
+/// Draws export settings. Returns true if the user requested the export.
+pub fn draw_view_export(ctx: &Context, open: &mut bool, state: State) -> bool {
+    let mut export = false;
+
+    Window::new(TITLE).open(open).show(ctx, |ui| {
+        ui.add(TextEdit::singleline(&mut *state.path).hint_text(HINT));
+        ui.horizontal(|ui| {
+            ui.label("width");
+            ui.add(
+                DragValue::new(&mut state.options.width)
+                    .clamp_range(100..=16384)
+                    .suffix(" px"),
+            );
+        });
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut *state.whole_graph, false, "visible area");
+            ui.radio_value(&mut *state.whole_graph, true, "whole graph");
+        });
+        ui.checkbox(&mut state.options.legend, "legend");
+
+        if ui.button("export").clicked() {
+            export = true;
+        }
+        if let Some(message) = state.message {
+            ui.label(message);
+        }
+    });
+
+    export
+}
@@ -1,16 +1,16 @@
 use crossbeam::channel::{Receiver, Sender};
-use egui::{Color32, Ui};
-use egui_graphs::{events::Event, Graph, SettingsInteraction, SettingsNavigation, SettingsStyle};
-use petgraph::Directed;
+use egui::Ui;
+use egui_graphs::{events::Event, SettingsInteraction, SettingsNavigation, SettingsStyle};
 
-use crate::node::Node;
+use crate::{edge::Edge, graph::Graph, url::Url};
+
+use super::edge_shape;
 
 const EDGE_WEIGHT: f32 = 0.05;
-const EDGE_COLOR: Color32 = Color32::from_rgba_premultiplied(128, 128, 128, 64);
 
 pub struct State<'a> {
     pub loading: bool,
-    pub g: &'a mut Graph<Node, (), Directed>,
+    pub g: &'a mut Graph,
     pub sender: Sender<Event>,
     pub receiver: Receiver<Event>,
 }
@@ -36,4 +36,20 @@ pub fn draw_view_graph(ui: &mut Ui, state: State) {
     }
 
     ui.add(&mut w);
+
+    if let Some((from, to)) = edge_shape::hovered(ui.ctx()) {
+        if let Some(e) = find_edge(state.g, &from, &to) {
+            edge_shape::show_context(ui.ctx(), e.occurrences());
+        }
+    }
+}
+
+/// Payload of the edge between the articles.
+fn find_edge<'a>(g: &'a Graph, from: &Url, to: &Url) -> Option<&'a Edge> {
+    let idx = |url: &Url| {
+        g.g.node_indices()
+            .find(|idx| g.g.node_weight(*idx).unwrap().payload().url() == url)
+    };
+    let e = g.g.find_edge(idx(from)?, idx(to)?)?;
+    Some(g.g.edge_weight(e).unwrap().payload())
 }
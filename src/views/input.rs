@@ -1,14 +1,20 @@
 use egui::{Align, Response, TextEdit, Ui};
 
+use crate::revision;
+
 use super::style::{header_accent, COLOR_ERROR, FONT_INPUT};
 
 const HEADING: &str = "Wiki Links";
 const DESCRIPTION : &str = "A demo application for egui_graphs widget. This application will display a graph of a wikipedia article links.";
 const TOOLTIP: &str = "enter a Wikipedia article url and hit enter";
 const ERROR_MSG: &str = "enter a valid wikipedia article url";
+const AS_OF_HINT: &str = "as of date, e.g. 2015-01-01, empty for live articles";
 
+/// Draws the article url input with an optional date to explore the articles as of. An invalid
+/// date is shown in the error color.
 pub fn draw_view_input(
     root_article_url: &mut String,
+    as_of: &mut String,
     ui: &mut Ui,
     url_valid: bool,
     size_section: f32,
@@ -38,7 +44,18 @@ pub fn draw_view_input(
         }
 
         let input_response = input.show(ui).response;
-        input_response.request_focus();
+
+        ui.add_space(size_margin / 4.);
+        let as_of_valid = as_of.trim().is_empty() || revision::parse_date(as_of).is_some();
+        let mut as_of_input = TextEdit::singleline(as_of)
+            .hint_text(AS_OF_HINT)
+            .horizontal_align(Align::Center);
+        if !as_of_valid {
+            as_of_input = as_of_input.text_color(COLOR_ERROR);
+        }
+        if !ui.add(as_of_input).has_focus() {
+            input_response.request_focus();
+        }
 
         if !url_valid {
             ui.add_space(size_margin / 4.);
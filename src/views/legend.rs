@@ -0,0 +1,66 @@
+use egui::{vec2, CollapsingHeader, Color32, Grid, Sense, Ui};
+
+use crate::url::Type;
+
+use super::{
+    node_shape::Form,
+    style::{header_accent, Theme},
+};
+
+const HEADING: &str = "Legend";
+const ICON_RADIUS: f32 = 6.;
+
+pub struct State<'a> {
+    pub spacing: f32,
+    pub theme: &'a Theme,
+}
+
+/// Draws form and color of every url type and explains the node decorations.
+pub fn draw_view_legend(ui: &mut Ui, state: State) {
+    ui.add_space(state.spacing);
+    ui.label(header_accent(HEADING));
+    ui.add_space(state.spacing);
+
+    CollapsingHeader::new("node types")
+        .id_source("legend")
+        .show(ui, |ui| {
+            Grid::new("legend").show(ui, |ui| {
+                Type::all().iter().for_each(|t| {
+                    icon(ui, Form::of(t), state.theme.type_color(t), false);
+                    ui.label(title(t));
+                    ui.end_row();
+                });
+
+                let color = state.theme.article;
+                icon(ui, Form::Circle, color, true);
+                ui.label("disambiguation page");
+                ui.end_row();
+
+                icon(ui, Form::Circle, color.gamma_multiply(0.2), false);
+                ui.label("filtered out");
+                ui.end_row();
+
+                ui.monospace("+n");
+                ui.label("n more hidden in the node, their links are not moved");
+                ui.end_row();
+            });
+            ui.weak("size and color can encode a metric, color a community or a class instead");
+        });
+}
+
+fn icon(ui: &mut Ui, form: Form, color: Color32, hollow: bool) {
+    let (rect, _) = ui.allocate_exact_size(vec2(ICON_RADIUS, ICON_RADIUS) * 2.5, Sense::hover());
+    ui.painter()
+        .add(form.shape(rect.center(), ICON_RADIUS, color, hollow));
+}
+
+pub fn title(t: &Type) -> &'static str {
+    match t {
+        Type::Article => "article",
+        Type::Category => "category",
+        Type::Missing => "missing article",
+        Type::File => "media file",
+        Type::ExternalArticle => "paper on arxiv or doi",
+        Type::Other => "other link",
+    }
+}
@@ -0,0 +1,65 @@
+use egui::{ScrollArea, TextEdit, Ui};
+use petgraph::stable_graph::NodeIndex;
+
+use crate::graph::Graph;
+
+use super::style::header_accent;
+
+const HEADING: &str = "Link Search";
+const HINT: &str = "search anchors and sentences";
+const MAX_RESULTS: usize = 50;
+
+pub struct State<'a> {
+    pub spacing: f32,
+    pub g: &'a Graph,
+    pub query: &'a mut String,
+}
+
+/// Draws search over the context of every link. Returns link target which was clicked.
+pub fn draw_view_link_search(ui: &mut Ui, state: State) -> Option<NodeIndex> {
+    let mut clicked = None;
+
+    ui.add_space(state.spacing);
+    ui.label(header_accent(HEADING));
+    ui.add_space(state.spacing);
+
+    ui.add(TextEdit::singleline(state.query).hint_text(HINT));
+
+    let query = state.query.trim().to_lowercase();
+    if query.is_empty() {
+        return None;
+    }
+
+    ui.separator();
+
+    let g = &state.g.g;
+    let label = |idx: NodeIndex| g.node_weight(idx).unwrap().payload().label();
+    ScrollArea::vertical()
+        .id_source("link_search")
+        .show(ui, |ui| {
+            g.edge_indices()
+                .flat_map(|e| {
+                    let (source, target) = g.edge_endpoints(e).unwrap();
+                    g.edge_weight(e)
+                        .unwrap()
+                        .payload()
+                        .occurrences()
+                        .iter()
+                        .filter(|o| o.matches(&query))
+                        .map(move |o| (source, target, o))
+                })
+                .take(MAX_RESULTS)
+                .for_each(|(source, target, o)| {
+                    if ui
+                        .link(format!("{} → {}", label(source), label(target)))
+                        .clicked()
+                    {
+                        clicked = Some(target);
+                    }
+                    ui.weak(format!("{}: {}", o.anchor, o.sentence));
+                    ui.add_space(state.spacing / 2.);
+                });
+        });
+
+    clicked
+}
@@ -0,0 +1,117 @@
+use egui::{ComboBox, Grid, ScrollArea, Ui};
+use petgraph::stable_graph::NodeIndex;
+
+use crate::{
+    communities::Community,
+    graph::Graph,
+    metrics::{Metric, Metrics},
+};
+
+use super::style::header_accent;
+
+const HEADING: &str = "Top Nodes";
+const TOP_NODES: usize = 20;
+const LABEL_MAX_CHARS: usize = 24;
+
+/// How the chosen metric is shown on the graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Encoding {
+    #[default]
+    None,
+    Size,
+    Color,
+}
+
+impl Encoding {
+    fn title(&self) -> &'static str {
+        match self {
+            Encoding::None => "none",
+            Encoding::Size => "size",
+            Encoding::Color => "color",
+        }
+    }
+}
+
+pub struct State<'a> {
+    pub spacing: f32,
+    pub g: &'a Graph,
+    pub metrics: &'a Metrics,
+    /// Only members of this community are listed.
+    pub filter: Option<&'a Community>,
+    pub sort_by: &'a mut Metric,
+    pub encoded: &'a mut Metric,
+    pub encoding: &'a mut Encoding,
+}
+
+/// Draws encoding controls and the table of top nodes sorted by the chosen metric.
+///
+/// Returns the node which was clicked in the table.
+pub fn draw_view_metrics(ui: &mut Ui, state: State) -> Option<NodeIndex> {
+    let mut clicked = None;
+
+    ui.add_space(state.spacing);
+    ui.label(header_accent(HEADING));
+    ui.add_space(state.spacing);
+
+    ui.horizontal(|ui| {
+        ui.label("encode");
+        ComboBox::from_id_source("encoded_metric")
+            .selected_text(state.encoded.title())
+            .show_ui(ui, |ui| {
+                Metric::all().iter().for_each(|m| {
+                    ui.selectable_value(state.encoded, *m, m.title());
+                });
+            });
+        ui.label("as");
+        ComboBox::from_id_source("encoding")
+            .selected_text(state.encoding.title())
+            .show_ui(ui, |ui| {
+                [Encoding::None, Encoding::Size, Encoding::Color]
+                    .iter()
+                    .for_each(|e| {
+                        ui.selectable_value(state.encoding, *e, e.title());
+                    });
+            });
+    });
+
+    ui.separator();
+
+    ScrollArea::both().show(ui, |ui| {
+        Grid::new("top_nodes").striped(true).show(ui, |ui| {
+            ui.label("node");
+            Metric::all().iter().for_each(|m| {
+                if ui
+                    .selectable_label(*state.sort_by == *m, m.title())
+                    .clicked()
+                {
+                    *state.sort_by = *m;
+                }
+            });
+            ui.end_row();
+
+            state
+                .metrics
+                .top(*state.sort_by, TOP_NODES, |idx| {
+                    state.filter.map_or(true, |c| c.members.contains(idx))
+                })
+                .iter()
+                .for_each(|(idx, m)| {
+                    let label = match state.g.g.node_weight(*idx) {
+                        Some(n) => n.payload().label(),
+                        None => return,
+                    };
+                    let short = label.chars().take(LABEL_MAX_CHARS).collect::<String>();
+                    if ui.link(short).on_hover_text(label).clicked() {
+                        clicked = Some(*idx);
+                    }
+
+                    Metric::all().iter().for_each(|metric| {
+                        ui.label(m.format(*metric));
+                    });
+                    ui.end_row();
+                });
+        });
+    });
+
+    clicked
+}
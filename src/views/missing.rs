@@ -0,0 +1,78 @@
+use egui::{Color32, Grid, RichText, ScrollArea, Ui};
+use petgraph::{stable_graph::NodeIndex, Direction::Incoming};
+
+use crate::{graph::Graph, url};
+
+use super::style::header_accent;
+
+const HEADING: &str = "Missing Articles";
+const MAX_RESULTS: usize = 50;
+
+pub struct State<'a> {
+    pub spacing: f32,
+    pub g: &'a Graph,
+    pub color: Color32,
+}
+
+/// Draws red links of the explored articles ordered by the number of articles linking to them.
+/// Returns the missing article which was clicked.
+pub fn draw_view_missing(ui: &mut Ui, state: State) -> Option<NodeIndex> {
+    let mut clicked = None;
+
+    ui.add_space(state.spacing);
+    ui.label(header_accent(HEADING));
+    ui.add_space(state.spacing);
+
+    let missing = most_linked(state.g);
+    if missing.is_empty() {
+        ui.weak("no red links found");
+        return None;
+    }
+
+    if ui.button("copy list").clicked() {
+        let list = missing
+            .iter()
+            .map(|(_, title, count)| format!("{}\t{}", title, count))
+            .collect::<Vec<_>>()
+            .join("\n");
+        ui.output_mut(|o| o.copied_text = list);
+    }
+
+    ui.separator();
+
+    ScrollArea::vertical().id_source("missing").show(ui, |ui| {
+        Grid::new("missing").striped(true).show(ui, |ui| {
+            missing
+                .iter()
+                .take(MAX_RESULTS)
+                .for_each(|(idx, title, count)| {
+                    let text = RichText::new(title).color(state.color);
+                    if ui.link(text).clicked() {
+                        clicked = Some(*idx);
+                    }
+                    ui.label(count.to_string());
+                    ui.end_row();
+                });
+        });
+    });
+
+    clicked
+}
+
+/// Missing articles with their titles and the number of articles linking to them, most linked
+/// first.
+fn most_linked(g: &Graph) -> Vec<(NodeIndex, String, usize)> {
+    let mut missing =
+        g.g.node_indices()
+            .filter_map(|idx| {
+                let n = g.g.node_weight(idx).unwrap().payload();
+                if n.url().url_type() != url::Type::Missing {
+                    return None;
+                }
+                let count = g.g.neighbors_directed(idx, Incoming).count();
+                Some((idx, n.label().replace('_', " "), count))
+            })
+            .collect::<Vec<_>>();
+    missing.sort_by(|(_, lt, lc), (_, rt, rc)| rc.cmp(lc).then(lt.cmp(rt)));
+    missing
+}
@@ -1,4 +1,21 @@
+pub mod communities;
+pub mod comparison;
+pub mod disambiguation;
+pub mod edge_shape;
+pub mod entities;
+pub mod export;
 pub mod graph;
 pub mod input;
+pub mod legend;
+pub mod link_search;
+pub mod metrics;
+pub mod missing;
+pub mod node_shape;
+pub mod palette;
+pub mod prune;
+pub mod settings;
+pub mod snapshots;
 pub mod style;
+pub mod tabs;
+pub mod timeline;
 pub mod toolbox;
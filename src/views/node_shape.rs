@@ -0,0 +1,321 @@
+use std::collections::HashMap;
+
+use egui::{
+    epaint::{CircleShape, RectShape, TextShape},
+    Color32, Context, FontFamily, FontId, Id, Pos2, Rect, Shape, Stroke, TextureId, Vec2,
+};
+use egui_graphs::{DisplayNode, DrawContext, NodeProps};
+use petgraph::{
+    stable_graph::{IndexType, NodeIndex},
+    EdgeType,
+};
+
+use crate::{
+    node::Node,
+    url::{Type, Url},
+};
+
+use super::style::change_color;
+
+const RADIUS: f32 = 5.;
+const LABEL_MIN_SIZE: f32 = 10.;
+const FADED_OPACITY: f32 = 0.2;
+const HOLLOW_WIDTH: f32 = 1.5;
+/// Side of the thumbnail relative to the node radius.
+const THUMBNAIL_SCALE: f32 = 4.;
+/// Node radius on the screen from which every label is drawn.
+const LABEL_RADIUS: f32 = 12.;
+/// Node radius on the screen from which labels of the best connected nodes are drawn.
+const LABELED_RADIUS: f32 = 2.;
+const HOVERED_ID: &str = "hovered_node";
+const TRANSFORM_ID: &str = "canvas_transform";
+
+/// Outline of the node which tells its url type.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Form {
+    Circle,
+    Square,
+    Diamond,
+}
+
+impl Form {
+    pub fn of(t: &Type) -> Self {
+        match t {
+            Type::Article | Type::Missing | Type::Other => Form::Circle,
+            Type::Category => Form::Square,
+            Type::File | Type::ExternalArticle => Form::Diamond,
+        }
+    }
+
+    /// Distance from the center to the boundary in the direction of the unit vector.
+    fn extent(&self, dir: Vec2, radius: f32) -> f32 {
+        match self {
+            Form::Circle => radius,
+            Form::Square => radius / dir.x.abs().max(dir.y.abs()),
+            Form::Diamond => radius / (dir.x.abs() + dir.y.abs()),
+        }
+    }
+
+    fn contains(&self, d: Vec2, radius: f32) -> bool {
+        match self {
+            Form::Circle => d.length() <= radius,
+            Form::Square => d.x.abs().max(d.y.abs()) <= radius,
+            Form::Diamond => d.x.abs() + d.y.abs() <= radius,
+        }
+    }
+
+    /// Filled or hollow outline, also used to draw the legend.
+    pub fn shape(&self, center: Pos2, radius: f32, color: Color32, hollow: bool) -> Shape {
+        let (fill, stroke) = match hollow {
+            true => (Color32::TRANSPARENT, Stroke::new(HOLLOW_WIDTH, color)),
+            false => (color, Stroke::NONE),
+        };
+        match self {
+            Form::Circle => CircleShape {
+                center,
+                radius,
+                fill,
+                stroke,
+            }
+            .into(),
+            Form::Square => RectShape::new(
+                Rect::from_center_size(center, Vec2::splat(radius * 2.)),
+                0.,
+                fill,
+                stroke,
+            )
+            .into(),
+            Form::Diamond => Shape::convex_polygon(
+                vec![
+                    center + Vec2::new(0., -radius),
+                    center + Vec2::new(radius, 0.),
+                    center + Vec2::new(0., radius),
+                    center + Vec2::new(-radius, 0.),
+                ],
+                fill,
+                stroke,
+            ),
+        }
+    }
+}
+
+/// Nodes which are not articles are smaller.
+pub fn type_size(t: &Type) -> f32 {
+    match t {
+        Type::Article | Type::Category | Type::File | Type::ExternalArticle => 1.,
+        Type::Missing => 0.8,
+        Type::Other => 0.6,
+    }
+}
+
+/// Draws node in the form of its url type which size and color are taken from the node payload.
+/// Articles added or removed between compared sessions are green or red. Disambiguation pages
+/// are hollow. Media with a loaded thumbnail are drawn as the thumbnail.
+///
+/// Labels depend on the zoom: zoomed out only selected, hovered and labeled nodes have them.
+#[derive(Clone, Debug)]
+pub struct NodeShape {
+    url: Url,
+    pos: Pos2,
+    selected: bool,
+    dragged: bool,
+    label: String,
+    radius: f32,
+    color: Option<egui::Color32>,
+    faded: bool,
+    hidden: bool,
+    collapsed: usize,
+    form: Form,
+    disambiguation: bool,
+    thumbnail: Option<(TextureId, Vec2)>,
+    labeled: bool,
+}
+
+impl From<NodeProps<Node>> for NodeShape {
+    fn from(props: NodeProps<Node>) -> Self {
+        Self {
+            url: props.payload.url().clone(),
+            pos: props.location,
+            selected: props.selected,
+            dragged: props.dragged,
+            label: props.label,
+            radius: radius(&props.payload),
+            color: props
+                .payload
+                .change()
+                .map(change_color)
+                .or(props.payload.color()),
+            faded: props.payload.faded(),
+            hidden: props.payload.hidden(),
+            collapsed: props.payload.collapsed(),
+            form: Form::of(&props.payload.url().url_type()),
+            disambiguation: props.payload.disambiguation(),
+            thumbnail: props.payload.thumbnail(),
+            labeled: props.payload.labeled(),
+        }
+    }
+}
+
+impl NodeShape {
+    /// Point on the node boundary in the given direction.
+    pub fn boundary_point(&self, dir: Vec2) -> Pos2 {
+        let dir = dir.normalized();
+        self.pos + dir * self.form.extent(dir, self.radius)
+    }
+
+    fn hovered(&self, ctx: &DrawContext, center: Pos2, radius: f32) -> bool {
+        match ctx.ctx.input(|i| i.pointer.hover_pos()) {
+            Some(pointer) => self.form.contains(pointer - center, radius),
+            None => false,
+        }
+    }
+
+    fn show_label(&self, radius: f32, hovered: bool) -> bool {
+        if self.selected || self.dragged || hovered {
+            return true;
+        }
+        !self.faded && (radius >= LABEL_RADIUS || (self.labeled && radius >= LABELED_RADIUS))
+    }
+}
+
+/// Part of the canvas shown in the screen rect of the graph in the last drawn frame.
+pub fn visible_area(ctx: &Context, screen: Rect) -> Option<Rect> {
+    let frame = ctx.frame_nr();
+    ctx.data(|d| d.get_temp::<(u64, f32, Vec2)>(Id::new(TRANSFORM_ID)))
+        .filter(|(drawn, _, _)| *drawn == frame)
+        .map(|(_, zoom, pan)| {
+            Rect::from_min_max(
+                ((screen.min - pan).to_vec2() / zoom).to_pos2(),
+                ((screen.max - pan).to_vec2() / zoom).to_pos2(),
+            )
+        })
+}
+
+/// Node hovered in the last drawn frame, found by its url as the shape does not know its index.
+pub fn hovered_node(ctx: &Context, node_by_url: &HashMap<Url, NodeIndex>) -> Option<NodeIndex> {
+    let frame = ctx.frame_nr();
+    ctx.data(|d| d.get_temp::<(u64, Url)>(Id::new(HOVERED_ID)))
+        .filter(|(drawn, _)| *drawn == frame)
+        .and_then(|(_, url)| node_by_url.get(&url).copied())
+}
+
+impl<E: Clone, Ty: EdgeType, Ix: IndexType> DisplayNode<Node, E, Ty, Ix> for NodeShape {
+    fn closest_boundary_point(&self, dir: Vec2) -> Pos2 {
+        self.boundary_point(dir)
+    }
+
+    fn shapes(&mut self, ctx: &DrawContext) -> Vec<Shape> {
+        if self.hidden {
+            return vec![];
+        }
+
+        let frame = ctx.ctx.frame_nr();
+        ctx.ctx.data_mut(|d| {
+            d.insert_temp(Id::new(TRANSFORM_ID), (frame, ctx.meta.zoom, ctx.meta.pan))
+        });
+
+        let center = ctx.meta.canvas_to_screen_pos(self.pos);
+        let radius = ctx.meta.canvas_to_screen_size(self.radius);
+
+        let hovered = self.hovered(ctx, center, radius);
+        if hovered {
+            ctx.ctx
+                .data_mut(|d| d.insert_temp(Id::new(HOVERED_ID), (frame, self.url.clone())));
+        }
+
+        let visuals = &ctx.ctx.style().visuals;
+        let mut color = match self.selected || self.dragged {
+            true => visuals.selection.stroke.color,
+            false => self
+                .color
+                .unwrap_or(visuals.widgets.inactive.fg_stroke.color),
+        };
+        if self.faded {
+            color = color.gamma_multiply(FADED_OPACITY);
+        }
+
+        let mut shapes = vec![];
+        match self.thumbnail {
+            Some((texture, size)) => {
+                let side = radius * THUMBNAIL_SCALE;
+                let rect = Rect::from_center_size(center, size * side / size.max_elem());
+                let uv = Rect::from_min_max(Pos2::ZERO, Pos2::new(1., 1.));
+                let tint = match self.faded {
+                    true => Color32::WHITE.gamma_multiply(FADED_OPACITY),
+                    false => Color32::WHITE,
+                };
+                shapes.push(Shape::image(texture, rect, uv, tint));
+                if self.selected {
+                    shapes.push(Shape::rect_stroke(
+                        rect.expand(radius / 2.),
+                        0.,
+                        Stroke::new(1., color),
+                    ));
+                }
+            }
+            None => {
+                shapes.push(self.form.shape(center, radius, color, self.disambiguation));
+                if self.selected {
+                    shapes.push(self.form.shape(center, radius * 1.5, color, true));
+                }
+            }
+        }
+
+        if self.collapsed > 0 {
+            let galley = ctx.ctx.fonts(|f| {
+                f.layout_no_wrap(
+                    format!("+{}", self.collapsed),
+                    FontId::new(radius.max(LABEL_MIN_SIZE), FontFamily::Monospace),
+                    color,
+                )
+            });
+            let badge_pos = Pos2::new(center.x + radius, center.y - galley.size().y / 2.);
+            shapes.push(TextShape::new(badge_pos, galley, color).into());
+        }
+
+        if !self.show_label(radius, hovered) {
+            return shapes;
+        }
+
+        let galley = ctx.ctx.fonts(|f| {
+            f.layout_no_wrap(
+                self.label.clone(),
+                FontId::new(radius.max(LABEL_MIN_SIZE), FontFamily::Monospace),
+                color,
+            )
+        });
+        let label_pos = Pos2::new(center.x - galley.size().x / 2., center.y - radius * 3.);
+        shapes.push(TextShape::new(label_pos, galley, color).into());
+
+        shapes
+    }
+
+    fn update(&mut self, state: &NodeProps<Node>) {
+        self.pos = state.location;
+        self.selected = state.selected;
+        self.dragged = state.dragged;
+        self.label = state.label.clone();
+        self.radius = radius(&state.payload);
+        self.color = state
+            .payload
+            .change()
+            .map(change_color)
+            .or(state.payload.color());
+        self.faded = state.payload.faded();
+        self.hidden = state.payload.hidden();
+        self.collapsed = state.payload.collapsed();
+        self.form = Form::of(&state.payload.url().url_type());
+        self.disambiguation = state.payload.disambiguation();
+        self.thumbnail = state.payload.thumbnail();
+        self.labeled = state.payload.labeled();
+    }
+
+    fn is_inside(&self, pos: Pos2) -> bool {
+        !self.hidden && self.form.contains(pos - self.pos, self.radius)
+    }
+}
+
+/// Collapsed nodes grow with the number of nodes collapsed into them.
+pub fn radius(n: &Node) -> f32 {
+    RADIUS * n.size() * type_size(&n.url().url_type()) * (1. + (n.collapsed() as f32 + 1.).ln())
+}
@@ -0,0 +1,66 @@
+use egui::{Align2, Context, Key, ScrollArea, TextEdit, Window};
+
+use crate::{command::Command, keymap::Keymap};
+
+use super::style::FONT_INPUT;
+
+const TITLE: &str = "commands";
+const HINT: &str = "type to filter commands";
+
+pub struct State<'a> {
+    pub query: &'a mut String,
+    pub keymap: &'a Keymap,
+}
+
+/// Draws command palette and returns picked command.
+///
+/// `open` is set to false when the user closes the palette with `Escape` or picks a command.
+pub fn draw_view_palette(ctx: &Context, open: &mut bool, state: State) -> Option<Command> {
+    let mut picked = None;
+
+    let query = state.query.to_lowercase();
+    let commands = Command::all()
+        .iter()
+        .filter(|cmd| cmd.title().contains(query.as_str()))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    Window::new(TITLE)
+        .collapsible(false)
+        .resizable(false)
+        .title_bar(false)
+        .anchor(Align2::CENTER_TOP, [0., 40.])
+        .show(ctx, |ui| {
+            let input = TextEdit::singleline(&mut *state.query)
+                .hint_text(HINT)
+                .font(FONT_INPUT)
+                .desired_width(f32::INFINITY)
+                .show(ui)
+                .response;
+            input.request_focus();
+
+            if input.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+                picked = commands.first().cloned();
+            }
+
+            ui.separator();
+
+            ScrollArea::vertical().max_height(300.).show(ui, |ui| {
+                commands.iter().for_each(|cmd| {
+                    ui.horizontal(|ui| {
+                        if ui.selectable_label(false, cmd.title()).clicked() {
+                            picked = Some(*cmd);
+                        }
+                        ui.weak(state.keymap.describe(*cmd));
+                    });
+                });
+            });
+        });
+
+    if picked.is_some() || ctx.input(|i| i.key_pressed(Key::Escape)) {
+        *open = false;
+        state.query.clear();
+    }
+
+    picked
+}
@@ -0,0 +1,48 @@
+use egui::Ui;
+
+use crate::url::Type;
+
+use super::{legend, style::header_accent};
+
+const HEADING: &str = "Prune";
+
+pub struct State {
+    pub spacing: f32,
+    /// Graph is being retrieved, nodes can not be removed.
+    pub loading: bool,
+}
+
+/// Removals requested by the user.
+pub enum Action {
+    Leaves,
+    Type(Type),
+}
+
+/// Draws buttons removing nodes from the whole graph. The article the exploration started from
+/// is always kept.
+pub fn draw_view_prune(ui: &mut Ui, state: State) -> Option<Action> {
+    let mut action = None;
+
+    ui.add_space(state.spacing);
+    ui.label(header_accent(HEADING));
+    ui.add_space(state.spacing);
+
+    ui.add_enabled_ui(!state.loading, |ui| {
+        if ui
+            .button("leaves")
+            .on_hover_text("delete nodes with a single neighbour")
+            .clicked()
+        {
+            action = Some(Action::Leaves);
+        }
+        ui.horizontal_wrapped(|ui| {
+            Type::all().iter().for_each(|t| {
+                if ui.button(legend::title(t)).clicked() {
+                    action = Some(Action::Type(*t));
+                }
+            });
+        });
+    });
+
+    action
+}
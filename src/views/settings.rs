@@ -0,0 +1,115 @@
+use egui::{Context, Grid, Window};
+
+use crate::{command::Command, disambiguation::Handling, keymap::Keymap};
+
+use super::style::Theme;
+
+const TITLE: &str = "settings";
+const MSG_REBINDING: &str = "press a key ...";
+const MSG_INVALID_CONFIG: &str = "config file is invalid, changes are not saved";
+
+pub struct State<'a> {
+    pub keymap: &'a Keymap,
+    pub rebinding: Option<Command>,
+    pub disambiguation: Handling,
+    /// Edited in place, the changes are seen immediately.
+    pub theme: &'a mut Theme,
+    /// Why the config file could not be read, changes are not saved then.
+    pub error: Option<&'a str>,
+}
+
+/// Actions requested by the user in the settings panel.
+pub enum Action {
+    Rebind(Command),
+    Clear(Command),
+    Reset,
+    SetDisambiguation(Handling),
+    /// Theme was changed and should be persisted.
+    SaveTheme,
+}
+
+pub fn draw_view_settings(ctx: &Context, open: &mut bool, state: State) -> Option<Action> {
+    let mut action = None;
+
+    Window::new(TITLE).open(open).show(ctx, |ui| {
+        if let Some(err) = state.error {
+            ui.colored_label(
+                ui.visuals().error_fg_color,
+                format!("{}: {}", MSG_INVALID_CONFIG, err),
+            );
+            ui.separator();
+        }
+
+        ui.heading("keymap");
+
+        Grid::new("keymap").striped(true).show(ui, |ui| {
+            Command::all().iter().for_each(|cmd| {
+                ui.label(cmd.title());
+
+                match state.rebinding == Some(*cmd) {
+                    true => ui.label(MSG_REBINDING),
+                    false => ui.monospace(state.keymap.describe(*cmd)),
+                };
+
+                if ui.button("rebind").clicked() {
+                    action = Some(Action::Rebind(*cmd));
+                }
+                if ui.button("clear").clicked() {
+                    action = Some(Action::Clear(*cmd));
+                }
+                ui.end_row();
+            });
+        });
+
+        ui.separator();
+        if ui.button("reset to defaults").clicked() {
+            action = Some(Action::Reset);
+        }
+
+        ui.separator();
+        ui.heading("disambiguation pages");
+        Handling::all().iter().for_each(|h| {
+            if ui.radio(state.disambiguation == *h, h.title()).clicked() {
+                action = Some(Action::SetDisambiguation(*h));
+            }
+        });
+
+        ui.separator();
+        ui.heading("theme");
+        ui.horizontal(|ui| {
+            [
+                ("dark", Theme::dark()),
+                ("light", Theme::light()),
+                ("color blind safe", Theme::color_blind()),
+            ]
+            .into_iter()
+            .for_each(|(name, preset)| {
+                if ui.selectable_label(*state.theme == preset, name).clicked() {
+                    *state.theme = preset;
+                    action = Some(Action::SaveTheme);
+                }
+            });
+        });
+        if ui
+            .checkbox(&mut state.theme.dark, "dark background")
+            .changed()
+        {
+            action = Some(Action::SaveTheme);
+        }
+        Grid::new("theme").show(ui, |ui| {
+            state
+                .theme
+                .palette_mut()
+                .into_iter()
+                .for_each(|(name, color)| {
+                    ui.label(name);
+                    if ui.color_edit_button_srgba(color).changed() {
+                        action = Some(Action::SaveTheme);
+                    }
+                    ui.end_row();
+                });
+        });
+    });
+
+    action
+}
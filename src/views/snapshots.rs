@@ -0,0 +1,197 @@
+use std::path::PathBuf;
+
+use egui::{Button, CollapsingHeader, ComboBox, Context, Grid, RichText, ScrollArea, Ui, Window};
+
+use crate::{
+    diff::{Change, Diff},
+    session::Session,
+};
+
+use super::style::change_color;
+
+const TITLE: &str = "snapshots";
+const MSG_RECRAWLING: &str = "re-crawling expanded articles ...";
+const MSG_NO_SESSIONS: &str = "no saved snapshots";
+
+/// What the older snapshot is compared with.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Against {
+    Snapshot(usize),
+    /// Graph of the shown tab.
+    Graph,
+    /// Links of the expanded articles retrieved again.
+    Recrawl,
+}
+
+pub struct State<'a> {
+    /// Saved sessions, the oldest first.
+    pub sessions: &'a [(PathBuf, Session)],
+    pub old: &'a mut Option<usize>,
+    pub against: &'a mut Against,
+    /// The shown tab has a graph which can be saved and compared.
+    pub has_graph: bool,
+    pub recrawling: bool,
+    pub diff: Option<&'a Diff>,
+    pub message: Option<&'a str>,
+}
+
+/// Actions requested by the user in the snapshots window.
+pub enum Action {
+    /// Save the graph of the shown tab.
+    Save,
+    /// Open the saved session in a new tab.
+    Open(usize),
+    Compare,
+    /// Show the compared sessions in one graph with the changes highlighted.
+    Show,
+}
+
+/// Draws saved snapshots of explorations and their comparison.
+pub fn draw_view_snapshots(ctx: &Context, open: &mut bool, state: State) -> Option<Action> {
+    let mut action = None;
+
+    Window::new(TITLE).open(open).show(ctx, |ui| {
+        if ui
+            .add_enabled(state.has_graph, Button::new("save shown graph"))
+            .clicked()
+        {
+            action = Some(Action::Save);
+        }
+        if let Some(message) = state.message {
+            ui.label(message);
+        }
+        ui.separator();
+
+        if state.sessions.is_empty() {
+            ui.label(MSG_NO_SESSIONS);
+            return;
+        }
+
+        ScrollArea::vertical()
+            .id_source("sessions")
+            .max_height(200.)
+            .show(ui, |ui| {
+                Grid::new("sessions").striped(true).show(ui, |ui| {
+                    state
+                        .sessions
+                        .iter()
+                        .enumerate()
+                        .for_each(|(i, (path, session))| {
+                            ui.label(session.title())
+                                .on_hover_text(path.display().to_string());
+                            ui.label(session.saved_at_label());
+                            ui.label(format!("{} articles", session.nodes.len()));
+                            if ui.button("open").clicked() {
+                                action = Some(Action::Open(i));
+                            }
+                            ui.end_row();
+                        });
+                });
+            });
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            let label = |i: usize| {
+                let session = &state.sessions[i].1;
+                format!("{} {}", session.title(), session.saved_at_label())
+            };
+
+            ComboBox::from_id_source("old")
+                .selected_text(state.old.map_or("older snapshot".to_string(), label))
+                .show_ui(ui, |ui| {
+                    (0..state.sessions.len()).for_each(|i| {
+                        ui.selectable_value(&mut *state.old, Some(i), label(i));
+                    });
+                });
+
+            ui.label("with");
+            let against_label = |against: Against| match against {
+                Against::Snapshot(i) => label(i),
+                Against::Graph => "shown graph".to_string(),
+                Against::Recrawl => "fresh re-crawl".to_string(),
+            };
+            ComboBox::from_id_source("against")
+                .selected_text(against_label(*state.against))
+                .show_ui(ui, |ui| {
+                    (0..state.sessions.len()).for_each(|i| {
+                        ui.selectable_value(&mut *state.against, Against::Snapshot(i), label(i));
+                    });
+                    ui.selectable_value(&mut *state.against, Against::Graph, "shown graph");
+                    ui.selectable_value(&mut *state.against, Against::Recrawl, "fresh re-crawl");
+                });
+
+            let enabled = state.old.is_some()
+                && !state.recrawling
+                && (state.has_graph || *state.against != Against::Graph);
+            if ui.add_enabled(enabled, Button::new("compare")).clicked() {
+                action = Some(Action::Compare);
+            }
+        });
+
+        if state.recrawling {
+            ui.label(MSG_RECRAWLING);
+            ui.spinner();
+            return;
+        }
+
+        let diff = match state.diff {
+            Some(diff) => diff,
+            None => return,
+        };
+
+        ui.separator();
+        if diff.is_empty() {
+            ui.label("no changes");
+            return;
+        }
+        if ui.button("show in graph").clicked() {
+            action = Some(Action::Show);
+        }
+
+        ScrollArea::vertical()
+            .id_source("diff")
+            .max_height(300.)
+            .show(ui, |ui| {
+                draw_changes(ui, "added articles", Change::Added, diff.added_nodes.iter());
+                draw_changes(
+                    ui,
+                    "removed articles",
+                    Change::Removed,
+                    diff.removed_nodes.iter(),
+                );
+                let link = |(from, to): &(String, String)| format!("{} -> {}", from, to);
+                draw_changes(
+                    ui,
+                    "added links",
+                    Change::Added,
+                    diff.added_links.iter().map(link),
+                );
+                draw_changes(
+                    ui,
+                    "removed links",
+                    Change::Removed,
+                    diff.removed_links.iter().map(link),
+                );
+            });
+    });
+
+    action
+}
+
+/// Draws the items in a collapsing list colored by their change.
+pub fn draw_changes<T: ToString>(
+    ui: &mut Ui,
+    title: &str,
+    change: Change,
+    items: impl Iterator<Item = T>,
+) {
+    let items = items.map(|item| item.to_string()).collect::<Vec<_>>();
+    let color = change_color(change);
+    CollapsingHeader::new(RichText::new(format!("{} ({})", title, items.len())).color(color))
+        .id_source(title)
+        .show(ui, |ui| {
+            items.iter().for_each(|item| {
+                ui.label(RichText::new(item).color(color));
+            });
+        });
+}
@@ -1,4 +1,10 @@
-use egui::{text::LayoutJob, Color32, FontFamily, FontId, TextFormat, WidgetText};
+use egui::{
+    ecolor::Hsva, text::LayoutJob, Color32, FontFamily, FontId, Stroke, Style, TextFormat, Visuals,
+    WidgetText,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{diff::Change, url};
 
 pub const COLOR_ACCENT: Color32 = Color32::from_rgb(128, 128, 255);
 pub const COLOR_SUB_ACCENT: Color32 = Color32::from_rgb(104, 138, 232);
@@ -7,11 +13,180 @@ pub const COLOR_LEFT_LOW: Color32 = Color32::from_rgb(148, 174, 179);
 pub const COLOR_RIGHT_LOW: Color32 = Color32::from_rgb(175, 145, 179);
 
 pub const COLOR_ERROR: Color32 = Color32::from_rgb(255, 64, 64);
+/// Color of red links on wikipedia.
+pub const COLOR_MISSING: Color32 = Color32::from_rgb(215, 51, 51);
+
+pub const COLOR_METRIC_LOW: Color32 = Color32::from_rgb(64, 64, 160);
+pub const COLOR_METRIC_HIGH: Color32 = Color32::from_rgb(255, 160, 64);
+
+pub const COLOR_ADDED: Color32 = Color32::from_rgb(64, 200, 96);
+pub const COLOR_REMOVED: Color32 = Color32::from_rgb(230, 64, 64);
 
 pub const FONT_INPUT: FontId = FontId::new(24., FontFamily::Monospace);
 
 pub const CURSOR_WIDTH: f32 = 5.;
 
+/// Colors of the app and of the node types. Persisted in the config.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    pub dark: bool,
+    #[serde(with = "hex")]
+    pub accent: Color32,
+    #[serde(with = "hex")]
+    pub sub_accent: Color32,
+    #[serde(with = "hex")]
+    pub article: Color32,
+    #[serde(with = "hex")]
+    pub category: Color32,
+    #[serde(with = "hex")]
+    pub missing: Color32,
+    #[serde(with = "hex")]
+    pub file: Color32,
+    #[serde(with = "hex")]
+    pub external: Color32,
+    #[serde(with = "hex")]
+    pub other: Color32,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Self {
+            dark: true,
+            accent: COLOR_ACCENT,
+            sub_accent: COLOR_SUB_ACCENT,
+            article: COLOR_SUB_ACCENT,
+            category: Color32::from_rgb(232, 198, 104),
+            missing: COLOR_MISSING,
+            file: COLOR_LEFT_LOW,
+            external: COLOR_RIGHT_LOW,
+            other: Color32::from_gray(140),
+        }
+    }
+
+    pub fn light() -> Self {
+        Self {
+            dark: false,
+            accent: Color32::from_rgb(64, 64, 208),
+            sub_accent: Color32::from_rgb(176, 196, 255),
+            article: Color32::from_rgb(51, 102, 204),
+            category: Color32::from_rgb(176, 128, 0),
+            missing: COLOR_MISSING,
+            file: Color32::from_rgb(64, 128, 128),
+            external: Color32::from_rgb(128, 64, 144),
+            other: Color32::from_gray(120),
+        }
+    }
+
+    /// Okabe-Ito palette which stays distinct with every kind of color blindness.
+    pub fn color_blind() -> Self {
+        Self {
+            dark: true,
+            accent: Color32::from_rgb(240, 228, 66),
+            sub_accent: Color32::from_rgb(0, 114, 178),
+            article: Color32::from_rgb(86, 180, 233),
+            category: Color32::from_rgb(230, 159, 0),
+            missing: Color32::from_rgb(213, 94, 0),
+            file: Color32::from_rgb(0, 158, 115),
+            external: Color32::from_rgb(204, 121, 167),
+            other: Color32::from_gray(153),
+        }
+    }
+
+    pub fn type_color(&self, t: &url::Type) -> Color32 {
+        match t {
+            url::Type::Article => self.article,
+            url::Type::Category => self.category,
+            url::Type::Missing => self.missing,
+            url::Type::File => self.file,
+            url::Type::ExternalArticle => self.external,
+            url::Type::Other => self.other,
+        }
+    }
+
+    /// Colors of the editable palette with their names.
+    pub fn palette_mut(&mut self) -> [(&'static str, &mut Color32); 8] {
+        [
+            ("accent", &mut self.accent),
+            ("sub accent", &mut self.sub_accent),
+            ("article", &mut self.article),
+            ("category", &mut self.category),
+            ("missing article", &mut self.missing),
+            ("file", &mut self.file),
+            ("external article", &mut self.external),
+            ("other", &mut self.other),
+        ]
+    }
+
+    pub fn apply(&self, style: &mut Style) {
+        style.visuals = match self.dark {
+            true => Visuals::dark(),
+            false => Visuals::light(),
+        };
+        style.visuals.selection.stroke = Stroke::new(1., self.accent);
+        style.visuals.selection.bg_fill = self.sub_accent;
+    }
+}
+
+/// Colors are stored as `#rrggbb` strings.
+mod hex {
+    use egui::Color32;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(color: &Color32, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&format!(
+            "#{:02x}{:02x}{:02x}",
+            color.r(),
+            color.g(),
+            color.b()
+        ))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Color32, D::Error> {
+        let s = String::deserialize(d)?;
+        Color32::from_hex(&s).map_err(|err| D::Error::custom(format!("{}: {:?}", s, err)))
+    }
+}
+
+/// Linear interpolation between two colors, `t` is in `0..=1`.
+pub fn lerp_color(from: Color32, to: Color32, t: f32) -> Color32 {
+    let lerp = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t.clamp(0., 1.)).round() as u8;
+    Color32::from_rgb(
+        lerp(from.r(), to.r()),
+        lerp(from.g(), to.g()),
+        lerp(from.b(), to.b()),
+    )
+}
+
+/// Distinct color for every community. Hues are spread with the golden ratio.
+pub fn community_color(id: usize) -> Color32 {
+    const GOLDEN_RATIO_CONJUGATE: f32 = 0.618_034;
+    let hue = (id as f32 * GOLDEN_RATIO_CONJUGATE).fract();
+    Hsva::new(hue, 0.6, 0.9, 1.).into()
+}
+
+/// Color of the entity class which stays the same while classes come and go.
+pub fn class_color(class: &str) -> Color32 {
+    const MAX_ID: usize = 1000;
+    let id = class
+        .bytes()
+        .fold(0usize, |h, b| h.wrapping_mul(31).wrapping_add(b as usize));
+    community_color(id % MAX_ID)
+}
+
+pub fn change_color(change: Change) -> Color32 {
+    match change {
+        Change::Added => COLOR_ADDED,
+        Change::Removed => COLOR_REMOVED,
+    }
+}
+
 pub fn header_accent(text: &str) -> impl Into<WidgetText> {
     let mut job = LayoutJob::default();
     job.append(
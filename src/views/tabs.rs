@@ -0,0 +1,80 @@
+use egui::{Response, Ui};
+
+const TITLE_MAX_CHARS: usize = 24;
+
+pub struct State {
+    pub titles: Vec<String>,
+    pub active: usize,
+    /// A node is dragged in the graph, it can be dropped on a tab to become a root there.
+    pub dragging: bool,
+}
+
+/// Actions requested by the user in the tab bar.
+pub enum Action {
+    Show(usize),
+    New,
+    Close(usize),
+    /// Merge the tab into the shown one.
+    Merge(usize),
+    /// The dragged node was dropped on the tab.
+    Drop(usize),
+    /// The dragged node was dropped on the new tab button.
+    DropNew,
+}
+
+/// Draws a tab for every exploration with a button opening a new one. Other tabs can be merged
+/// into the shown one from their context menu.
+pub fn draw_view_tabs(ui: &mut Ui, state: State) -> Option<Action> {
+    let mut action = None;
+
+    ui.horizontal(|ui| {
+        state.titles.iter().enumerate().for_each(|(i, title)| {
+            let short = title.chars().take(TITLE_MAX_CHARS).collect::<String>();
+            let resp = ui
+                .selectable_label(i == state.active, short)
+                .on_hover_text(title);
+            if resp.clicked() {
+                action = Some(Action::Show(i));
+            }
+            if i != state.active && dropped(ui, &resp, state.dragging) {
+                action = Some(Action::Drop(i));
+            }
+            resp.context_menu(|ui| {
+                if i != state.active && ui.button("merge into shown tab").clicked() {
+                    action = Some(Action::Merge(i));
+                    ui.close_menu();
+                }
+                if ui.button("close").clicked() {
+                    action = Some(Action::Close(i));
+                    ui.close_menu();
+                }
+            });
+
+            if ui.small_button("x").on_hover_text("close tab").clicked() {
+                action = Some(Action::Close(i));
+            }
+            ui.separator();
+        });
+
+        let resp = ui.button("+").on_hover_text("new tab");
+        if resp.clicked() {
+            action = Some(Action::New);
+        }
+        if dropped(ui, &resp, state.dragging) {
+            action = Some(Action::DropNew);
+        }
+    });
+
+    action
+}
+
+/// Highlights the drop target under the dragged node and tells if the node was released there.
+fn dropped(ui: &Ui, resp: &Response, dragging: bool) -> bool {
+    if !dragging || !ui.rect_contains_pointer(resp.rect) {
+        return false;
+    }
+
+    ui.painter()
+        .rect_stroke(resp.rect, 2., ui.visuals().selection.stroke);
+    ui.input(|i| i.pointer.any_released())
+}
@@ -0,0 +1,103 @@
+use egui::{Button, Context, DragValue, ScrollArea, Slider, Window};
+
+use crate::{
+    diff::Change,
+    revision::{Timeline, MAX_REVISIONS},
+    url::Url,
+};
+
+use super::snapshots::draw_changes;
+
+const TITLE: &str = "revision timeline";
+const MSG_LOADING: &str = "retrieving revisions ...";
+
+pub struct State<'a> {
+    /// Title of the selected article, its history can not be loaded without it.
+    pub article: Option<String>,
+    /// Number of the latest revisions to load.
+    pub limit: &'a mut usize,
+    pub loading: bool,
+    /// Timeline shown in the tab.
+    pub timeline: Option<&'a mut Timeline>,
+    /// Time of the frame in seconds.
+    pub time: f64,
+}
+
+/// Draws the revision slider of the timeline shown in the tab with the links the shown revision
+/// added and removed. Returns true if the user requested the history of the selected article.
+pub fn draw_view_timeline(ctx: &Context, open: &mut bool, state: State) -> bool {
+    let mut load = false;
+
+    Window::new(TITLE).open(open).show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            ui.add(DragValue::new(&mut *state.limit).clamp_range(1..=MAX_REVISIONS));
+            ui.label("latest revisions of");
+
+            let label = match &state.article {
+                Some(article) => format!("load {}", article),
+                None => "select an article".to_string(),
+            };
+            let enabled = state.article.is_some() && !state.loading;
+            if ui.add_enabled(enabled, Button::new(label)).clicked() {
+                load = true;
+            }
+        });
+
+        if state.loading {
+            ui.label(MSG_LOADING);
+            ui.spinner();
+            return;
+        }
+
+        let timeline = match state.timeline {
+            Some(timeline) => timeline,
+            None => return,
+        };
+
+        ui.separator();
+        ui.label(format!(
+            "{}: {} revisions",
+            timeline.article.val_for_type(),
+            timeline.revisions.len()
+        ));
+
+        ui.horizontal(|ui| {
+            let playing = timeline.playing.is_some();
+            if ui.button(if playing { "pause" } else { "play" }).clicked() {
+                match playing {
+                    true => timeline.playing = None,
+                    false => timeline.play(state.time),
+                }
+            }
+
+            let last = timeline.revisions.len() - 1;
+            if ui
+                .add(Slider::new(&mut timeline.position, 0..=last).text("revision"))
+                .changed()
+            {
+                timeline.playing = None;
+            }
+        });
+
+        let shown = timeline.shown();
+        ui.label(format!(
+            "{} of {}",
+            shown.id,
+            humantime::format_rfc3339_seconds(shown.at)
+        ));
+
+        let (added, removed) = timeline.changes();
+        let title = |url: &&Url| url.val_for_type();
+        ScrollArea::vertical().max_height(300.).show(ui, |ui| {
+            draw_changes(ui, "added links", Change::Added, added.iter().map(title));
+            draw_changes(
+                ui,
+                "removed links",
+                Change::Removed,
+                removed.iter().map(title),
+            );
+        });
+    });
+
+    load
+}
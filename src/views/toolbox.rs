@@ -1,8 +1,15 @@
-use egui::{Response, ScrollArea, TextEdit, Ui};
-use egui_graphs::Graph;
-use petgraph::{stable_graph::NodeIndex, Directed};
+use std::time::SystemTime;
 
-use crate::{node::Node, utils};
+use egui::{load::SizedTexture, Grid, Image, Response, ScrollArea, TextEdit, TextureHandle, Ui};
+use petgraph::stable_graph::NodeIndex;
+
+use crate::{
+    edge::Edge,
+    entities::Entity,
+    graph::Graph,
+    metrics::{Metric, NodeMetrics},
+    revision, utils,
+};
 
 use super::style::header_accent;
 
@@ -12,9 +19,19 @@ const MSG_SCRAPPING: &str = "scrapping links ...";
 pub struct State<'a> {
     pub loading: bool,
     pub spacing: f32,
-    pub g: &'a Graph<Node, (), Directed>,
+    pub g: &'a Graph,
     pub selected_node: Option<NodeIndex>,
     pub selected_node_root: Option<NodeIndex>,
+    pub selected_node_metrics: Option<NodeMetrics>,
+    pub selected_node_summary: Option<&'a str>,
+    pub selected_node_thumbnail: Option<&'a TextureHandle>,
+    pub selected_node_entity: Option<&'a Entity>,
+    /// Expanding an article retrieves its categories instead of its links.
+    pub category_mode: bool,
+    /// Articles are explored as they were at the time.
+    pub as_of: Option<SystemTime>,
+    /// Edge from the selected node root to the selected node.
+    pub selected_edge: Option<&'a Edge>,
 }
 
 /// Draws toolbox view and returns response from `get links` button if it was displayed.
@@ -30,6 +47,9 @@ pub fn draw_view_toolbox(ui: &mut Ui, state: &State) -> Option<Response> {
 
             ui.label(format!("urls: {}", state.g.g.node_count()));
             ui.label(format!("connections: {}", state.g.g.edge_count()));
+            if let Some(as_of) = state.as_of {
+                ui.label(format!("as of: {}", revision::date_label(as_of)));
+            }
 
             match state.loading {
                 true => {
@@ -72,16 +92,77 @@ pub fn draw_selected_node(ui: &mut Ui, state: &State) -> Option<Response> {
         });
     });
 
+    if let Some(media) = node.url().media() {
+        ui.label(format!("media: {:?}", media).to_lowercase());
+    }
+
+    if let Some(thumbnail) = state.selected_node_thumbnail {
+        ui.add_space(state.spacing);
+        ui.add(Image::new(SizedTexture::from_handle(thumbnail)).shrink_to_fit());
+        ui.add_space(state.spacing);
+    }
+
+    if let Some(summary) = state.selected_node_summary.filter(|s| !s.is_empty()) {
+        ui.add_space(state.spacing);
+        ui.label(summary);
+        ui.add_space(state.spacing);
+    }
+
+    if let Some(entity) = state.selected_node_entity {
+        ui.label(format!("wikidata: {}", entity.qid));
+        Grid::new("selected_node_entity").show(ui, |ui| {
+            entity.statements.iter().for_each(|s| {
+                ui.label(s.property);
+                ui.label(s.values.join(", "));
+                ui.end_row();
+            });
+        });
+    }
+
+    if let Some(edge) = state.selected_edge {
+        ui.label(format!("linked {} times", edge.count()));
+        edge.occurrences().iter().for_each(|o| {
+            ui.label(format!(
+                "{}: {}",
+                o.section.as_deref().unwrap_or("lead"),
+                o.anchor
+            ));
+        });
+    }
+
+    if let Some(id) = node.revision() {
+        ui.label(format!("revision: {}", id));
+    }
+
+    if let Some(community) = node.community() {
+        ui.label(format!("community: {}", community));
+    }
+
+    if let Some(metrics) = state.selected_node_metrics {
+        Grid::new("selected_node_metrics").show(ui, |ui| {
+            Metric::all().iter().for_each(|m| {
+                ui.label(m.title());
+                ui.label(metrics.format(*m));
+                ui.end_row();
+            });
+        });
+    }
+
     if ui.button("copy").clicked() {
         todo!()
     };
 
     if ui.button("open").clicked() {
-        utils::url::open_url(node.url().val());
+        match node.revision() {
+            Some(id) => utils::url::open_url(&revision::url(node.url(), id)),
+            None => utils::url::open_url(node.url().val()),
+        }
     };
 
-    match node.url().url_type() {
-        crate::url::Type::Article => Some(ui.button("get links")),
+    match (node.url().url_type(), state.category_mode) {
+        (crate::url::Type::Category, _) => Some(ui.button("get members")),
+        (crate::url::Type::Article, true) => Some(ui.button("get categories")),
+        (crate::url::Type::Article, false) => Some(ui.button("get links")),
         _ => None,
     }
 }
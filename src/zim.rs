@@ -0,0 +1,472 @@
+use std::{
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use log::info;
+
+use crate::{
+    link::Link,
+    link_source::{Error, LinkSource},
+    url::Url,
+    url_retriever::{is_disambiguation, parse_links, parse_summary},
+};
+
+const MAGIC: u32 = 72173914;
+const HEADER_SIZE: usize = 80;
+
+const MIME_REDIRECT: u16 = 0xffff;
+/// Link targets and deleted entries do not point to any content.
+const MIME_NO_CONTENT: u16 = 0xfffd;
+
+const COMPRESSION_MASK: u8 = 0x0f;
+const EXTENDED_CLUSTER: u8 = 0x10;
+const COMPRESSION_DEFAULT: u8 = 0;
+const COMPRESSION_NONE: u8 = 1;
+const COMPRESSION_XZ: u8 = 4;
+const COMPRESSION_ZSTD: u8 = 5;
+
+/// Articles are in `A` namespace in the old archives and in `C` namespace in the new ones.
+const ARTICLE_NAMESPACES: [u8; 2] = [b'C', b'A'];
+
+/// Redirect chains longer than this are considered broken.
+const MAX_REDIRECTS: usize = 10;
+
+/// Kiwix ZIM archive reader.
+///
+/// Only the parts needed to get an article by its path are read: header, path pointers,
+/// directory entries and clusters.
+pub struct Zim {
+    file: Mutex<File>,
+    entry_count: u32,
+    path_ptr_pos: u64,
+    cluster_count: u32,
+    cluster_ptr_pos: u64,
+    checksum_pos: u64,
+}
+
+struct Entry {
+    namespace: u8,
+    path: String,
+    content: Content,
+}
+
+enum Content {
+    Blob { cluster: u32, blob: u32 },
+    Redirect(u32),
+    None,
+}
+
+impl Zim {
+    pub fn open(path: &Path) -> Result<Self, Error> {
+        let mut file = File::open(path)?;
+        let mut header = [0; HEADER_SIZE];
+        file.read_exact(&mut header)?;
+
+        if u32_at(&header, 0) != MAGIC {
+            return Err(Error::Parse("not a zim archive".to_string()));
+        }
+
+        let zim = Self {
+            entry_count: u32_at(&header, 24),
+            cluster_count: u32_at(&header, 28),
+            path_ptr_pos: u64_at(&header, 32),
+            cluster_ptr_pos: u64_at(&header, 48),
+            checksum_pos: u64_at(&header, 72),
+            file: Mutex::new(file),
+        };
+        info!(
+            "opened zim archive {} with {} entries",
+            path.display(),
+            zim.entry_count
+        );
+
+        Ok(zim)
+    }
+
+    /// Gets html of the article following redirects. Path is the article title with
+    /// underscores, as in the wikipedia url.
+    pub fn article(&self, path: &str) -> Result<Option<String>, Error> {
+        let mut entry = match self.find(path)? {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+
+        for _ in 0..MAX_REDIRECTS {
+            match entry.content {
+                Content::Blob { cluster, blob } => {
+                    let data = self.blob(cluster, blob)?;
+                    return Ok(Some(String::from_utf8_lossy(&data).to_string()));
+                }
+                Content::Redirect(idx) => entry = self.entry(idx)?,
+                Content::None => return Ok(None),
+            }
+        }
+
+        Err(Error::Parse(format!("redirect loop for {}", path)))
+    }
+
+    /// Binary search over the path pointers which are sorted by namespace and path.
+    fn find(&self, path: &str) -> Result<Option<Entry>, Error> {
+        for namespace in ARTICLE_NAMESPACES {
+            let key = (namespace, path.as_bytes());
+            let (mut lo, mut hi) = (0, self.entry_count);
+            while lo < hi {
+                let mid = lo + (hi - lo) / 2;
+                let entry = self.entry(mid)?;
+                match (entry.namespace, entry.path.as_bytes()).cmp(&key) {
+                    std::cmp::Ordering::Less => lo = mid + 1,
+                    std::cmp::Ordering::Greater => hi = mid,
+                    std::cmp::Ordering::Equal => return Ok(Some(entry)),
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn entry(&self, idx: u32) -> Result<Entry, Error> {
+        let pos = self.read_u64(self.path_ptr_pos + idx as u64 * 8)?;
+
+        let mut file = self.file.lock().unwrap();
+        file.seek(SeekFrom::Start(pos))?;
+        let mut head = [0; 8];
+        file.read_exact(&mut head)?;
+
+        let mime = u16::from_le_bytes([head[0], head[1]]);
+        let namespace = head[3];
+        let content = match mime {
+            MIME_REDIRECT => {
+                let mut buf = [0; 4];
+                file.read_exact(&mut buf)?;
+                Content::Redirect(u32_at(&buf, 0))
+            }
+            mime if mime >= MIME_NO_CONTENT => Content::None,
+            _ => {
+                let mut buf = [0; 8];
+                file.read_exact(&mut buf)?;
+                Content::Blob {
+                    cluster: u32_at(&buf, 0),
+                    blob: u32_at(&buf, 4),
+                }
+            }
+        };
+
+        let mut path = vec![];
+        let mut b = [0; 1];
+        loop {
+            file.read_exact(&mut b)?;
+            if b[0] == 0 {
+                break;
+            }
+            path.push(b[0]);
+        }
+
+        Ok(Entry {
+            namespace,
+            path: String::from_utf8_lossy(&path).to_string(),
+            content,
+        })
+    }
+
+    fn blob(&self, cluster: u32, blob: u32) -> Result<Vec<u8>, Error> {
+        if cluster >= self.cluster_count {
+            return Err(Error::Parse("cluster out of range".to_string()));
+        }
+
+        let start = self.read_u64(self.cluster_ptr_pos + cluster as u64 * 8)?;
+        let end = match cluster + 1 < self.cluster_count {
+            true => self.read_u64(self.cluster_ptr_pos + (cluster as u64 + 1) * 8)?,
+            false => self.checksum_pos,
+        };
+
+        // a truncated or corrupt archive can have clusters without even the info byte
+        if end <= start {
+            return Err(Error::Parse(format!("cluster {} is empty", cluster)));
+        }
+        let mut raw = vec![0; (end - start) as usize];
+        {
+            let mut file = self.file.lock().unwrap();
+            file.seek(SeekFrom::Start(start))?;
+            file.read_exact(&mut raw)?;
+        }
+
+        let (info, raw) = (raw[0], &raw[1..]);
+        let data = match info & COMPRESSION_MASK {
+            COMPRESSION_DEFAULT | COMPRESSION_NONE => raw.to_vec(),
+            COMPRESSION_XZ => {
+                let mut data = vec![];
+                xz2::read::XzDecoder::new(raw).read_to_end(&mut data)?;
+                data
+            }
+            COMPRESSION_ZSTD => zstd::decode_all(raw)?,
+            c => return Err(Error::Parse(format!("unknown compression {}", c))),
+        };
+
+        let offset_size = match info & EXTENDED_CLUSTER != 0 {
+            true => 8,
+            false => 4,
+        };
+        let offset = |i: usize| -> Result<usize, Error> {
+            let at = i * offset_size;
+            if at + offset_size > data.len() {
+                return Err(Error::Parse("blob out of range".to_string()));
+            }
+            Ok(match offset_size {
+                8 => u64_at(&data, at) as usize,
+                _ => u32_at(&data, at) as usize,
+            })
+        };
+
+        let (from, to) = (offset(blob as usize)?, offset(blob as usize + 1)?);
+        if from > to || to > data.len() {
+            return Err(Error::Parse("blob out of range".to_string()));
+        }
+
+        Ok(data[from..to].to_vec())
+    }
+
+    fn read_u64(&self, pos: u64) -> Result<u64, Error> {
+        let mut file = self.file.lock().unwrap();
+        file.seek(SeekFrom::Start(pos))?;
+        let mut buf = [0; 8];
+        file.read_exact(&mut buf)?;
+        Ok(u64::from_le_bytes(buf))
+    }
+}
+
+/// Reads articles from a local ZIM archive instead of wikipedia.
+///
+/// Urls stay wikipedia urls, only their content comes from the archive.
+pub struct ZimSource {
+    zim: Arc<Zim>,
+}
+
+impl ZimSource {
+    pub fn new(zim: Zim) -> Self {
+        Self { zim: Arc::new(zim) }
+    }
+
+    async fn html(&self, url: &Url) -> Result<String, Error> {
+        let path = url.val_for_type().replace(' ', "_");
+        let zim = self.zim.clone();
+        tokio::task::spawn_blocking(move || zim.article(&path))
+            .await
+            .map_err(io::Error::from)??
+            .ok_or_else(|| Error::NotFound(url.val().to_string()))
+    }
+}
+
+/// ZIM archives hold a single revision of every article in a single language, without
+/// backlinks, categories, wikidata and most media files, so only links, summaries and
+/// disambiguation pages are provided.
+#[async_trait]
+impl LinkSource for ZimSource {
+    async fn links(&self, url: &Url) -> Result<Vec<Link>, Error> {
+        let html = self.html(url).await?;
+        let protocol = format!("{}://", url.scheme());
+        Ok(parse_links(&html, &protocol, url.host()))
+    }
+
+    async fn summary(&self, url: &Url) -> Result<String, Error> {
+        let html = self.html(url).await?;
+        parse_summary(&html).ok_or_else(|| Error::NotFound(url.val().to_string()))
+    }
+
+    /// Page properties are not stored, so every article is checked for the disambiguation
+    /// markers.
+    async fn disambiguations(&self, urls: &[Url]) -> Result<Vec<Url>, Error> {
+        let mut pages = vec![];
+        for url in urls {
+            match self.html(url).await {
+                Ok(html) if is_disambiguation(&html) => pages.push(url.clone()),
+                Ok(_) | Err(Error::NotFound(_)) => (),
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(pages)
+    }
+}
+
+fn u32_at(buf: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(buf[at..at + 4].try_into().unwrap())
+}
+
+fn u64_at(buf: &[u8], at: usize) -> u64 {
+    u64::from_le_bytes(buf[at..at + 8].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::PathBuf};
+
+    use super::*;
+
+    /// Entry of the archive written by the tests, entries have to be sorted by namespace and path.
+    struct TestEntry {
+        namespace: u8,
+        path: &'static str,
+        content: Content,
+    }
+
+    /// Writes an archive with uncompressed clusters of the blobs.
+    fn write_archive(name: &str, entries: &[TestEntry], clusters: &[&[&str]]) -> PathBuf {
+        let dirents = entries
+            .iter()
+            .map(|e| {
+                let mut dirent = vec![];
+                let mime = match e.content {
+                    Content::Blob { .. } => 0,
+                    Content::Redirect(_) => MIME_REDIRECT,
+                    Content::None => MIME_NO_CONTENT,
+                };
+                dirent.extend(mime.to_le_bytes());
+                dirent.extend([0, e.namespace, 0, 0, 0, 0]);
+                match e.content {
+                    Content::Blob { cluster, blob } => {
+                        dirent.extend(cluster.to_le_bytes());
+                        dirent.extend(blob.to_le_bytes());
+                    }
+                    Content::Redirect(idx) => dirent.extend(idx.to_le_bytes()),
+                    Content::None => (),
+                }
+                dirent.extend(e.path.as_bytes());
+                dirent.extend([0, 0]);
+                dirent
+            })
+            .collect::<Vec<_>>();
+        let clusters = clusters
+            .iter()
+            .map(|blobs| {
+                let mut offsets = vec![];
+                let mut offset = (blobs.len() as u32 + 1) * 4;
+                offsets.extend(offset.to_le_bytes());
+                blobs.iter().for_each(|b| {
+                    offset += b.len() as u32;
+                    offsets.extend(offset.to_le_bytes());
+                });
+                let mut cluster = vec![COMPRESSION_NONE];
+                cluster.extend(offsets);
+                blobs.iter().for_each(|b| cluster.extend(b.as_bytes()));
+                cluster
+            })
+            .collect::<Vec<_>>();
+
+        let path_ptr_pos = HEADER_SIZE as u64;
+        let mut pos = path_ptr_pos + dirents.len() as u64 * 8;
+        let mut path_ptrs = vec![];
+        dirents.iter().for_each(|d| {
+            path_ptrs.extend(pos.to_le_bytes());
+            pos += d.len() as u64;
+        });
+        let cluster_ptr_pos = pos;
+        pos += clusters.len() as u64 * 8;
+        let mut cluster_ptrs = vec![];
+        clusters.iter().for_each(|c| {
+            cluster_ptrs.extend(pos.to_le_bytes());
+            pos += c.len() as u64;
+        });
+
+        let mut header = vec![0; HEADER_SIZE];
+        header[0..4].copy_from_slice(&MAGIC.to_le_bytes());
+        header[24..28].copy_from_slice(&(dirents.len() as u32).to_le_bytes());
+        header[28..32].copy_from_slice(&(clusters.len() as u32).to_le_bytes());
+        header[32..40].copy_from_slice(&path_ptr_pos.to_le_bytes());
+        header[48..56].copy_from_slice(&cluster_ptr_pos.to_le_bytes());
+        header[72..80].copy_from_slice(&pos.to_le_bytes());
+
+        let mut archive = header;
+        archive.extend(path_ptrs);
+        dirents.into_iter().for_each(|d| archive.extend(d));
+        archive.extend(cluster_ptrs);
+        clusters.into_iter().for_each(|c| archive.extend(c));
+
+        let path = env::temp_dir().join(format!("wikilinks-{}-{}.zim", name, std::process::id()));
+        fs::write(&path, archive).unwrap();
+        path
+    }
+
+    fn entry(namespace: u8, path: &'static str, content: Content) -> TestEntry {
+        TestEntry {
+            namespace,
+            path,
+            content,
+        }
+    }
+
+    fn open(path: PathBuf) -> Zim {
+        let zim = Zim::open(&path).unwrap();
+        fs::remove_file(path).unwrap();
+        zim
+    }
+
+    #[test]
+    fn reads_articles_and_follows_redirects() {
+        let zim = open(write_archive(
+            "articles",
+            &[
+                entry(
+                    b'C',
+                    "Euler",
+                    Content::Blob {
+                        cluster: 1,
+                        blob: 0,
+                    },
+                ),
+                entry(b'C', "Graph", Content::Redirect(2)),
+                entry(
+                    b'C',
+                    "Graph_theory",
+                    Content::Blob {
+                        cluster: 0,
+                        blob: 1,
+                    },
+                ),
+                entry(b'C', "Removed", Content::None),
+            ],
+            &[&["<p>main</p>", "<p>graphs</p>"], &["<p>euler</p>"]],
+        ));
+
+        assert_eq!(zim.entry_count, 4);
+        assert_eq!(zim.cluster_count, 2);
+        assert_eq!(zim.article("Graph").unwrap().unwrap(), "<p>graphs</p>");
+        assert_eq!(zim.article("Euler").unwrap().unwrap(), "<p>euler</p>");
+        assert_eq!(zim.article("Removed").unwrap(), None);
+        assert_eq!(zim.article("Missing").unwrap(), None);
+    }
+
+    #[test]
+    fn rejects_corrupt_archives() {
+        let path = env::temp_dir().join(format!("wikilinks-magic-{}.zim", std::process::id()));
+        fs::write(&path, [0; HEADER_SIZE]).unwrap();
+        let opened = Zim::open(&path);
+        fs::remove_file(path).unwrap();
+        assert!(matches!(opened, Err(Error::Parse(_))));
+
+        let path = write_archive(
+            "empty_cluster",
+            &[entry(
+                b'A',
+                "Graph",
+                Content::Blob {
+                    cluster: 0,
+                    blob: 0,
+                },
+            )],
+            &[&["<p>graphs</p>"]],
+        );
+        // the archive ends where its only cluster starts
+        let mut archive = fs::read(&path).unwrap();
+        let cluster_ptr_pos = u64_at(&archive, 48) as usize;
+        let start = archive[cluster_ptr_pos..cluster_ptr_pos + 8].to_vec();
+        archive[72..80].copy_from_slice(&start);
+        fs::write(&path, archive).unwrap();
+
+        let zim = open(path);
+        assert!(matches!(zim.article("Graph"), Err(Error::Parse(_))));
+        assert!(matches!(zim.blob(1, 0), Err(Error::Parse(_))));
+    }
+}